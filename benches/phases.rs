@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use novel_set_neat::benchmarks::{Mutation, Novelty, Reproduction};
+
+fn mutation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mutation");
+    for &growth in &[0, 50, 200] {
+        let mut mutation = Mutation::new(100, growth);
+        group.bench_with_input(BenchmarkId::from_parameter(growth), &growth, |b, _| {
+            b.iter_batched(
+                || mutation.individuals(),
+                |mut individuals| {
+                    mutation.run(&mut individuals);
+                    individuals
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn novelty(c: &mut Criterion) {
+    let mut group = c.benchmark_group("novelty");
+    for &archive_size in &[0, 500, 2000] {
+        let novelty = Novelty::new(100, archive_size, 16);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(archive_size),
+            &archive_size,
+            |b, _| b.iter(|| novelty.run()),
+        );
+    }
+    group.finish();
+}
+
+fn reproduction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reproduction");
+    group.sample_size(20);
+    for &population_size in &[50, 200, 800] {
+        let reproduction = Reproduction::new(population_size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(population_size),
+            &population_size,
+            |b, _| {
+                b.iter_batched(
+                    || reproduction.population(),
+                    |population| reproduction.run(population),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(phases, mutation, novelty, reproduction);
+criterion_main!(phases);
@@ -0,0 +1,147 @@
+// workloads of the core phases, driven by criterion in benches/phases.rs,
+// they live in the crate to reach Genes, Behaviors and Population
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    error::NeatError,
+    genes::IdGenerator,
+    individual::behavior::{Behavior, Behaviors},
+    population::Population,
+    utility::{rng::NeatRng, statistics::PopulationStatistics},
+    Individual, Parameters, Progress,
+};
+
+const SEED: u64 = 42;
+
+fn parameters(population_size: usize) -> Parameters {
+    Parameters::builder()
+        .seed(SEED)
+        .population_size(population_size)
+        .survival_rate(0.2)
+        .dimensions(4, 2)
+        .novelty_nearest_neighbors(15)
+        .mutation(|mutation| {
+            mutation.new_node_chance = 0.2;
+            mutation.new_connection_chance = 0.5;
+        })
+        .build()
+}
+
+// structural and weight mutation of evolved, i.e. non-minimal, genomes
+pub struct Mutation {
+    parameters: Parameters,
+    individuals: Vec<Individual>,
+    rng: NeatRng,
+    id_gen: IdGenerator,
+}
+
+impl Mutation {
+    // genomes are grown by given number of mutations before measuring
+    pub fn new(genome_count: usize, growth: usize) -> Self {
+        let parameters = parameters(genome_count);
+        let mut rng = NeatRng::new(SEED, parameters.mutation.weight_perturbation_std_dev);
+        let mut id_gen = IdGenerator::default();
+
+        let initial = Individual::initial(&mut id_gen, &parameters);
+        let individuals = (0..genome_count)
+            .map(|_| {
+                let mut individual = initial.clone();
+                individual.init(&mut rng, &parameters);
+                for _ in 0..growth {
+                    individual.mutate(&mut rng, &mut id_gen, &parameters);
+                }
+                individual
+            })
+            .collect();
+
+        Mutation {
+            parameters,
+            individuals,
+            rng,
+            id_gen,
+        }
+    }
+
+    // fresh copies, so every measured run starts from the same genomes
+    pub fn individuals(&self) -> Vec<Individual> {
+        self.individuals.clone()
+    }
+
+    pub fn run(&mut self, individuals: &mut [Individual]) {
+        for individual in individuals {
+            individual.mutate(&mut self.rng, &mut self.id_gen, &self.parameters);
+        }
+    }
+}
+
+// novelty of a population against an archive of given size
+pub struct Novelty {
+    behaviors: Vec<Behavior>,
+    population_size: usize,
+    nearest_neighbors: usize,
+}
+
+impl Novelty {
+    pub fn new(population_size: usize, archive_size: usize, behavior_length: usize) -> Self {
+        let mut rng = SmallRng::seed_from_u64(SEED);
+        let behaviors = (0..population_size + archive_size)
+            .map(|_| {
+                Behavior::from(
+                    (0..behavior_length)
+                        .map(|_| rng.gen::<f64>())
+                        .collect::<Vec<f64>>(),
+                )
+            })
+            .collect();
+
+        Novelty {
+            behaviors,
+            population_size,
+            nearest_neighbors: 15,
+        }
+    }
+
+    pub fn run(&self) -> Vec<f64> {
+        let neighborhoods = Behaviors::from(self.behaviors.iter().collect::<Vec<&Behavior>>())
+            .with_queries(self.population_size)
+            .compute_neighborhoods();
+        Behaviors::novelty(&neighborhoods, self.nearest_neighbors)
+    }
+}
+
+// population about to reproduce, see Reproduction::population
+pub struct Evaluated(Population);
+
+// fitness and novelty assignment, selection and offspring creation of one generation
+pub struct Reproduction {
+    parameters: Parameters,
+    evaluations: Vec<(f64, Vec<f64>)>,
+}
+
+impl Reproduction {
+    pub fn new(population_size: usize) -> Self {
+        let mut rng = SmallRng::seed_from_u64(SEED);
+        let evaluations = (0..population_size)
+            .map(|_| (rng.gen::<f64>(), vec![rng.gen::<f64>(), rng.gen::<f64>()]))
+            .collect();
+
+        Reproduction {
+            parameters: parameters(population_size),
+            evaluations,
+        }
+    }
+
+    pub fn population(&self) -> Evaluated {
+        Evaluated(Population::new(&self.parameters))
+    }
+
+    pub fn run(&self, population: Evaluated) -> Result<PopulationStatistics, NeatError> {
+        let Evaluated(mut population) = population;
+        let progress: Vec<Progress> = self
+            .evaluations
+            .iter()
+            .map(|(fitness, behavior)| Progress::new(*fitness, behavior.clone()))
+            .collect();
+        population.next_generation(&self.parameters, &progress)
+    }
+}
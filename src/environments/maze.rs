@@ -0,0 +1,175 @@
+use std::f64::consts::PI;
+
+use crate::{Controller, Individual, ParametersBuilder, Progress};
+
+// rangefinders, goal radar slices and a constant bias
+pub const INPUT_DIMENSION: usize = 10;
+// turning rate and speed, both from [-1, 1]
+pub const OUTPUT_DIMENSION: usize = 2;
+
+pub const MAX_STEPS: usize = 400;
+
+// unit square with a wall between start and goal, heading straight for the goal ends in the
+// dead end below it, i.e. fitness is deceptive and novelty of the final position is not
+const WALLS: [((f64, f64), (f64, f64)); 5] = [
+    ((0.0, 0.0), (1.0, 0.0)),
+    ((1.0, 0.0), (1.0, 1.0)),
+    ((1.0, 1.0), (0.0, 1.0)),
+    ((0.0, 1.0), (0.0, 0.0)),
+    ((0.0, 0.5), (0.8, 0.5)),
+];
+const START: (f64, f64) = (0.1, 0.1);
+const GOAL: (f64, f64) = (0.1, 0.9);
+const GOAL_RADIUS: f64 = 0.05;
+
+const ROBOT_RADIUS: f64 = 0.02;
+const MAX_SPEED: f64 = 0.01;
+const MAX_TURN: f64 = 0.1;
+const RANGEFINDER_RANGE: f64 = 0.5;
+// relative to the heading
+const RANGEFINDER_ANGLES: [f64; 5] = [-PI / 2.0, -PI / 4.0, 0.0, PI / 4.0, PI / 2.0];
+
+#[derive(Debug, Clone, Copy)]
+struct Robot {
+    position: (f64, f64),
+    heading: f64,
+}
+
+impl Robot {
+    // normalized rangefinders, the radar slice of 90 degrees facing the goal lights up
+    fn observe(&self) -> Vec<f64> {
+        let mut observation: Vec<f64> = RANGEFINDER_ANGLES
+            .iter()
+            .map(|angle| cast(self.position, self.heading + angle) / RANGEFINDER_RANGE)
+            .collect();
+
+        let bearing = (GOAL.1 - self.position.1).atan2(GOAL.0 - self.position.0) - self.heading;
+        // front, left, back, right
+        let slice = ((bearing + PI / 4.0).rem_euclid(2.0 * PI) / (PI / 2.0)) as usize % 4;
+        observation.extend((0..4).map(|index| if index == slice { 1.0 } else { 0.0 }));
+
+        observation.push(1.0);
+        observation
+    }
+
+    fn step(&mut self, turn: f64, speed: f64) {
+        self.heading += MAX_TURN * turn;
+        let position = (
+            self.position.0 + MAX_SPEED * speed * self.heading.cos(),
+            self.position.1 + MAX_SPEED * speed * self.heading.sin(),
+        );
+
+        // robots stop at walls
+        if WALLS
+            .iter()
+            .all(|&(start, end)| distance_to_segment(position, start, end) > ROBOT_RADIUS)
+        {
+            self.position = position;
+        }
+    }
+}
+
+pub fn parameters() -> ParametersBuilder {
+    ParametersBuilder::default()
+        .population_size(250)
+        .survival_rate(0.2)
+        .dimensions(INPUT_DIMENSION, OUTPUT_DIMENSION)
+        .novelty_nearest_neighbors(15)
+}
+
+// fitness is closeness to the goal, behavior the final position
+pub fn progress(individual: &Individual) -> Progress {
+    let mut controller = Controller::from(individual);
+    let mut robot = Robot {
+        position: START,
+        heading: 0.0,
+    };
+
+    for _ in 0..MAX_STEPS {
+        let outputs = controller.activate(&robot.observe());
+        robot.step(outputs[0].clamp(-1.0, 1.0), outputs[1].clamp(-1.0, 1.0));
+
+        if distance(robot.position, GOAL) < GOAL_RADIUS {
+            break;
+        }
+    }
+
+    let remaining = distance(robot.position, GOAL);
+    let progress = Progress::new(
+        1.0 - remaining / 2f64.sqrt(),
+        vec![robot.position.0, robot.position.1],
+    );
+
+    if remaining < GOAL_RADIUS {
+        progress.solved(individual.clone())
+    } else {
+        progress
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn distance_to_segment(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
+    let direction = (end.0 - start.0, end.1 - start.1);
+    let length_squared = direction.0.powi(2) + direction.1.powi(2);
+    let along = (((point.0 - start.0) * direction.0 + (point.1 - start.1) * direction.1)
+        / length_squared)
+        .clamp(0.0, 1.0);
+    distance(
+        point,
+        (start.0 + along * direction.0, start.1 + along * direction.1),
+    )
+}
+
+// distance to the nearest wall along the ray, at most RANGEFINDER_RANGE
+fn cast(origin: (f64, f64), angle: f64) -> f64 {
+    let ray = (angle.cos(), angle.sin());
+
+    WALLS
+        .iter()
+        .filter_map(|&(start, end)| {
+            let wall = (end.0 - start.0, end.1 - start.1);
+            let denominator = ray.0 * wall.1 - ray.1 * wall.0;
+            if denominator.abs() < f64::EPSILON {
+                return None;
+            }
+            let offset = (start.0 - origin.0, start.1 - origin.1);
+            let along_ray = (offset.0 * wall.1 - offset.1 * wall.0) / denominator;
+            let along_wall = (offset.0 * ray.1 - offset.1 * ray.0) / denominator;
+            if along_ray >= 0.0 && (0.0..=1.0).contains(&along_wall) {
+                Some(along_ray)
+            } else {
+                None
+            }
+        })
+        .fold(RANGEFINDER_RANGE, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{cast, Robot, START};
+
+    #[test]
+    fn sense_and_stop_at_walls() {
+        // dividing wall is 0.4 above the start
+        assert!((cast(START, PI / 2.0) - 0.4).abs() < 1e-9);
+        // outer wall is out of range
+        assert_eq!(cast(START, 0.0), 0.5);
+
+        let mut robot = Robot {
+            position: START,
+            heading: PI / 2.0,
+        };
+        for _ in 0..100 {
+            robot.step(0.0, 1.0);
+        }
+
+        // blocked below the dividing wall
+        assert!(robot.position.1 < 0.5);
+        assert!(robot.position.1 > 0.45);
+    }
+}
@@ -0,0 +1,7 @@
+// benchmark tasks with ready-made progress functions, i.e. baselines for new setups
+//
+// let neat = Neat::from_parameters(xor::parameters().build(), Box::new(xor::progress));
+// for evaluation in neat.run().take(100) { ... }
+pub mod maze;
+pub mod pole_balancing;
+pub mod xor;
@@ -0,0 +1,185 @@
+use crate::{Controller, Individual, ParametersBuilder, Progress};
+
+// cart position and velocity, angle and angular velocity of every pole, and a constant bias
+pub const SINGLE_INPUT_DIMENSION: usize = 5;
+pub const DOUBLE_INPUT_DIMENSION: usize = 7;
+// force applied to the cart, scaled from [-1, 1]
+pub const OUTPUT_DIMENSION: usize = 1;
+
+// balanced this long counts as solved
+pub const MAX_STEPS: usize = 10_000;
+
+const GRAVITY: f64 = -9.8;
+const CART_MASS: f64 = 1.0;
+const CART_FRICTION: f64 = 0.0005;
+const POLE_FRICTION: f64 = 0.000_002;
+const MAX_FORCE: f64 = 10.0;
+const TRACK_LIMIT: f64 = 2.4;
+const TIME_STEP: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy)]
+struct Pole {
+    mass: f64,
+    half_length: f64,
+    angle: f64,
+    angular_velocity: f64,
+}
+
+impl Pole {
+    fn new(mass: f64, half_length: f64, angle: f64) -> Self {
+        Pole {
+            mass,
+            half_length,
+            angle,
+            angular_velocity: 0.0,
+        }
+    }
+}
+
+// equations of motion as in Wieland (1991), integrated by euler steps
+#[derive(Debug, Clone)]
+struct Cart {
+    position: f64,
+    velocity: f64,
+    poles: Vec<Pole>,
+    angle_limit: f64,
+}
+
+impl Cart {
+    fn single() -> Self {
+        Cart {
+            position: 0.0,
+            velocity: 0.0,
+            poles: vec![Pole::new(0.1, 0.5, 0.05)],
+            angle_limit: 12f64.to_radians(),
+        }
+    }
+
+    fn double() -> Self {
+        Cart {
+            position: 0.0,
+            velocity: 0.0,
+            poles: vec![
+                Pole::new(0.1, 0.5, 1f64.to_radians()),
+                Pole::new(0.01, 0.05, 0.0),
+            ],
+            angle_limit: 36f64.to_radians(),
+        }
+    }
+
+    // state normalized to roughly [-1, 1] and the bias
+    fn observe(&self) -> Vec<f64> {
+        let mut observation = vec![self.position / TRACK_LIMIT, self.velocity / 2.0];
+        for pole in &self.poles {
+            observation.push(pole.angle / self.angle_limit);
+            observation.push(pole.angular_velocity / 2.0);
+        }
+        observation.push(1.0);
+        observation
+    }
+
+    fn step(&mut self, force: f64) {
+        let (mut effective_force, mut effective_mass) = (0.0, 0.0);
+        for pole in &self.poles {
+            let (sin, cos) = pole.angle.sin_cos();
+            let friction = POLE_FRICTION * pole.angular_velocity / (pole.mass * pole.half_length);
+            effective_force += pole.mass * pole.half_length * pole.angular_velocity.powi(2) * sin
+                + 0.75 * pole.mass * cos * (friction + GRAVITY * sin);
+            effective_mass += pole.mass * (1.0 - 0.75 * cos.powi(2));
+        }
+
+        let acceleration = (force - CART_FRICTION * self.velocity.signum() + effective_force)
+            / (CART_MASS + effective_mass);
+
+        for pole in &mut self.poles {
+            let (sin, cos) = pole.angle.sin_cos();
+            let friction = POLE_FRICTION * pole.angular_velocity / (pole.mass * pole.half_length);
+            let angular_acceleration =
+                -0.75 * (acceleration * cos + GRAVITY * sin + friction) / pole.half_length;
+
+            pole.angle += TIME_STEP * pole.angular_velocity;
+            pole.angular_velocity += TIME_STEP * angular_acceleration;
+        }
+
+        self.position += TIME_STEP * self.velocity;
+        self.velocity += TIME_STEP * acceleration;
+    }
+
+    fn failed(&self) -> bool {
+        self.position.abs() > TRACK_LIMIT
+            || self
+                .poles
+                .iter()
+                .any(|pole| pole.angle.abs() > self.angle_limit)
+    }
+}
+
+pub fn single_parameters() -> ParametersBuilder {
+    ParametersBuilder::default()
+        .population_size(150)
+        .survival_rate(0.2)
+        .dimensions(SINGLE_INPUT_DIMENSION, OUTPUT_DIMENSION)
+        .novelty_nearest_neighbors(10)
+}
+
+pub fn double_parameters() -> ParametersBuilder {
+    single_parameters().dimensions(DOUBLE_INPUT_DIMENSION, OUTPUT_DIMENSION)
+}
+
+// fitness is the fraction of MAX_STEPS balanced, behavior the average cart position and pole angles
+pub fn single(individual: &Individual) -> Progress {
+    balance(Cart::single(), individual)
+}
+
+pub fn double(individual: &Individual) -> Progress {
+    balance(Cart::double(), individual)
+}
+
+fn balance(mut cart: Cart, individual: &Individual) -> Progress {
+    let mut controller = Controller::from(individual);
+    let mut totals = vec![0.0; 1 + cart.poles.len()];
+
+    let mut steps = 0;
+    while steps < MAX_STEPS && !cart.failed() {
+        let output = controller.activate(&cart.observe())[0];
+        cart.step(MAX_FORCE * output.clamp(-1.0, 1.0));
+
+        totals[0] += cart.position / TRACK_LIMIT;
+        for (total, pole) in totals[1..].iter_mut().zip(&cart.poles) {
+            *total += pole.angle / cart.angle_limit;
+        }
+        steps += 1;
+    }
+
+    let behavior = totals
+        .into_iter()
+        .map(|total| total / steps.max(1) as f64)
+        .collect();
+    let progress = Progress::new(steps as f64 / MAX_STEPS as f64, behavior);
+
+    if steps == MAX_STEPS {
+        progress.solved(individual.clone())
+    } else {
+        progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cart;
+
+    #[test]
+    fn unbalanced_poles_fall() {
+        for cart in &mut [Cart::single(), Cart::double()] {
+            let mut steps = 0;
+            while !cart.failed() {
+                cart.step(0.0);
+                steps += 1;
+            }
+
+            // the long pole tips over towards its initial angle within a few seconds
+            assert!(steps < 1000, "took {} steps", steps);
+            assert!(cart.poles[0].angle > 0.0);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{Controller, Individual, ParametersBuilder, Progress};
+
+// both operands and a constant bias
+pub const INPUT_DIMENSION: usize = 3;
+pub const OUTPUT_DIMENSION: usize = 1;
+
+const CASES: [([f64; 3], f64); 4] = [
+    ([0.0, 0.0, 1.0], 0.0),
+    ([0.0, 1.0, 1.0], 1.0),
+    ([1.0, 0.0, 1.0], 1.0),
+    ([1.0, 1.0, 1.0], 0.0),
+];
+
+pub fn parameters() -> ParametersBuilder {
+    ParametersBuilder::default()
+        .population_size(150)
+        .survival_rate(0.2)
+        .dimensions(INPUT_DIMENSION, OUTPUT_DIMENSION)
+        .novelty_nearest_neighbors(10)
+}
+
+// fitness is 4 minus the squared error over all cases, the outputs are the behavior,
+// solved once every output rounds to its target
+pub fn progress(individual: &Individual) -> Progress {
+    let mut controller = Controller::from(individual);
+
+    let outputs: Vec<f64> = CASES
+        .iter()
+        .map(|(inputs, _)| {
+            controller.reset();
+            controller.activate(inputs)[0]
+        })
+        .collect();
+
+    let error: f64 = CASES
+        .iter()
+        .zip(&outputs)
+        .map(|((_, target), output)| (target - output).powi(2))
+        .sum();
+    let solved = CASES
+        .iter()
+        .zip(&outputs)
+        .all(|((_, target), output)| (target - output).abs() < 0.5);
+
+    let progress = Progress::new(4.0 - error, outputs);
+
+    if solved {
+        progress.solved(individual.clone())
+    } else {
+        progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parameters, progress};
+    use crate::{Evaluation, Neat};
+
+    #[test]
+    fn run_full_loop() {
+        let neat = Neat::from_parameters(
+            parameters().seed(42).population_size(30).build(),
+            Box::new(progress),
+        );
+
+        for evaluation in neat.run().take(5) {
+            match evaluation {
+                Evaluation::Progress(statistics) => {
+                    assert!(statistics.population.fitness.raw_maximum <= 4.0)
+                }
+                Evaluation::Solution(solution) => {
+                    assert!(progress(&solution).is_solution());
+                    break;
+                }
+                Evaluation::Error(error) => panic!("{}", error),
+                Evaluation::BudgetExhausted(_) => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::{error::Error, fmt, io};
+
+#[cfg(feature = "config-file")]
+use config::ConfigError;
+
+use crate::{individual::validation::Violation, runtime::evaluation::EvalError};
+
+#[derive(Debug)]
+pub enum NeatError {
+    #[cfg(feature = "config-file")]
+    Config(ConfigError),
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    Evaluation(EvalError),
+    Serialization(serde_json::Error),
+    Io(io::Error),
+    #[cfg(feature = "parallel")]
+    ThreadPool(rayon::ThreadPoolBuildError),
+    // genome of another library that has no counterpart here, see individual::neat_python
+    Conversion(String),
+    // genome does not fit the dimensions of parameters.setup or the population, see Genome::grow_io
+    Dimension(String),
+    // genome that can not be evaluated, see Runtime::inject
+    InvalidGenome(Vec<Violation>),
+    // custom activation that is not registered, see Neat::with_activation
+    UnknownActivation(String),
+    Internal(&'static str),
+}
+
+impl fmt::Display for NeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "config-file")]
+            NeatError::Config(error) => write!(f, "invalid configuration: {}", error),
+            #[cfg(feature = "toml")]
+            NeatError::Toml(error) => write!(f, "invalid configuration: {}", error),
+            NeatError::Evaluation(error) => write!(f, "{}", error),
+            NeatError::Serialization(error) => write!(f, "(de)serialization failed: {}", error),
+            NeatError::Io(error) => write!(f, "io failed: {}", error),
+            #[cfg(feature = "parallel")]
+            NeatError::ThreadPool(error) => write!(f, "could not build thread pool: {}", error),
+            NeatError::Conversion(message) => write!(f, "conversion failed: {}", message),
+            NeatError::Dimension(message) => write!(f, "dimensions do not match: {}", message),
+            NeatError::InvalidGenome(violations) => {
+                let violations: Vec<String> = violations.iter().map(Violation::to_string).collect();
+                write!(f, "invalid genome: {}", violations.join(", "))
+            }
+            NeatError::UnknownActivation(name) => write!(f, "unknown activation: {}", name),
+            NeatError::Internal(message) => write!(f, "internal invariant violated: {}", message),
+        }
+    }
+}
+
+impl Error for NeatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "config-file")]
+            NeatError::Config(error) => Some(error),
+            #[cfg(feature = "toml")]
+            NeatError::Toml(error) => Some(error),
+            NeatError::Evaluation(error) => Some(error),
+            NeatError::Serialization(error) => Some(error),
+            NeatError::Io(error) => Some(error),
+            #[cfg(feature = "parallel")]
+            NeatError::ThreadPool(error) => Some(error),
+            NeatError::Conversion(_)
+            | NeatError::Dimension(_)
+            | NeatError::InvalidGenome(_)
+            | NeatError::UnknownActivation(_)
+            | NeatError::Internal(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "config-file")]
+impl From<ConfigError> for NeatError {
+    fn from(error: ConfigError) -> Self {
+        NeatError::Config(error)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for NeatError {
+    fn from(error: toml::de::Error) -> Self {
+        NeatError::Toml(error)
+    }
+}
+
+impl From<EvalError> for NeatError {
+    fn from(error: EvalError) -> Self {
+        NeatError::Evaluation(error)
+    }
+}
+
+impl From<serde_json::Error> for NeatError {
+    fn from(error: serde_json::Error) -> Self {
+        NeatError::Serialization(error)
+    }
+}
+
+impl From<io::Error> for NeatError {
+    fn from(error: io::Error) -> Self {
+        NeatError::Io(error)
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl From<rayon::ThreadPoolBuildError> for NeatError {
+    fn from(error: rayon::ThreadPoolBuildError) -> Self {
+        NeatError::ThreadPool(error)
+    }
+}
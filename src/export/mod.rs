@@ -0,0 +1,77 @@
+// standalone representations of evolved networks, convert individuals via Controller::from
+mod onnx;
+mod rust;
+
+pub use onnx::to_onnx;
+pub use rust::to_rust;
+
+use crate::genes::Activation;
+
+// every activation in terms of a pre-activation value x, matches genes::activations,
+// custom activations call a function of their name, made a valid identifier, that has to be provided
+// next to the generated code
+fn activation_expression(activation: Activation, x: &str) -> String {
+    match activation {
+        Activation::Linear => x.to_owned(),
+        Activation::Sigmoid => format!("1.0 / (1.0 + (-4.9 * {}).exp())", x),
+        Activation::Tanh => format!("(4.9 * {}).tanh()", x),
+        Activation::Gaussian => format!("({x} * {x} / -2.0).exp()", x = x),
+        Activation::Step => format!("if {} > 0.0 {{ 1.0 }} else {{ 0.0 }}", x),
+        Activation::Sine => format!("({} * std::f64::consts::PI).sin()", x),
+        Activation::Cosine => format!("({} * std::f64::consts::PI).cos()", x),
+        Activation::Inverse => format!("-{}", x),
+        Activation::Absolute => format!("{}.abs()", x),
+        Activation::Relu => format!("{}.max(0.0)", x),
+        Activation::Squared => format!("{x} * {x}", x = x),
+        Activation::Custom(custom) => format!("{}({})", identifier(custom.name()), x),
+    }
+}
+
+// invalid characters become underscores, keywords are escaped as raw identifiers
+fn identifier(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let",
+        "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+        "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+        "virtual", "where", "while", "yield",
+    ];
+
+    let mut identifier: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    // neither allowed as raw identifiers
+    if ["_", "self", "Self", "super", "crate"].contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    if KEYWORDS.contains(&identifier.as_str()) {
+        identifier.insert_str(0, "r#");
+    }
+    identifier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::identifier;
+
+    #[test]
+    fn sanitize_custom_activation_names() {
+        assert_eq!(identifier("softsign"), "softsign");
+        assert_eq!(identifier("soft-sign 2"), "soft_sign_2");
+        assert_eq!(identifier("2x"), "_2x");
+        assert_eq!(identifier("fn"), "r#fn");
+        assert_eq!(identifier("_"), "__");
+        assert_eq!(identifier("self"), "self_");
+        assert_eq!(identifier("x); panic!(\""), "x___panic___");
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{
+    genes::{Activation, Aggregation},
+    inference::Controller,
+};
+
+// onnx element type of all tensors
+const DOUBLE: u64 = 11;
+const INT64: u64 = 7;
+const OPSET_VERSION: u64 = 13;
+const IR_VERSION: u64 = 7;
+// operator domain of custom activations, implementations have to be provided to the onnx runtime
+const CUSTOM_DOMAIN: &str = "novel_set_neat";
+
+// serialized onnx model of the forward pass
+// graph inputs: "input" [inputs], "state_in" [inputs + nodes]
+// graph outputs: "output" [outputs], "state_out" [inputs + nodes]
+// feed state_out of one activation as state_in of the next to carry recurrent connections,
+// start with zeros
+pub fn to_onnx(controller: &Controller) -> Vec<u8> {
+    let size = controller.input_count + controller.nodes.len();
+    let mut graph = Graph::default();
+
+    // every value of the buffer as a tensor of shape [1]
+    let mut values: Vec<String> = (0..controller.input_count)
+        .map(|index| graph.gather("input", index))
+        .collect();
+    let state: Vec<String> = (0..size)
+        .map(|index| graph.gather("state_in", index))
+        .collect();
+
+    for (index, node) in controller.nodes.iter().enumerate() {
+        let terms: Vec<String> = node
+            .inputs
+            .iter()
+            .map(|&(source, weight)| (values[source].clone(), weight))
+            .chain(
+                node.recurrent_inputs
+                    .iter()
+                    .map(|&(source, weight)| (state[source].clone(), weight)),
+            )
+            .map(|(value, weight)| {
+                let weight = graph.constant(weight);
+                graph.operation("Mul", &[&value, &weight])
+            })
+            .collect();
+
+        let aggregated = graph.aggregation(node.aggregation, &terms);
+
+        let response = graph.constant(node.response);
+        let bias = graph.constant(node.bias);
+        let scaled = graph.operation("Mul", &[&aggregated, &response]);
+        let x = graph.operation("Add", &[&bias, &scaled]);
+
+        let mut value = graph.activation(node.activation, &x);
+
+        // leaky integration of the previous output
+        if node.time_constant != 1.0 {
+            let previous = &state[controller.input_count + index];
+            let rate = graph.constant(1.0 / node.time_constant);
+            let difference = graph.operation("Sub", &[&value, previous]);
+            let step = graph.operation("Mul", &[&difference, &rate]);
+            value = graph.operation("Add", &[previous, &step]);
+        }
+
+        values.push(value);
+    }
+
+    let outputs: Vec<&str> = controller
+        .outputs
+        .iter()
+        .map(|&index| values[index].as_str())
+        .collect();
+    graph.concat(&outputs, "output");
+    let all: Vec<&str> = values.iter().map(String::as_str).collect();
+    graph.concat(&all, "state_out");
+
+    let graph_proto = message(|m| {
+        for node in &graph.nodes {
+            m.bytes(1, node);
+        }
+        m.string(2, "novel_set_neat");
+        for initializer in &graph.initializers {
+            m.bytes(5, initializer);
+        }
+        m.bytes(11, &value_info("input", controller.input_count));
+        m.bytes(11, &value_info("state_in", size));
+        m.bytes(12, &value_info("output", controller.outputs.len()));
+        m.bytes(12, &value_info("state_out", size));
+    });
+
+    message(|m| {
+        m.varint_field(1, IR_VERSION);
+        m.string(2, "novel-set-neat");
+        m.bytes(7, &graph_proto);
+        m.bytes(
+            8,
+            &message(|opset| {
+                opset.string(1, "");
+                opset.varint_field(2, OPSET_VERSION);
+            }),
+        );
+        if graph.uses_custom_domain {
+            m.bytes(
+                8,
+                &message(|opset| {
+                    opset.string(1, CUSTOM_DOMAIN);
+                    opset.varint_field(2, 1);
+                }),
+            );
+        }
+    })
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Vec<u8>>,
+    initializers: Vec<Vec<u8>>,
+    names: usize,
+    uses_custom_domain: bool,
+}
+
+impl Graph {
+    fn name(&mut self, prefix: &str) -> String {
+        self.names += 1;
+        format!("{}_{}", prefix, self.names)
+    }
+
+    fn node(&mut self, op_type: &str, inputs: &[&str], output: &str, attribute: Option<Vec<u8>>) {
+        self.node_in_domain(op_type, "", inputs, output, attribute);
+    }
+
+    fn node_in_domain(
+        &mut self,
+        op_type: &str,
+        domain: &str,
+        inputs: &[&str],
+        output: &str,
+        attribute: Option<Vec<u8>>,
+    ) {
+        let name = self.name(op_type);
+        self.nodes.push(message(|m| {
+            for input in inputs {
+                m.string(1, input);
+            }
+            m.string(2, output);
+            m.string(3, &name);
+            m.string(4, op_type);
+            if let Some(attribute) = &attribute {
+                m.bytes(5, attribute);
+            }
+            if !domain.is_empty() {
+                m.string(7, domain);
+            }
+        }));
+    }
+
+    fn operation(&mut self, op_type: &str, inputs: &[&str]) -> String {
+        let output = self.name("value");
+        self.node(op_type, inputs, &output, None);
+        output
+    }
+
+    // tensor of shape [1]
+    fn constant(&mut self, value: f64) -> String {
+        let name = self.name("constant");
+        self.initializers.push(message(|m| {
+            m.varint_field(1, 1);
+            m.varint_field(2, DOUBLE);
+            m.string(8, &name);
+            m.bytes(9, &value.to_le_bytes());
+        }));
+        name
+    }
+
+    // element at index of a graph input as tensor of shape [1]
+    fn gather(&mut self, input: &str, index: usize) -> String {
+        let indices = self.name("index");
+        self.initializers.push(message(|m| {
+            m.varint_field(1, 1);
+            m.varint_field(2, INT64);
+            m.string(8, &indices);
+            m.bytes(9, &(index as i64).to_le_bytes());
+        }));
+        self.operation("Gather", &[input, &indices])
+    }
+
+    fn concat(&mut self, inputs: &[&str], output: &str) {
+        self.node("Concat", inputs, output, Some(int_attribute("axis", 0)));
+    }
+
+    // same functions as genes::aggregations
+    fn aggregation(&mut self, aggregation: Aggregation, terms: &[String]) -> String {
+        let (first, rest) = match terms.split_first() {
+            None => return self.constant(0.0),
+            Some(split) => split,
+        };
+        let op_type = match aggregation {
+            Aggregation::Sum | Aggregation::Mean => "Add",
+            Aggregation::Product => "Mul",
+            Aggregation::Max => "Max",
+            Aggregation::Min => "Min",
+        };
+        let aggregated = rest.iter().fold(first.clone(), |aggregated, term| {
+            self.operation(op_type, &[&aggregated, term])
+        });
+
+        if aggregation == Aggregation::Mean {
+            let count = self.constant(terms.len() as f64);
+            self.operation("Div", &[&aggregated, &count])
+        } else {
+            aggregated
+        }
+    }
+
+    // same functions as genes::activations
+    fn activation(&mut self, activation: Activation, x: &str) -> String {
+        match activation {
+            Activation::Linear => self.operation("Identity", &[x]),
+            Activation::Sigmoid => {
+                let steepness = self.constant(4.9);
+                let scaled = self.operation("Mul", &[x, &steepness]);
+                self.operation("Sigmoid", &[&scaled])
+            }
+            Activation::Tanh => {
+                let steepness = self.constant(4.9);
+                let scaled = self.operation("Mul", &[x, &steepness]);
+                self.operation("Tanh", &[&scaled])
+            }
+            Activation::Gaussian => {
+                let half = self.constant(-0.5);
+                let squared = self.operation("Mul", &[x, x]);
+                let scaled = self.operation("Mul", &[&squared, &half]);
+                self.operation("Exp", &[&scaled])
+            }
+            Activation::Step => {
+                let zero = self.constant(0.0);
+                let positive = self.operation("Greater", &[x, &zero]);
+                let output = self.name("value");
+                self.node(
+                    "Cast",
+                    &[&positive],
+                    &output,
+                    Some(int_attribute("to", DOUBLE as i64)),
+                );
+                output
+            }
+            Activation::Sine | Activation::Cosine => {
+                let pi = self.constant(std::f64::consts::PI);
+                let scaled = self.operation("Mul", &[x, &pi]);
+                let op_type = if activation == Activation::Sine {
+                    "Sin"
+                } else {
+                    "Cos"
+                };
+                self.operation(op_type, &[&scaled])
+            }
+            Activation::Inverse => self.operation("Neg", &[x]),
+            Activation::Absolute => self.operation("Abs", &[x]),
+            Activation::Relu => self.operation("Relu", &[x]),
+            Activation::Squared => self.operation("Mul", &[x, x]),
+            Activation::Custom(custom) => {
+                self.uses_custom_domain = true;
+                let output = self.name("value");
+                self.node_in_domain(&custom.name(), CUSTOM_DOMAIN, &[x], &output, None);
+                output
+            }
+        }
+    }
+}
+
+fn int_attribute(name: &str, value: i64) -> Vec<u8> {
+    message(|m| {
+        m.string(1, name);
+        m.varint_field(3, value as u64);
+        // AttributeType::INT
+        m.varint_field(20, 2);
+    })
+}
+
+// 1-d double tensor of fixed length
+fn value_info(name: &str, length: usize) -> Vec<u8> {
+    let dimension = message(|m| m.varint_field(1, length as u64));
+    let shape = message(|m| m.bytes(1, &dimension));
+    let tensor_type = message(|m| {
+        m.varint_field(1, DOUBLE);
+        m.bytes(2, &shape);
+    });
+    let type_proto = message(|m| m.bytes(1, &tensor_type));
+    message(|m| {
+        m.string(1, name);
+        m.bytes(2, &type_proto);
+    })
+}
+
+// minimal protobuf wire format writer, sufficient for onnx
+struct Message(Vec<u8>);
+
+fn message(build: impl FnOnce(&mut Message)) -> Vec<u8> {
+    let mut message = Message(Vec::new());
+    build(&mut message);
+    message.0
+}
+
+impl Message {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn key(&mut self, field: u64, wire_type: u64) {
+        self.varint((field << 3) | wire_type);
+    }
+
+    fn varint_field(&mut self, field: u64, value: u64) {
+        self.key(field, 0);
+        self.varint(value);
+    }
+
+    fn bytes(&mut self, field: u64, bytes: &[u8]) {
+        self.key(field, 2);
+        self.varint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn string(&mut self, field: u64, string: &str) {
+        self.bytes(field, string.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{message, to_onnx};
+    use crate::{
+        genes::{Activation, Aggregation},
+        inference::{Controller, ControllerNode},
+    };
+
+    #[test]
+    fn encode_varint() {
+        assert_eq!(message(|m| m.varint_field(1, 300)), vec![0x08, 0xac, 0x02]);
+    }
+
+    #[test]
+    fn export_deterministically() {
+        let controller = Controller::new(
+            1,
+            vec![ControllerNode {
+                activation: Activation::Step,
+                bias: 0.5,
+                response: 1.0,
+                inputs: vec![(0, 2.0)],
+                recurrent_inputs: vec![(1, 1.0)],
+                time_constant: 4.0,
+                aggregation: Aggregation::Sum,
+            }],
+            vec![1],
+        );
+
+        let model = to_onnx(&controller);
+
+        assert!(!model.is_empty());
+        assert_eq!(model, to_onnx(&controller));
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use super::activation_expression;
+use crate::{genes::Aggregation, inference::Controller};
+
+// source of a dependency free struct with the given name implementing the forward pass,
+// recurrent connections read the values of the previous activation kept in `state`
+pub fn to_rust(controller: &Controller, name: &str) -> String {
+    let size = controller.input_count + controller.nodes.len();
+    let mut source = String::new();
+
+    // writing into a String can not fail
+    let mut line = |text: String| writeln!(source, "{}", text).unwrap();
+
+    line("// generated by novel-set-neat, do not edit".to_owned());
+    line("#[derive(Debug, Clone)]".to_owned());
+    line(format!("pub struct {} {{", name));
+    line(format!("    pub state: [f64; {}],", size));
+    line("}".to_owned());
+    line(String::new());
+    line(format!("impl Default for {} {{", name));
+    line("    fn default() -> Self {".to_owned());
+    line(format!("        Self {{ state: [0.0; {}] }}", size));
+    line("    }".to_owned());
+    line("}".to_owned());
+    line(String::new());
+    line(format!("impl {} {{", name));
+    line(format!(
+        "    pub fn activate(&mut self, inputs: &[f64; {}]) -> [f64; {}] {{",
+        controller.input_count,
+        controller.outputs.len()
+    ));
+    line(format!("        let mut values = [0.0f64; {}];", size));
+    line(format!(
+        "        values[..{count}].copy_from_slice(&inputs[..{count}]);",
+        count = controller.input_count
+    ));
+
+    for (index, node) in controller.nodes.iter().enumerate() {
+        let terms =
+            node.inputs
+                .iter()
+                .map(|&(source, weight)| format!("values[{}] * {}", source, literal(weight)))
+                .chain(node.recurrent_inputs.iter().map(|&(source, weight)| {
+                    format!("self.state[{}] * {}", source, literal(weight))
+                }))
+                .collect::<Vec<String>>();
+        let aggregated = aggregation_expression(node.aggregation, &terms);
+
+        line("        {".to_owned());
+        line(format!(
+            "            let x: f64 = {} + {} * ({});",
+            literal(node.bias),
+            literal(node.response),
+            aggregated
+        ));
+        let position = controller.input_count + index;
+        if node.time_constant == 1.0 {
+            line(format!(
+                "            values[{}] = {};",
+                position,
+                activation_expression(node.activation, "x")
+            ));
+        } else {
+            // leaky integration of the previous output
+            line(format!(
+                "            let activation: f64 = {};",
+                activation_expression(node.activation, "x")
+            ));
+            line(format!(
+                "            values[{position}] = self.state[{position}] + (activation - self.state[{position}]) / {};",
+                literal(node.time_constant),
+                position = position
+            ));
+        }
+        line("        }".to_owned());
+    }
+
+    line("        self.state = values;".to_owned());
+    line(format!(
+        "        [{}]",
+        controller
+            .outputs
+            .iter()
+            .map(|index| format!("values[{}]", index))
+            .collect::<Vec<String>>()
+            .join(", ")
+    ));
+    line("    }".to_owned());
+    line(String::new());
+    line("    // forget values carried by recurrent connections".to_owned());
+    line("    pub fn reset(&mut self) {".to_owned());
+    line(format!("        self.state = [0.0; {}];", size));
+    line("    }".to_owned());
+    line("}".to_owned());
+
+    source
+}
+
+// same functions as genes::aggregations
+fn aggregation_expression(aggregation: Aggregation, terms: &[String]) -> String {
+    let (first, rest) = match terms.split_first() {
+        None => return "0.0".to_owned(),
+        Some(split) => split,
+    };
+    match aggregation {
+        Aggregation::Sum => terms.join(" + "),
+        Aggregation::Product => terms.join(" * "),
+        Aggregation::Max => rest.iter().fold(first.clone(), |aggregated, term| {
+            format!("({}).max({})", aggregated, term)
+        }),
+        Aggregation::Min => rest.iter().fold(first.clone(), |aggregated, term| {
+            format!("({}).min({})", aggregated, term)
+        }),
+        Aggregation::Mean => format!("({}) / {}", terms.join(" + "), literal(terms.len() as f64)),
+    }
+}
+
+// valid rust float literal, debug formatting round-trips exactly
+fn literal(value: f64) -> String {
+    if value.is_nan() {
+        "f64::NAN".to_owned()
+    } else if value.is_infinite() {
+        if value > 0.0 {
+            "f64::INFINITY".to_owned()
+        } else {
+            "f64::NEG_INFINITY".to_owned()
+        }
+    } else {
+        format!("({:?}f64)", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_rust;
+    use crate::{
+        genes::{Activation, Aggregation},
+        inference::{Controller, ControllerNode},
+    };
+
+    #[test]
+    fn generate_rust_source() {
+        let controller = Controller::new(
+            1,
+            vec![ControllerNode {
+                activation: Activation::Relu,
+                bias: 0.5,
+                response: 1.0,
+                inputs: vec![(0, -2.0)],
+                recurrent_inputs: vec![(1, 1.0)],
+                time_constant: 1.0,
+                aggregation: Aggregation::Sum,
+            }],
+            vec![1],
+        );
+
+        let source = to_rust(&controller, "Champion");
+
+        assert!(source.contains("pub struct Champion"));
+        assert!(source.contains("values[0] * (-2.0f64) + self.state[1] * (1.0f64)"));
+        assert!(source.contains("values[1] = x.max(0.0);"));
+        // deterministic output
+        assert_eq!(source, to_rust(&controller, "Champion"));
+    }
+
+    #[test]
+    fn generate_maximum_aggregation() {
+        let controller = Controller::new(
+            2,
+            vec![ControllerNode {
+                activation: Activation::Linear,
+                bias: 0.0,
+                response: 1.0,
+                inputs: vec![(0, 1.0), (1, 2.0)],
+                recurrent_inputs: vec![],
+                time_constant: 1.0,
+                aggregation: Aggregation::Max,
+            }],
+            vec![2],
+        );
+
+        assert!(to_rust(&controller, "Champion")
+            .contains("((values[0] * (1.0f64)).max(values[1] * (2.0f64)))"));
+    }
+}
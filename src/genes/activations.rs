@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg(feature = "std")]
+use super::custom_activations::CustomActivation;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Activation {
     Linear,
     Sigmoid,
@@ -13,6 +16,9 @@ pub enum Activation {
     Absolute,
     Relu,
     Squared,
+    // registered by name, see custom_activations::ActivationRegistry
+    #[cfg(feature = "std")]
+    Custom(CustomActivation),
 }
 
 impl Default for Activation {
@@ -21,15 +27,97 @@ impl Default for Activation {
     }
 }
 
+impl Activation {
+    // inclusive bounds of values the activation can produce
+    pub fn output_range(&self) -> (f64, f64) {
+        match self {
+            Activation::Linear | Activation::Inverse => (f64::NEG_INFINITY, f64::INFINITY),
+            Activation::Sigmoid | Activation::Gaussian | Activation::Step => (0.0, 1.0),
+            Activation::Tanh | Activation::Sine | Activation::Cosine => (-1.0, 1.0),
+            Activation::Absolute | Activation::Relu | Activation::Squared => (0.0, f64::INFINITY),
+            #[cfg(feature = "std")]
+            Activation::Custom(custom) => custom.output_range(),
+        }
+    }
+
+    // activations whose shape depends on the scale of the input, only those evolve a coefficient
+    pub fn is_parameterized(&self) -> bool {
+        matches!(
+            self,
+            Activation::Sigmoid
+                | Activation::Tanh
+                | Activation::Gaussian
+                | Activation::Sine
+                | Activation::Cosine
+        )
+    }
+
+    pub fn function(&self) -> fn(f64) -> f64 {
+        match self {
+            Activation::Linear => LINEAR,
+            Activation::Sigmoid => SIGMOID,
+            Activation::Gaussian => GAUSSIAN,
+            Activation::Tanh => TANH,
+            Activation::Step => STEP,
+            Activation::Sine => SINE,
+            Activation::Cosine => COSINE,
+            Activation::Inverse => INVERSE,
+            Activation::Absolute => ABSOLUTE,
+            Activation::Relu => RELU,
+            Activation::Squared => SQUARED,
+            #[cfg(feature = "std")]
+            Activation::Custom(custom) => custom.function(),
+        }
+    }
+}
+
+// math via libm, so activations are available without std
 pub const LINEAR: fn(f64) -> f64 = |val| val;
-// pub const SIGMOID: fn(f64) -> f64 = |val| 1.0 / (1.0 + (-1.0 * val).exp());
-pub const SIGMOID: fn(f64) -> f64 = |val| 1.0 / (1.0 + (-4.9 * val).exp());
+// pub const SIGMOID: fn(f64) -> f64 = |val| 1.0 / (1.0 + libm::exp(-1.0 * val));
+pub const SIGMOID: fn(f64) -> f64 = |val| 1.0 / (1.0 + libm::exp(-4.9 * val));
 pub const TANH: fn(f64) -> f64 = |val| 2.0 * SIGMOID(2.0 * val) - 1.0;
-pub const GAUSSIAN: fn(f64) -> f64 = |val| (val * val / -2.0).exp(); // a = 1, b = 0, c = 1
+pub const GAUSSIAN: fn(f64) -> f64 = |val| libm::exp(val * val / -2.0); // a = 1, b = 0, c = 1
 pub const STEP: fn(f64) -> f64 = |val| if val > 0.0 { 1.0 } else { 0.0 };
-pub const SINE: fn(f64) -> f64 = |val| (val * std::f64::consts::PI).sin();
-pub const COSINE: fn(f64) -> f64 = |val| (val * std::f64::consts::PI).cos();
+pub const SINE: fn(f64) -> f64 = |val| libm::sin(val * core::f64::consts::PI);
+pub const COSINE: fn(f64) -> f64 = |val| libm::cos(val * core::f64::consts::PI);
 pub const INVERSE: fn(f64) -> f64 = |val| -val;
-pub const ABSOLUTE: fn(f64) -> f64 = |val| val.abs();
-pub const RELU: fn(f64) -> f64 = |val| 0f64.max(val);
+pub const ABSOLUTE: fn(f64) -> f64 = libm::fabs;
+pub const RELU: fn(f64) -> f64 = |val| libm::fmax(0.0, val);
 pub const SQUARED: fn(f64) -> f64 = |val| val * val;
+
+#[cfg(test)]
+mod tests {
+    use super::Activation;
+
+    #[test]
+    fn outputs_within_range() {
+        let activations = [
+            Activation::Linear,
+            Activation::Sigmoid,
+            Activation::Tanh,
+            Activation::Gaussian,
+            Activation::Step,
+            Activation::Sine,
+            Activation::Cosine,
+            Activation::Inverse,
+            Activation::Absolute,
+            Activation::Relu,
+            Activation::Squared,
+        ];
+
+        for activation in &activations {
+            let (minimum, maximum) = activation.output_range();
+            for input in (-100..=100).map(|x| x as f64 / 10.0) {
+                let output = activation.function()(input);
+                assert!(
+                    output >= minimum && output <= maximum,
+                    "{:?} produced {} outside of [{}, {}]",
+                    activation,
+                    output,
+                    minimum,
+                    maximum
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+// how a node combines its weighted inputs before the activation, as in neat-python
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Aggregation {
+    Sum,
+    Product,
+    Max,
+    Min,
+    Mean,
+}
+
+impl Default for Aggregation {
+    fn default() -> Self {
+        Aggregation::Sum
+    }
+}
+
+impl Aggregation {
+    // nodes without inputs aggregate to zero, whatever the function
+    pub fn aggregate<I: IntoIterator<Item = f64>>(&self, inputs: I) -> f64 {
+        let mut inputs = inputs.into_iter();
+        let first = match inputs.next() {
+            Some(first) => first,
+            None => return 0.0,
+        };
+
+        match self {
+            Aggregation::Sum => inputs.fold(first, |sum, input| sum + input),
+            Aggregation::Product => inputs.fold(first, |product, input| product * input),
+            Aggregation::Max => inputs.fold(first, f64::max),
+            Aggregation::Min => inputs.fold(first, f64::min),
+            Aggregation::Mean => {
+                let (sum, count) =
+                    inputs.fold((first, 1), |(sum, count), input| (sum + input, count + 1));
+                sum / count as f64
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Aggregation;
+
+    #[test]
+    fn aggregate_inputs() {
+        let inputs = [2.0, -1.0, 5.0];
+
+        assert_eq!(Aggregation::Sum.aggregate(inputs.iter().cloned()), 6.0);
+        assert_eq!(
+            Aggregation::Product.aggregate(inputs.iter().cloned()),
+            -10.0
+        );
+        assert_eq!(Aggregation::Max.aggregate(inputs.iter().cloned()), 5.0);
+        assert_eq!(Aggregation::Min.aggregate(inputs.iter().cloned()), -1.0);
+        assert_eq!(Aggregation::Mean.aggregate(inputs.iter().cloned()), 2.0);
+        assert_eq!(Aggregation::Product.aggregate(Vec::new()), 0.0);
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Serialize};
+
+use super::Id;
+
+// gene an annotation belongs to, connections are identified by their nodes as everywhere else
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AnnotatedGene {
+    Node(Id),
+    Connection(Id, Id),
+}
+
+// user metadata, evolution carries it along but never reads it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub values: BTreeMap<String, String>,
+}
+
+impl Annotation {
+    pub fn tagged(tag: &str) -> Self {
+        Annotation::default().with_tag(tag)
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.insert(tag.to_string());
+        self
+    }
+
+    pub fn with_value(mut self, key: &str, value: &str) -> Self {
+        self.values.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    // tags of both, values of self win
+    pub fn merge(&mut self, other: &Annotation) {
+        self.tags.extend(other.tags.iter().cloned());
+        for (key, value) in &other.values {
+            self.values
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+}
+
+// annotations by gene, serialized as a list as enum keys can not be map keys in most formats
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    from = "Vec<(AnnotatedGene, Annotation)>",
+    into = "Vec<(AnnotatedGene, Annotation)>"
+)]
+pub struct Annotations(BTreeMap<AnnotatedGene, Annotation>);
+
+impl From<Vec<(AnnotatedGene, Annotation)>> for Annotations {
+    fn from(annotations: Vec<(AnnotatedGene, Annotation)>) -> Self {
+        Annotations(annotations.into_iter().collect())
+    }
+}
+
+impl From<Annotations> for Vec<(AnnotatedGene, Annotation)> {
+    fn from(annotations: Annotations) -> Self {
+        annotations.0.into_iter().collect()
+    }
+}
+
+impl Deref for Annotations {
+    type Target = BTreeMap<AnnotatedGene, Annotation>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Annotations {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Annotations {
+    // adds to an existing annotation of the gene
+    pub fn annotate(&mut self, gene: AnnotatedGene, annotation: Annotation) {
+        match self.0.get_mut(&gene) {
+            Some(existing) => {
+                // values of the new annotation win
+                let mut annotation = annotation;
+                annotation.merge(existing);
+                *existing = annotation;
+            }
+            None => {
+                self.0.insert(gene, annotation);
+            }
+        }
+    }
+
+    // genes carrying the tag, in order of their ids
+    pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = AnnotatedGene> + 'a {
+        self.0
+            .iter()
+            .filter(move |(_, annotation)| annotation.has_tag(tag))
+            .map(|(&gene, _)| gene)
+    }
+
+    // annotations of both parents, those of self win on conflicting values
+    pub fn cross_in(&self, other: &Self) -> Self {
+        let mut annotations = self.clone();
+        for (gene, annotation) in other.iter() {
+            annotations.0.entry(*gene).or_default().merge(annotation);
+        }
+        annotations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnnotatedGene, Annotation, Annotations};
+    use crate::genes::Id;
+
+    #[test]
+    fn merge_annotations() {
+        let mut fitter = Annotations::default();
+        fitter.annotate(
+            AnnotatedGene::Node(Id(0)),
+            Annotation::tagged("sensor group A").with_value("unit", "m"),
+        );
+
+        let mut weaker = Annotations::default();
+        weaker.annotate(
+            AnnotatedGene::Node(Id(0)),
+            Annotation::tagged("front").with_value("unit", "cm"),
+        );
+        weaker.annotate(
+            AnnotatedGene::Connection(Id(0), Id(1)),
+            Annotation::tagged("sensor group A"),
+        );
+
+        let crossed = fitter.cross_in(&weaker);
+        let node = &crossed[&AnnotatedGene::Node(Id(0))];
+
+        assert!(node.has_tag("sensor group A") && node.has_tag("front"));
+        assert_eq!(node.value("unit"), Some("m"));
+        assert_eq!(
+            crossed.tagged("sensor group A").collect::<Vec<_>>(),
+            vec![
+                AnnotatedGene::Node(Id(0)),
+                AnnotatedGene::Connection(Id(0), Id(1))
+            ]
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use serde::{Deserialize, Serialize};
-use std::{
+use core::{
     cmp::Ordering,
     hash::Hash,
     hash::Hasher,
     ops::{Deref, DerefMut},
 };
+use serde::{Deserialize, Serialize};
 
 use super::{Gene, Id, Weight};
 
@@ -28,7 +28,7 @@ impl Connection {
         self.2
     }
     pub fn adjust_weight(&mut self, adjustment: f64) {
-        *self.1 += adjustment;
+        self.1 = Weight::new(self.1.value() + adjustment);
     }
 }
 impl Gene for Connection {}
@@ -59,6 +59,51 @@ impl Ord for Connection {
     }
 }
 
+// additional connection between the nodes of a feed forward connection, identified by its own id
+// evaluation sums it into the feed forward connection, see Genome::expressed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Parallel(pub Id, pub Connection);
+
+impl PartialEq for Parallel {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Parallel {}
+
+impl Hash for Parallel {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialOrd for Parallel {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Parallel {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl Deref for Parallel {
+    type Target = Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.1
+    }
+}
+
+impl DerefMut for Parallel {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.1
+    }
+}
+
 macro_rules! makeConnectionSpecifier {
     ( $( $name:ident ),* ) => {
         $(
@@ -0,0 +1,177 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::Activation;
+use crate::error::NeatError;
+
+// names are interned for the whole process so genes stay Copy, functions belong to a registry
+static NAMES: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+fn intern(name: &str) -> &'static str {
+    let mut names = NAMES.lock().expect("activation names poisoned");
+    let names = names.get_or_insert_with(HashSet::new);
+    match names.get(name) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(name.to_owned().into_boxed_str());
+            names.insert(interned);
+            interned
+        }
+    }
+}
+
+// genes carry the function of their registry, deserialized ones only the name until resolved,
+// see ActivationRegistry::resolve
+#[derive(Clone, Copy)]
+pub struct CustomActivation {
+    name: &'static str,
+    function: Option<fn(f64) -> f64>,
+    output_range: (f64, f64),
+}
+
+// stands in for activations that were never resolved, see Violation::UnresolvedActivation
+fn unresolved(_: f64) -> f64 {
+    f64::NAN
+}
+
+impl CustomActivation {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.function.is_some()
+    }
+
+    pub fn function(&self) -> fn(f64) -> f64 {
+        self.function.unwrap_or(unresolved)
+    }
+
+    pub fn output_range(&self) -> (f64, f64) {
+        self.output_range
+    }
+}
+
+// equal by name, whichever registry resolved them
+impl PartialEq for CustomActivation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomActivation {}
+
+impl Hash for CustomActivation {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state)
+    }
+}
+
+impl fmt::Debug for CustomActivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Serialize for CustomActivation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomActivation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if name.is_empty() {
+            return Err(de::Error::custom("custom activation needs a name"));
+        }
+        Ok(CustomActivation {
+            name: intern(&name),
+            function: None,
+            output_range: (f64::NEG_INFINITY, f64::INFINITY),
+        })
+    }
+}
+
+// named activations of one Neat, see Neat::with_activation
+#[derive(Debug, Clone, Default)]
+pub struct ActivationRegistry(Vec<CustomActivation>);
+
+impl ActivationRegistry {
+    // registering a known name replaces its function
+    pub fn register(
+        &mut self,
+        name: &str,
+        function: fn(f64) -> f64,
+        output_range: (f64, f64),
+    ) -> Activation {
+        let custom = CustomActivation {
+            name: intern(name),
+            function: Some(function),
+            output_range,
+        };
+
+        match self.0.iter_mut().find(|registered| **registered == custom) {
+            Some(registered) => *registered = custom,
+            None => self.0.push(custom),
+        }
+        Activation::Custom(custom)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Activation, NeatError> {
+        self.0
+            .iter()
+            .find(|registered| registered.name == name)
+            .map(|&registered| Activation::Custom(registered))
+            .ok_or_else(|| NeatError::UnknownActivation(name.to_owned()))
+    }
+
+    // custom activations take the function registered under their name, built-in ones stay as they are
+    pub fn resolve(&self, activation: Activation) -> Result<Activation, NeatError> {
+        match activation {
+            Activation::Custom(custom) => self.get(custom.name),
+            activation => Ok(activation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActivationRegistry;
+    use crate::{error::NeatError, genes::Activation};
+
+    #[test]
+    fn round_trip_custom_activation() {
+        let mut registry = ActivationRegistry::default();
+        let softsign = registry.register("softsign", |x| x / (1.0 + x.abs()), (-1.0, 1.0));
+
+        assert_eq!(softsign.function()(1.0), 0.5);
+
+        let json = serde_json::to_string(&softsign).unwrap();
+        assert_eq!(json, r#"{"Custom":"softsign"}"#);
+
+        let deserialized: Activation = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, softsign);
+        assert!(deserialized.function()(1.0).is_nan());
+
+        let resolved = registry.resolve(deserialized).unwrap();
+        assert_eq!(resolved.function()(1.0), 0.5);
+        assert_eq!(resolved.output_range(), (-1.0, 1.0));
+    }
+
+    #[test]
+    fn reject_unknown_activation() {
+        let deserialized: Activation = serde_json::from_str(r#"{"Custom":"unknown"}"#).unwrap();
+
+        assert!(matches!(
+            ActivationRegistry::default().resolve(deserialized),
+            Err(NeatError::UnknownActivation(name)) if name == "unknown"
+        ));
+    }
+}
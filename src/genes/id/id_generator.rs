@@ -1,13 +1,48 @@
-use std::{collections::HashMap, ops::RangeFrom};
+use alloc::vec::Vec;
+use core::ops::{Range, RangeFrom};
+
+use serde::{Deserialize, Serialize};
 
 use super::{id_iter::IdIter, Id};
+use crate::genes::HashMap;
 
-#[derive(Debug)]
+// id_cache is the innovation table, it records the ids of nodes created by splitting a connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "IdGeneratorState", into = "IdGeneratorState")]
 pub struct IdGenerator {
     id_gen: RangeFrom<usize>,
     id_cache: HashMap<(Id, Id), Vec<Id>>,
 }
 
+// serializable representation, as tuple keys can not be map keys in most formats
+#[derive(Serialize, Deserialize)]
+struct IdGeneratorState {
+    next_id: usize,
+    id_cache: Vec<((Id, Id), Vec<Id>)>,
+}
+
+impl From<IdGeneratorState> for IdGenerator {
+    fn from(state: IdGeneratorState) -> Self {
+        IdGenerator {
+            id_gen: state.next_id..,
+            id_cache: state.id_cache.into_iter().collect(),
+        }
+    }
+}
+
+impl From<IdGenerator> for IdGeneratorState {
+    fn from(id_generator: IdGenerator) -> Self {
+        let mut id_cache: Vec<((Id, Id), Vec<Id>)> = id_generator.id_cache.into_iter().collect();
+        // stable order for snapshots
+        id_cache.sort_unstable_by_key(|&(key, _)| key);
+
+        IdGeneratorState {
+            next_id: id_generator.id_gen.start,
+            id_cache,
+        }
+    }
+}
+
 impl Default for IdGenerator {
     fn default() -> Self {
         IdGenerator {
@@ -21,6 +56,36 @@ impl IdGenerator {
     pub fn next_id(&mut self) -> Id {
         self.id_gen.next().map(Id).unwrap()
     }
+
+    // id the next call to next_id would return, without using it up
+    pub fn peek(&self) -> Id {
+        Id(self.id_gen.start)
+    }
+
+    // hands out count consecutive ids at once, i.e. for external genome editors, map with Id
+    pub fn reserve_range(&mut self, count: usize) -> Range<usize> {
+        let start = self.id_gen.start;
+        self.id_gen = (start + count)..;
+        start..start + count
+    }
+
+    // ids of nodes created so far by splitting the connection from key.0 to key.1
+    pub fn cached_ids(&self, cache_key: (Id, Id)) -> &[Id] {
+        self.id_cache.get(&cache_key).map_or(&[], Vec::as_slice)
+    }
+
+    // make sure all future ids are greater than given id, i.e. when adopting existing genomes
+    pub fn skip_past(&mut self, id: Id) {
+        if self.id_gen.start <= id.0 {
+            self.id_gen = (id.0 + 1)..;
+        }
+    }
+
+    // forget recorded splits, ids already handed out are never reused
+    pub fn clear_cache(&mut self) {
+        self.id_cache.clear();
+    }
+
     pub fn cached_id_iter(&mut self, cache_key: (Id, Id)) -> IdIter {
         let cache_entry = self.id_cache.entry(cache_key).or_insert_with(Vec::new);
         IdIter::new(cache_entry, &mut self.id_gen)
@@ -41,6 +106,63 @@ mod tests {
         assert_eq!(test_id_manager.next_id(), Id(2));
     }
 
+    #[test]
+    fn skip_past_existing_ids() {
+        let mut test_id_manager = IdGenerator::default();
+
+        test_id_manager.skip_past(Id(5));
+        assert_eq!(test_id_manager.next_id(), Id(6));
+
+        // never go back
+        test_id_manager.skip_past(Id(2));
+        assert_eq!(test_id_manager.next_id(), Id(7));
+    }
+
+    #[test]
+    fn peek_and_reserve_ids() {
+        let mut test_id_manager = IdGenerator::default();
+
+        assert_eq!(test_id_manager.peek(), Id(0));
+        assert_eq!(test_id_manager.reserve_range(3), 0..3);
+        assert_eq!(test_id_manager.peek(), Id(3));
+        assert_eq!(test_id_manager.next_id(), Id(3));
+
+        test_id_manager.cached_id_iter((Id(4), Id(2))).next();
+        assert_eq!(test_id_manager.cached_ids((Id(4), Id(2))), &[Id(4)]);
+        assert!(test_id_manager.cached_ids((Id(2), Id(4))).is_empty());
+    }
+
+    #[test]
+    fn clear_cached_ids() {
+        let mut test_id_manager = IdGenerator::default();
+
+        assert_eq!(
+            test_id_manager.cached_id_iter((Id(4), Id(2))).next(),
+            Some(Id(0))
+        );
+
+        test_id_manager.clear_cache();
+
+        assert_eq!(
+            test_id_manager.cached_id_iter((Id(4), Id(2))).next(),
+            Some(Id(1))
+        );
+    }
+
+    #[test]
+    fn restore_serialized() {
+        let mut test_id_manager = IdGenerator::default();
+
+        test_id_manager.next_id();
+        test_id_manager.cached_id_iter((Id(4), Id(2))).next();
+
+        let serialized = serde_json::to_string(&test_id_manager).unwrap();
+        let mut restored: IdGenerator = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(restored.cached_id_iter((Id(4), Id(2))).next(), Some(Id(1)));
+        assert_eq!(restored.next_id(), Id(2));
+    }
+
     #[test]
     fn iter_cached_ids() {
         let mut test_id_manager = IdGenerator::default();
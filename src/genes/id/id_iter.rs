@@ -1,4 +1,5 @@
-use std::ops::RangeFrom;
+use alloc::vec::Vec;
+use core::ops::RangeFrom;
 
 use super::Id;
 
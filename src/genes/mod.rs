@@ -1,23 +1,50 @@
+use alloc::vec::Vec;
+use core::{hash::Hash, iter::FromIterator, ops::Deref, ops::DerefMut};
 use rand::{prelude::IteratorRandom, prelude::SliceRandom, Rng};
-use serde::{Deserialize, Serialize};
-use std::{collections::HashSet, hash::Hash, iter::FromIterator, ops::Deref, ops::DerefMut};
+use serde::{Deserialize, Serialize, Serializer};
+
+// hashbrown stands in for std collections when only alloc is available, see feature genome
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::{HashMap, HashSet};
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap, HashSet};
 
 pub mod activations;
+pub mod aggregations;
+pub mod annotations;
 pub mod connections;
+#[cfg(feature = "std")]
+pub mod custom_activations;
 mod id;
 pub mod nodes;
 mod weights;
 
 pub use activations::Activation;
+pub use aggregations::Aggregation;
+pub use annotations::{AnnotatedGene, Annotation, Annotations};
+#[cfg(feature = "std")]
+pub use custom_activations::{ActivationRegistry, CustomActivation};
 pub use id::{id_generator::IdGenerator, Id};
-pub use weights::Weight;
+pub use weights::{from_float, to_float, Bias, Coefficient, Float, Response, TimeConstant, Weight};
 
+// genes are identified by their ids only, i.e. two connections between the same nodes are equal
 pub trait Gene: Eq + Hash {}
 
 impl<U: Gene, T: Eq + Hash + Deref<Target = U>> Gene for T {}
-#[derive(Debug, Clone, Deserialize, Serialize)]
+
+// set of genes, derefs to HashSet for insert, replace, get, remove and friends
+#[derive(Debug, Clone, Deserialize)]
 pub struct Genes<T: Gene>(pub HashSet<T>);
 
+// sorted by id, so equal genomes serialize to equal output, see utility::snapshot
+impl<T: Gene + Ord + Serialize> Serialize for Genes<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut genes: Vec<&T> = self.iter().collect();
+        genes.sort_unstable();
+        serializer.collect_seq(genes)
+    }
+}
+
 impl<T: Gene> Default for Genes<T> {
     fn default() -> Self {
         Genes(Default::default())
@@ -39,6 +66,7 @@ impl<T: Gene> DerefMut for Genes<T> {
 }
 
 impl<T: Gene> Genes<T> {
+    // every gene once, starting at a random position
     pub fn iterate_with_random_offset(&self, rng: &mut impl Rng) -> impl Iterator<Item = &T> {
         self.iter()
             .cycle()
@@ -50,12 +78,14 @@ impl<T: Gene> Genes<T> {
         self.iter().choose(rng)
     }
 
+    // empties the set in random order
     pub fn drain_into_random(&mut self, rng: &mut impl Rng) -> impl Iterator<Item = T> {
         let mut random_vec = self.drain().collect::<Vec<T>>();
         random_vec.shuffle(rng);
         random_vec.into_iter()
     }
 
+    // pairs of genes with equal id in both sets
     pub fn iterate_matches<'a>(
         &'a self,
         other: &'a Genes<T>,
@@ -65,6 +95,7 @@ impl<T: Gene> Genes<T> {
             .map(move |item_self| (item_self, other.get(item_self).unwrap()))
     }
 
+    // genes with an id present in only one of both sets
     pub fn iterate_unmatches<'a>(&'a self, other: &'a Genes<T>) -> impl Iterator<Item = &'a T> {
         self.symmetric_difference(other)
     }
@@ -77,6 +108,7 @@ impl<T: Gene> FromIterator<T> for Genes<T> {
 }
 
 impl<'a, U: 'a, T: Gene + Deref<Target = U>> Genes<T> {
+    // genes without their specifier, i.e. Node instead of Hidden<Node>
     pub fn iterate_unwrapped(&'a self) -> impl Iterator<Item = &'a U> + Sized + Clone {
         self.iter().map(|value| value.deref())
     }
@@ -91,6 +123,7 @@ impl<U: Ord, T: Gene + Deref<Target = U>> Genes<T> {
 }
 
 impl<T: Gene + Clone> Genes<T> {
+    // matching genes are taken from either side at random, others only from self
     pub fn cross_in(&self, other: &Self, rng: &mut impl Rng) -> Self {
         self.iterate_matches(other)
             .map(|(gene_self, gene_other)| {
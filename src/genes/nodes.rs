@@ -1,25 +1,91 @@
-use serde::{Deserialize, Serialize};
-use std::{
+use core::{
     cmp::Ordering,
     hash::{Hash, Hasher},
     ops::{Deref, DerefMut},
 };
+use serde::{Deserialize, Serialize};
 
-use super::{Activation, Gene, Id};
+use super::{Activation, Aggregation, Bias, Coefficient, Gene, Id, Response, TimeConstant};
 
 pub trait NodeSpecifier {}
 
 pub trait NodeMarker {}
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Node(pub Id, pub Activation);
+pub struct Node(
+    pub Id,
+    pub Activation,
+    pub Bias,
+    pub Response,
+    // only evolved for parameterized activations
+    #[serde(default)] pub Coefficient,
+    // only used by inference::Controller, i.e. CTRNN mode
+    #[serde(default)] pub TimeConstant,
+    // combines weighted inputs, summation unless evolved otherwise
+    #[serde(default)] pub Aggregation,
+);
 
 impl NodeMarker for Node {}
 
 impl Node {
+    pub fn new(id: Id, activation: Activation) -> Self {
+        Node(
+            id,
+            activation,
+            Bias::default(),
+            Response::default(),
+            Coefficient::default(),
+            TimeConstant::default(),
+            Aggregation::default(),
+        )
+    }
+    pub fn with_bias(mut self, bias: f64) -> Self {
+        self.2 = Bias(bias);
+        self
+    }
+    pub fn with_response(mut self, response: f64) -> Self {
+        self.3 = Response(response);
+        self
+    }
+    pub fn with_coefficient(mut self, coefficient: f64) -> Self {
+        self.4 = Coefficient(coefficient);
+        self
+    }
+    pub fn with_time_constant(mut self, time_constant: f64) -> Self {
+        self.5 = TimeConstant(time_constant.max(1.0));
+        self
+    }
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.6 = aggregation;
+        self
+    }
     pub fn id(&self) -> Id {
         self.0
     }
+    pub fn adjust_bias(&mut self, adjustment: f64) {
+        *self.2 += adjustment;
+    }
+    pub fn adjust_response(&mut self, adjustment: f64) {
+        *self.3 += adjustment;
+    }
+    pub fn adjust_coefficient(&mut self, adjustment: f64) {
+        *self.4 += adjustment;
+    }
+    // time constants below one would overshoot with a time step of one
+    pub fn adjust_time_constant(&mut self, adjustment: f64) {
+        *self.5 = (*self.5 + adjustment).max(1.0);
+    }
+    // bias and response with the coefficient folded in, i.e. for evaluation by plain functions
+    pub fn effective_bias(&self) -> f64 {
+        *self.4 * *self.2
+    }
+    pub fn effective_response(&self) -> f64 {
+        *self.4 * *self.3
+    }
+    // output of node for aggregated input
+    pub fn express(&self, input: f64) -> f64 {
+        self.1.function()(self.effective_bias() + self.effective_response() * input)
+    }
 }
 
 impl Gene for Node {}
@@ -82,3 +148,19 @@ macro_rules! makeNodeSpecifier {
 }
 
 makeNodeSpecifier!(Input, Hidden, Output);
+
+#[cfg(test)]
+mod tests {
+    use super::Node;
+    use crate::genes::{Activation, Id};
+
+    #[test]
+    fn coefficient_scales_pre_activation() {
+        let mut node = Node::new(Id(0), Activation::Gaussian);
+        node.adjust_bias(1.0);
+        node.adjust_coefficient(1.0);
+
+        // gaussian of 2 * (1 + 1 * 0.5)
+        assert_eq!(node.express(0.5), Activation::Gaussian.function()(3.0));
+    }
+}
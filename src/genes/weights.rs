@@ -1,12 +1,38 @@
-use std::ops::{Deref, DerefMut};
+use core::ops::{Deref, DerefMut};
 
 use serde::{Deserialize, Serialize};
 
+// storage precision of weights and behaviors, arithmetic stays in f64, see feature f32
+#[cfg(feature = "f32")]
+pub type Float = f32;
+#[cfg(not(feature = "f32"))]
+pub type Float = f64;
+
+#[allow(clippy::unnecessary_cast)]
+pub fn to_float(value: f64) -> Float {
+    value as Float
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub fn from_float(value: Float) -> f64 {
+    value as f64
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
-pub struct Weight(pub f64);
+pub struct Weight(pub Float);
+
+impl Weight {
+    pub fn new(weight: f64) -> Self {
+        Weight(to_float(weight))
+    }
+
+    pub fn value(&self) -> f64 {
+        from_float(self.0)
+    }
+}
 
 impl Deref for Weight {
-    type Target = f64;
+    type Target = Float;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -18,3 +44,91 @@ impl DerefMut for Weight {
         &mut self.0
     }
 }
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Bias(pub f64);
+
+impl Deref for Bias {
+    type Target = f64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Bias {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Response(pub f64);
+
+impl Default for Response {
+    fn default() -> Self {
+        Response(1.0)
+    }
+}
+
+impl Deref for Response {
+    type Target = f64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Response {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// leaky integration of the node output, 1.0 means no integration
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TimeConstant(pub f64);
+
+impl Default for TimeConstant {
+    fn default() -> Self {
+        TimeConstant(1.0)
+    }
+}
+
+impl Deref for TimeConstant {
+    type Target = f64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for TimeConstant {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// scales the pre-activation value, i.e. steepness, width or frequency of the activation
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Coefficient(pub f64);
+
+impl Default for Coefficient {
+    fn default() -> Self {
+        Coefficient(1.0)
+    }
+}
+
+impl Deref for Coefficient {
+    type Target = f64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Coefficient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
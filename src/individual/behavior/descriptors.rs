@@ -0,0 +1,111 @@
+// common ways to turn what an individual did into a behavior, pass the results to Progress::novelty,
+// states and actions are given in order of time steps, any slice-like state works
+// i.e. Vec<f64> or [f64; 2]
+
+// last state, empty for an empty trajectory
+pub fn final_position<S: AsRef<[f64]>>(trajectory: &[S]) -> Vec<f64> {
+    trajectory
+        .last()
+        .map(|state| state.as_ref().to_vec())
+        .unwrap_or_default()
+}
+
+// given number of evenly spaced states from first to last, concatenated,
+// short trajectories repeat their last state so behaviors of one run keep their length
+pub fn subsampled_trajectory<S: AsRef<[f64]>>(trajectory: &[S], points: usize) -> Vec<f64> {
+    if trajectory.is_empty() {
+        return Vec::new();
+    }
+
+    let last = trajectory.len() - 1;
+    (0..points)
+        .flat_map(|index| {
+            let position = if points > 1 && trajectory.len() >= points {
+                index * last / (points - 1)
+            } else {
+                index.min(last)
+            };
+            trajectory[position].as_ref().iter().cloned()
+        })
+        .collect()
+}
+
+// share of states within each cell of a grid with bins per dimension over bounds, states outside the bounds count
+// for the nearest cell, cells are ordered with the first dimension varying slowest
+pub fn visitation_histogram<S: AsRef<[f64]>>(
+    trajectory: &[S],
+    bounds: &[(f64, f64)],
+    bins: usize,
+) -> Vec<f64> {
+    let cells = bins.pow(bounds.len() as u32);
+    let mut histogram = vec![0.0; cells];
+    if trajectory.is_empty() || bins == 0 {
+        return histogram;
+    }
+
+    for state in trajectory {
+        let cell = bounds
+            .iter()
+            .zip(state.as_ref())
+            .fold(0, |cell, (&(lower, upper), &value)| {
+                let fraction = if upper > lower {
+                    (value - lower) / (upper - lower)
+                } else {
+                    0.0
+                };
+                let bin = ((fraction * bins as f64).floor().max(0.0) as usize).min(bins - 1);
+                cell * bins + bin
+            });
+        histogram[cell] += 1.0;
+    }
+
+    for share in &mut histogram {
+        *share /= trajectory.len() as f64;
+    }
+    histogram
+}
+
+// share of every discrete action among all taken, actions beyond action_count are ignored,
+// discretize continuous actions with visitation_histogram
+pub fn action_counts(actions: &[usize], action_count: usize) -> Vec<f64> {
+    let mut counts = vec![0.0; action_count];
+    for &action in actions {
+        if let Some(count) = counts.get_mut(action) {
+            *count += 1.0;
+        }
+    }
+
+    if !actions.is_empty() {
+        for count in &mut counts {
+            *count /= actions.len() as f64;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{action_counts, final_position, subsampled_trajectory, visitation_histogram};
+
+    #[test]
+    fn describe_trajectory() {
+        let trajectory = vec![[0.0, 0.0], [0.5, 0.2], [0.9, 0.9], [1.0, 0.4]];
+
+        assert_eq!(final_position(&trajectory), vec![1.0, 0.4]);
+        assert!(final_position::<[f64; 2]>(&[]).is_empty());
+
+        assert_eq!(
+            subsampled_trajectory(&trajectory, 2),
+            vec![0.0, 0.0, 1.0, 0.4]
+        );
+        // padded by the last state
+        assert_eq!(subsampled_trajectory(&trajectory[..1], 2), vec![0.0; 4]);
+
+        assert_eq!(
+            visitation_histogram(&trajectory, &[(0.0, 1.0), (0.0, 1.0)], 2),
+            vec![0.25, 0.0, 0.5, 0.25]
+        );
+
+        assert_eq!(action_counts(&[0, 2, 2, 5], 3), vec![0.25, 0.0, 0.5]);
+    }
+}
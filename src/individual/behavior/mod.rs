@@ -0,0 +1,538 @@
+use std::ops::Deref;
+
+use ndarray::{Array2, ArrayView1, Axis};
+use rand::{prelude::SmallRng, SeedableRng};
+use rand_distr::{Distribution, StandardNormal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    genes::{from_float, to_float, Float},
+    parameters::{BehaviorAlignment, BehaviorReduction},
+    utility::gym::StandardScaler,
+};
+
+pub mod descriptors;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Behavior(pub Vec<Float>);
+
+impl From<Vec<f64>> for Behavior {
+    fn from(behavior: Vec<f64>) -> Self {
+        Behavior(behavior.into_iter().map(to_float).collect())
+    }
+}
+
+impl Deref for Behavior {
+    type Target = Vec<Float>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Behavior {
+    // values in full precision, see feature f32
+    pub fn values(&self) -> Vec<f64> {
+        self.iter().cloned().map(from_float).collect()
+    }
+
+    // linear interpolation onto given number of evenly spaced points
+    pub fn resample(&self, size: usize) -> Vec<f64> {
+        let values = self.values();
+        match values.len() {
+            0 => vec![0.0; size],
+            1 => vec![values[0]; size],
+            len => (0..size)
+                .map(|index| {
+                    let position = if size > 1 {
+                        index as f64 * (len - 1) as f64 / (size - 1) as f64
+                    } else {
+                        0.0
+                    };
+                    let lower = position.floor() as usize;
+                    let upper = (lower + 1).min(len - 1);
+                    let fraction = position - lower as f64;
+                    values[lower] * (1.0 - fraction) + values[upper] * fraction
+                })
+                .collect(),
+        }
+    }
+
+    // given number of evenly spaced values, starting with the first
+    pub fn subsample(&self, size: usize) -> Vec<f64> {
+        let values = self.values();
+        if values.len() <= size {
+            return values;
+        }
+        (0..size)
+            .map(|index| values[index * values.len() / size])
+            .collect()
+    }
+
+    // means of given number of consecutive, near equally sized chunks
+    pub fn bins(&self, size: usize) -> Vec<f64> {
+        let values = self.values();
+        if values.len() <= size {
+            return values;
+        }
+        (0..size)
+            .map(|index| {
+                let bin = &values[index * values.len() / size..(index + 1) * values.len() / size];
+                bin.iter().sum::<f64>() / bin.len() as f64
+            })
+            .collect()
+    }
+
+    // projection columns are only drawn up to the length of the behavior
+    pub fn project(&self, projection: &[Vec<f64>]) -> Vec<f64> {
+        let mut projected = vec![0.0; projection.first().map_or(0, Vec::len)];
+        for (value, column) in self.iter().zip(projection) {
+            for (target, factor) in projected.iter_mut().zip(column) {
+                *target += from_float(*value) * factor;
+            }
+        }
+        projected
+    }
+
+    // dynamic time warping distance, allows comparing behaviors of different length
+    pub fn dtw_distance(&self, other: &Behavior) -> f64 {
+        if self.is_empty() || other.is_empty() {
+            return if self.is_empty() && other.is_empty() {
+                0.0
+            } else {
+                f64::INFINITY
+            };
+        }
+
+        let mut previous = vec![f64::INFINITY; other.len() + 1];
+        let mut current = vec![f64::INFINITY; other.len() + 1];
+        previous[0] = 0.0;
+
+        let other_values = other.values();
+        for a in self.values() {
+            current[0] = f64::INFINITY;
+            for (index, b) in other_values.iter().enumerate() {
+                let cost = (a - b).abs();
+                current[index + 1] =
+                    cost + previous[index].min(previous[index + 1]).min(current[index]);
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        previous[other.len()]
+    }
+}
+
+// gaussian projection of given width onto dimension, one column per input value
+// column i is the same for every width, so zero padded behaviors project alike
+fn random_projection(dimension: usize, seed: u64, width: usize) -> Vec<Vec<f64>> {
+    let scale = 1.0 / (dimension.max(1) as f64).sqrt();
+    (0..width)
+        .map(|index| {
+            let mut rng = SmallRng::seed_from_u64(seed.wrapping_add(index as u64));
+            (0..dimension)
+                .map(|_| {
+                    let factor: f64 = StandardNormal.sample(&mut rng);
+                    scale * factor
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub struct Behaviors<'a> {
+    behaviors: Vec<&'a Behavior>,
+    alignment: BehaviorAlignment,
+    reduction: BehaviorReduction,
+    // only this many leading behaviors get a neighborhood, the rest only serve as neighbors
+    queries: Option<usize>,
+    // distances to a behavior are divided by its weight, all weigh 1.0 if empty
+    weights: Vec<f64>,
+}
+
+impl<'a> Deref for Behaviors<'a> {
+    type Target = Vec<&'a Behavior>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.behaviors
+    }
+}
+
+impl<'a> From<Vec<&'a Behavior>> for Behaviors<'a> {
+    fn from(behaviors: Vec<&'a Behavior>) -> Self {
+        Behaviors {
+            behaviors,
+            alignment: BehaviorAlignment::default(),
+            reduction: BehaviorReduction::default(),
+            queries: None,
+            weights: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Behaviors<'a> {
+    pub fn with_alignment(mut self, alignment: BehaviorAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: BehaviorReduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    // i.e. the archive, distances among archived behaviors are never needed
+    pub fn with_queries(mut self, queries: usize) -> Self {
+        self.queries = Some(queries);
+        self
+    }
+
+    // one weight per behavior, i.e. to let archived ones decay, see ArchiveDecay
+    pub fn with_weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    fn query_count(&self) -> usize {
+        self.queries
+            .map_or(self.len(), |queries| queries.min(self.len()))
+    }
+
+    fn reduced(&self) -> Vec<Behavior> {
+        let projection = match self.reduction {
+            BehaviorReduction::RandomProjection { dimension, seed } => {
+                let width = self
+                    .iter()
+                    .map(|behavior| behavior.len())
+                    .max()
+                    .unwrap_or(0);
+                random_projection(dimension, seed, width)
+            }
+            _ => Vec::new(),
+        };
+
+        self.iter()
+            .map(|behavior| match self.reduction {
+                BehaviorReduction::None => behavior.values(),
+                BehaviorReduction::Subsample(size) => behavior.subsample(size),
+                BehaviorReduction::Bins(size) => behavior.bins(size),
+                // every behavior is projected, so all end up in the same space
+                BehaviorReduction::RandomProjection { .. } => behavior.project(&projection),
+            })
+            .map(Behavior::from)
+            .collect()
+    }
+
+    // behaviors brought to equal length, as required for z-scores
+    fn aligned(&self, behaviors: &[&Behavior]) -> Vec<Vec<f64>> {
+        let lengths = behaviors.iter().map(|behavior| behavior.len());
+
+        match self.alignment {
+            BehaviorAlignment::Truncate | BehaviorAlignment::DynamicTimeWarping => {
+                let width = lengths.min().unwrap_or(0);
+                behaviors
+                    .iter()
+                    .map(|behavior| behavior.values()[..width].to_vec())
+                    .collect()
+            }
+            BehaviorAlignment::ZeroPad => {
+                let width = lengths.max().unwrap_or(0);
+                behaviors
+                    .iter()
+                    .map(|behavior| {
+                        let mut padded = behavior.values();
+                        padded.resize(width, 0.0);
+                        padded
+                    })
+                    .collect()
+            }
+            BehaviorAlignment::Resample(size) => behaviors
+                .iter()
+                .map(|behavior| behavior.resample(size))
+                .collect(),
+        }
+    }
+
+    fn distance_matrix(&self) -> Vec<Vec<f64>> {
+        let reduced;
+        let behaviors: Vec<&Behavior> = if self.reduction == BehaviorReduction::None {
+            self.behaviors.clone()
+        } else {
+            reduced = self.reduced();
+            reduced.iter().collect()
+        };
+
+        if self.alignment == BehaviorAlignment::DynamicTimeWarping {
+            return behaviors
+                .iter()
+                .take(self.query_count())
+                .map(|behavior| {
+                    behaviors
+                        .iter()
+                        .map(|neighbor| behavior.dtw_distance(neighbor))
+                        .collect()
+                })
+                .collect();
+        }
+
+        let aligned = self.aligned(&behaviors);
+
+        let width = aligned.first().map_or(0, Vec::len);
+        let height = aligned.len();
+
+        let mut behavior_iter = aligned.iter();
+
+        let mut behavior_arr: Array2<f64> = Array2::zeros((width, height));
+        for mut row in behavior_arr.axis_iter_mut(Axis(1)) {
+            row += &ArrayView1::from(behavior_iter.next().unwrap().as_slice());
+        }
+
+        let standard_scaler = StandardScaler::new(behavior_arr.view().t());
+
+        let mut z_scores_arr: Array2<f64> = Array2::zeros((width, height));
+
+        for (index, row) in behavior_arr.axis_iter(Axis(1)).enumerate() {
+            let mut z_row = z_scores_arr.index_axis_mut(Axis(1), index);
+            z_row += &standard_scaler.scale(row);
+        }
+
+        z_scores_arr
+            .axis_iter(Axis(1))
+            .take(self.query_count())
+            .map(|z_score| {
+                z_scores_arr
+                    .axis_iter(Axis(1))
+                    // build euclidian distance to neighbor
+                    .map(|neighbor| {
+                        neighbor
+                            .iter()
+                            .zip(z_score.iter())
+                            .map(|(n, z)| (n - z).powi(2))
+                            .sum::<f64>()
+                    })
+                    .map(|sum| sum.sqrt())
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn compute_novelty(&self, nearest_neighbors: usize) -> Vec<f64> {
+        Self::novelty(&self.compute_neighborhoods(), nearest_neighbors)
+    }
+
+    // indices of the k nearest neighbors of every behavior, closest first
+    pub fn compute_nearest_neighbors(&self, nearest_neighbors: usize) -> Vec<Vec<usize>> {
+        Self::nearest_neighbors(&self.compute_neighborhoods(), nearest_neighbors)
+    }
+
+    pub fn novelty(neighborhoods: &[Vec<(usize, f64)>], nearest_neighbors: usize) -> Vec<f64> {
+        neighborhoods
+            .iter()
+            .map(|neighborhood| {
+                // take k nearest neighbors, calculate and assign spareseness
+                neighborhood
+                    .iter()
+                    .take(nearest_neighbors)
+                    .map(|(_, distance)| distance)
+                    .sum::<f64>()
+                    / nearest_neighbors as f64
+            })
+            .collect()
+    }
+
+    pub fn nearest_neighbors(
+        neighborhoods: &[Vec<(usize, f64)>],
+        nearest_neighbors: usize,
+    ) -> Vec<Vec<usize>> {
+        neighborhoods
+            .iter()
+            .map(|neighborhood| {
+                neighborhood
+                    .iter()
+                    .take(nearest_neighbors)
+                    .map(|&(index, _)| index)
+                    .collect()
+            })
+            .collect()
+    }
+
+    // all other behaviors with their distance, sorted ascending by distance
+    pub fn compute_neighborhoods(&self) -> Vec<Vec<(usize, f64)>> {
+        self.distance_matrix()
+            .into_iter()
+            .enumerate()
+            .map(|(index, distances)| {
+                let mut distances = distances
+                    .into_iter()
+                    .enumerate()
+                    // skip self with zero distance
+                    .filter(|&(neighbor_index, _)| neighbor_index != index)
+                    // behaviors without weight left are no neighbors at all
+                    .filter_map(|(neighbor_index, distance)| {
+                        match self.weights.get(neighbor_index) {
+                            Some(&weight) if weight <= 0.0 => None,
+                            Some(&weight) => Some((neighbor_index, distance / weight)),
+                            None => Some((neighbor_index, distance)),
+                        }
+                    })
+                    .collect::<Vec<(usize, f64)>>();
+
+                // NaN distances, i.e. of behaviors containing NaN, rank last
+                distances.sort_by(|(_, dist_0), (_, dist_1)| {
+                    dist_0
+                        .is_nan()
+                        .cmp(&dist_1.is_nan())
+                        .then_with(|| dist_0.total_cmp(dist_1))
+                });
+
+                distances
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Behavior, Behaviors};
+    use crate::parameters::{BehaviorAlignment, BehaviorReduction};
+
+    #[test]
+    fn compute_z_score() {
+        let behavior_a = Behavior(vec![0.0, 1.0, 2.0]);
+        let behavior_b = Behavior(vec![2.0, 1.0, 0.0]);
+
+        let behaviors: Behaviors = vec![&behavior_a, &behavior_b].into();
+
+        let novelty = behaviors.compute_novelty(1);
+
+        dbg!(novelty);
+
+        // assert_eq!(novelty, vec![]);
+    }
+
+    #[test]
+    fn find_nearest_neighbors() {
+        let behavior_a = Behavior(vec![0.0]);
+        let behavior_b = Behavior(vec![1.0]);
+        let behavior_c = Behavior(vec![3.0]);
+
+        let behaviors: Behaviors = vec![&behavior_a, &behavior_b, &behavior_c].into();
+
+        let neighbors = behaviors.compute_nearest_neighbors(1);
+
+        assert_eq!(neighbors, vec![vec![1], vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn tolerate_nan_behaviors() {
+        let behavior_a = Behavior(vec![0.0]);
+        let behavior_b = Behavior(vec![f64::NAN]);
+        let behavior_c = Behavior(vec![3.0]);
+
+        let behaviors: Behaviors = vec![&behavior_a, &behavior_b, &behavior_c].into();
+
+        // the scaler spreads NaN to every distance, sorting must not panic on them
+        let neighborhoods = behaviors.compute_neighborhoods();
+
+        assert!(neighborhoods
+            .iter()
+            .all(|neighborhood| neighborhood.len() == 2));
+    }
+
+    #[test]
+    fn resample_behavior() {
+        let behavior = Behavior(vec![0.0, 2.0]);
+
+        assert_eq!(behavior.resample(3), vec![0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn dtw_ignores_time_shift() {
+        let behavior_a = Behavior(vec![0.0, 1.0, 2.0]);
+        let behavior_b = Behavior(vec![0.0, 0.0, 1.0, 2.0]);
+
+        assert_eq!(behavior_a.dtw_distance(&behavior_b), 0.0);
+    }
+
+    #[test]
+    fn align_heterogeneous_lengths() {
+        let behavior_a = Behavior(vec![0.0, 1.0]);
+        let behavior_b = Behavior(vec![0.0, 1.0, 5.0]);
+        let behavior_c = Behavior(vec![3.0]);
+
+        for &alignment in &[
+            BehaviorAlignment::Truncate,
+            BehaviorAlignment::ZeroPad,
+            BehaviorAlignment::Resample(4),
+            BehaviorAlignment::DynamicTimeWarping,
+        ] {
+            let behaviors: Behaviors = vec![&behavior_a, &behavior_b, &behavior_c].into();
+
+            let novelty = behaviors.with_alignment(alignment).compute_novelty(1);
+
+            assert_eq!(novelty.len(), 3);
+            assert!(novelty.iter().all(|n| n.is_finite()));
+        }
+    }
+
+    #[test]
+    fn weigh_distances() {
+        let behaviors = vec![
+            Behavior(vec![0.0]),
+            Behavior(vec![1.0]),
+            Behavior(vec![2.0]),
+            Behavior(vec![3.0]),
+        ];
+
+        let neighborhoods = Behaviors::from(behaviors.iter().collect::<Vec<&Behavior>>())
+            .with_queries(1)
+            .with_weights(vec![1.0, 0.25, 1.0, 0.0])
+            .compute_neighborhoods();
+
+        // the decayed behavior moves behind a farther one, the one without weight disappears
+        let order: Vec<usize> = neighborhoods[0].iter().map(|&(index, _)| index).collect();
+        assert_eq!(order, vec![2, 1]);
+    }
+
+    #[test]
+    fn neighborhoods_of_queries_only() {
+        let behavior_a = Behavior(vec![0.0]);
+        let behavior_b = Behavior(vec![1.0]);
+        let behavior_c = Behavior(vec![3.0]);
+
+        let behaviors: Behaviors = vec![&behavior_a, &behavior_b, &behavior_c].into();
+
+        let all = behaviors.compute_novelty(2);
+        let queried = behaviors.with_queries(1).compute_novelty(2);
+
+        assert_eq!(queried.len(), 1);
+        assert_eq!(queried[0], all[0]);
+    }
+
+    #[test]
+    fn reduce_long_behaviors() {
+        let behavior = Behavior::from((0..10).map(f64::from).collect::<Vec<f64>>());
+
+        assert_eq!(behavior.subsample(5), vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+        assert_eq!(behavior.bins(2), vec![2.0, 7.0]);
+        assert_eq!(behavior.bins(20), behavior.values());
+
+        let behavior_b = Behavior::from((0..10).rev().map(f64::from).collect::<Vec<f64>>());
+        let behavior_c = Behavior(vec![1.0; 10]);
+
+        for &reduction in &[
+            BehaviorReduction::Subsample(3),
+            BehaviorReduction::Bins(3),
+            BehaviorReduction::RandomProjection {
+                dimension: 3,
+                seed: 42,
+            },
+        ] {
+            let behaviors: Behaviors = vec![&behavior, &behavior_b, &behavior_c].into();
+            let behaviors = behaviors.with_reduction(reduction);
+
+            assert!(behaviors.reduced().iter().all(|reduced| reduced.len() == 3));
+            assert!(behaviors.compute_novelty(1).iter().all(|n| n.is_finite()));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::NeatError, parameters::Parameters, utility::time::unix_timestamp};
+
+use super::Individual;
+
+// self-describing artifact to share and reproduce a champion
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    pub crate_version: String,
+    pub time_stamp: u64,
+    pub parameters: Parameters,
+    pub individual: Individual,
+}
+
+impl Bundle {
+    pub fn new(individual: &Individual, parameters: &Parameters) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+            time_stamp: unix_timestamp(),
+            parameters: parameters.clone(),
+            individual: individual.clone(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), NeatError> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
+
+    pub fn load(path: &str) -> Result<Self, NeatError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+impl Individual {
+    pub fn export_bundle(&self, path: &str, parameters: &Parameters) -> Result<(), NeatError> {
+        Bundle::new(self, parameters).save(path)
+    }
+
+    pub fn import_bundle(path: &str) -> Result<Bundle, NeatError> {
+        Bundle::load(path)
+    }
+}
@@ -0,0 +1,301 @@
+use std::{collections::HashMap, fmt};
+
+use serde::Serialize;
+
+use crate::genes::{connections::Connection, nodes::Node, Activation, Aggregation, Id};
+
+use super::genome::Genome;
+
+// structural and parametric differences between two genomes, from the first to the second
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GenomeDiff {
+    pub added_nodes: Vec<NodeSummary>,
+    pub removed_nodes: Vec<NodeSummary>,
+    pub changed_nodes: Vec<NodeChange>,
+    pub added_connections: Vec<ConnectionSummary>,
+    pub removed_connections: Vec<ConnectionSummary>,
+    pub changed_connections: Vec<ConnectionChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeSummary {
+    pub id: Id,
+    pub activation: Activation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeChange {
+    pub id: Id,
+    // previous and new activation, if changed
+    pub activation: Option<(Activation, Activation)>,
+    // previous and new aggregation, if changed
+    pub aggregation: Option<(Aggregation, Aggregation)>,
+    pub bias_delta: f64,
+    pub response_delta: f64,
+    pub coefficient_delta: f64,
+    pub time_constant_delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionSummary {
+    pub input: Id,
+    pub output: Id,
+    pub weight: f64,
+    pub recurrent: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionChange {
+    pub input: Id,
+    pub output: Id,
+    pub recurrent: bool,
+    pub weight_delta: f64,
+}
+
+impl GenomeDiff {
+    pub fn new(from: &Genome, to: &Genome) -> Self {
+        let mut diff = GenomeDiff::default();
+
+        let nodes = |genome: &Genome| -> HashMap<Id, Node> {
+            genome
+                .nodes()
+                .map(|node| (node.id(), node.clone()))
+                .collect()
+        };
+        let (from_nodes, to_nodes) = (nodes(from), nodes(to));
+
+        for (id, node) in &to_nodes {
+            match from_nodes.get(id) {
+                None => diff.added_nodes.push(NodeSummary::from(node)),
+                Some(previous) => {
+                    let change = NodeChange {
+                        id: *id,
+                        activation: if previous.1 != node.1 {
+                            Some((previous.1, node.1))
+                        } else {
+                            None
+                        },
+                        aggregation: if previous.6 != node.6 {
+                            Some((previous.6, node.6))
+                        } else {
+                            None
+                        },
+                        bias_delta: (node.2).0 - (previous.2).0,
+                        response_delta: (node.3).0 - (previous.3).0,
+                        coefficient_delta: (node.4).0 - (previous.4).0,
+                        time_constant_delta: (node.5).0 - (previous.5).0,
+                    };
+                    if change.activation.is_some()
+                        || change.aggregation.is_some()
+                        || change.bias_delta != 0.0
+                        || change.response_delta != 0.0
+                        || change.coefficient_delta != 0.0
+                        || change.time_constant_delta != 0.0
+                    {
+                        diff.changed_nodes.push(change);
+                    }
+                }
+            }
+        }
+        for (id, node) in &from_nodes {
+            if !to_nodes.contains_key(id) {
+                diff.removed_nodes.push(NodeSummary::from(node));
+            }
+        }
+
+        let connections = |genome: &Genome| -> HashMap<(Id, Id, bool), f64> {
+            genome
+                .feed_forward
+                .iterate_unwrapped()
+                .map(|connection| (connection, false))
+                .chain(
+                    genome
+                        .recurrent
+                        .iterate_unwrapped()
+                        .map(|connection| (connection, true)),
+                )
+                .map(|(connection, recurrent): (&Connection, bool)| {
+                    (
+                        (connection.input(), connection.output(), recurrent),
+                        connection.1.value(),
+                    )
+                })
+                .collect()
+        };
+        let (from_connections, to_connections) = (connections(from), connections(to));
+
+        for (&(input, output, recurrent), &weight) in &to_connections {
+            match from_connections.get(&(input, output, recurrent)) {
+                None => diff.added_connections.push(ConnectionSummary {
+                    input,
+                    output,
+                    weight,
+                    recurrent,
+                }),
+                Some(&previous) if previous != weight => {
+                    diff.changed_connections.push(ConnectionChange {
+                        input,
+                        output,
+                        recurrent,
+                        weight_delta: weight - previous,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for (&(input, output, recurrent), &weight) in &from_connections {
+            if !to_connections.contains_key(&(input, output, recurrent)) {
+                diff.removed_connections.push(ConnectionSummary {
+                    input,
+                    output,
+                    weight,
+                    recurrent,
+                });
+            }
+        }
+
+        // stable order for display and comparison
+        diff.added_nodes.sort_by_key(|node| node.id);
+        diff.removed_nodes.sort_by_key(|node| node.id);
+        diff.changed_nodes.sort_by_key(|node| node.id);
+        diff.added_connections
+            .sort_by_key(|c| (c.recurrent, c.input, c.output));
+        diff.removed_connections
+            .sort_by_key(|c| (c.recurrent, c.input, c.output));
+        diff.changed_connections
+            .sort_by_key(|c| (c.recurrent, c.input, c.output));
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_connections.is_empty()
+            && self.removed_connections.is_empty()
+            && self.changed_connections.is_empty()
+    }
+}
+
+impl From<&Node> for NodeSummary {
+    fn from(node: &Node) -> Self {
+        NodeSummary {
+            id: node.id(),
+            activation: node.1,
+        }
+    }
+}
+
+fn arrow(recurrent: bool) -> &'static str {
+    if recurrent {
+        "~>"
+    } else {
+        "->"
+    }
+}
+
+impl fmt::Display for GenomeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+
+        for node in &self.added_nodes {
+            writeln!(f, "+ node {} ({:?})", node.id.0, node.activation)?;
+        }
+        for node in &self.removed_nodes {
+            writeln!(f, "- node {} ({:?})", node.id.0, node.activation)?;
+        }
+        for node in &self.changed_nodes {
+            write!(f, "~ node {}:", node.id.0)?;
+            if let Some((previous, current)) = node.activation {
+                write!(f, " activation {:?} => {:?}", previous, current)?;
+            }
+            if let Some((previous, current)) = node.aggregation {
+                write!(f, " aggregation {:?} => {:?}", previous, current)?;
+            }
+            if node.bias_delta != 0.0 {
+                write!(f, " bias {:+.4}", node.bias_delta)?;
+            }
+            if node.response_delta != 0.0 {
+                write!(f, " response {:+.4}", node.response_delta)?;
+            }
+            if node.coefficient_delta != 0.0 {
+                write!(f, " coefficient {:+.4}", node.coefficient_delta)?;
+            }
+            if node.time_constant_delta != 0.0 {
+                write!(f, " time constant {:+.4}", node.time_constant_delta)?;
+            }
+            writeln!(f)?;
+        }
+        for connection in &self.added_connections {
+            writeln!(
+                f,
+                "+ connection {} {} {} ({:.4})",
+                connection.input.0,
+                arrow(connection.recurrent),
+                connection.output.0,
+                connection.weight
+            )?;
+        }
+        for connection in &self.removed_connections {
+            writeln!(
+                f,
+                "- connection {} {} {} ({:.4})",
+                connection.input.0,
+                arrow(connection.recurrent),
+                connection.output.0,
+                connection.weight
+            )?;
+        }
+        for connection in &self.changed_connections {
+            writeln!(
+                f,
+                "~ connection {} {} {}: weight {:+.4}",
+                connection.input.0,
+                arrow(connection.recurrent),
+                connection.output.0,
+                connection.weight_delta
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        genes::{
+            connections::{Connection, FeedForward},
+            nodes::{Hidden, Node},
+            Activation, Id, Weight,
+        },
+        individual::genome::Genome,
+    };
+
+    #[test]
+    fn diff_split_connection() {
+        let mut from = Genome::default();
+        from.feed_forward
+            .insert(FeedForward(Connection(Id(0), Weight(1.0), Id(1))));
+
+        let mut to = from.clone();
+        to.feed_forward.clear();
+        to.hidden.insert(Hidden(Node::new(Id(2), Activation::Tanh)));
+        to.feed_forward
+            .insert(FeedForward(Connection(Id(0), Weight(1.0), Id(2))));
+        to.feed_forward
+            .insert(FeedForward(Connection(Id(2), Weight(1.0), Id(1))));
+
+        let diff = from.diff(&to);
+
+        assert_eq!(diff.added_nodes.len(), 1);
+        assert_eq!(diff.added_connections.len(), 2);
+        assert_eq!(diff.removed_connections.len(), 1);
+        assert!(diff.changed_connections.is_empty());
+        assert!(from.diff(&from).is_empty());
+        assert!(diff.to_string().contains("+ node 2 (Tanh)"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+// origin of an individual, parents are ordered fitter first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ancestry {
+    pub parents: Vec<usize>,
+    pub generation: usize,
+}
+
+// parent ids of every individual ever created, only filled if parameters.setup.track_lineage is set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Genealogy {
+    pub records: HashMap<usize, Ancestry>,
+}
+
+impl Genealogy {
+    pub fn record(&mut self, id: usize, parents: Vec<usize>, generation: usize) {
+        self.records.insert(
+            id,
+            Ancestry {
+                parents,
+                generation,
+            },
+        );
+    }
+
+    pub fn ancestry(&self, id: usize) -> Option<&Ancestry> {
+        self.records.get(&id)
+    }
+
+    // every recorded ancestor of given individual, ascending by id
+    pub fn ancestors(&self, id: usize) -> Vec<usize> {
+        let mut ancestors = BTreeSet::new();
+        let mut pending = vec![id];
+
+        while let Some(current) = pending.pop() {
+            for &parent in self.parents(current) {
+                if ancestors.insert(parent) {
+                    pending.push(parent);
+                }
+            }
+        }
+
+        ancestors.into_iter().collect()
+    }
+
+    // chain of fitter parents from given individual back to the initial population
+    pub fn lineage(&self, id: usize) -> Vec<usize> {
+        let mut lineage = vec![id];
+
+        while let Some(&parent) = self.parents(*lineage.last().unwrap()).first() {
+            // a self-crossing records the same parent, guard against cycles anyway
+            if lineage.contains(&parent) {
+                break;
+            }
+            lineage.push(parent);
+        }
+
+        lineage
+    }
+
+    fn parents(&self, id: usize) -> &[usize] {
+        self.records
+            .get(&id)
+            .map(|ancestry| ancestry.parents.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // highest id recorded, as parent or child
+    pub fn max_id(&self) -> Option<usize> {
+        self.records
+            .iter()
+            .flat_map(|(&id, ancestry)| ancestry.parents.iter().cloned().chain(Some(id)))
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Genealogy;
+
+    #[test]
+    fn trace_lineage() {
+        let mut genealogy = Genealogy::default();
+        genealogy.record(0, vec![], 0);
+        genealogy.record(1, vec![], 0);
+        genealogy.record(2, vec![1, 0], 1);
+        genealogy.record(3, vec![2, 2], 2);
+
+        assert_eq!(genealogy.lineage(3), vec![3, 2, 1]);
+        assert_eq!(genealogy.ancestors(3), vec![0, 1, 2]);
+        assert_eq!(genealogy.max_id(), Some(3));
+    }
+}
@@ -1,13 +1,26 @@
 use crate::{
+    error::NeatError,
     genes::{
-        connections::{Connection, FeedForward, Recurrent},
+        connections::{Connection, FeedForward, Parallel, Recurrent},
         nodes::{Hidden, Input, Node, Output},
-        Activation, Genes, IdGenerator, Weight,
+        Activation, ActivationRegistry, AnnotatedGene, Annotation, Annotations, Gene, Genes, Id,
+        IdGenerator, Weight,
     },
-    parameters::Parameters,
+    individual::{
+        diff::GenomeDiff,
+        mutation,
+        validation::{self, Violation},
+    },
+    parameters::{CycleRepair, Parameters, SplitSelection},
     utility::rng::NeatRng,
 };
 
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    ops::{Deref, Range},
+};
+
 use rand::{
     prelude::{IteratorRandom, SliceRandom},
     Rng,
@@ -21,21 +34,254 @@ pub struct Genome {
     pub outputs: Genes<Output<Node>>,
     pub feed_forward: Genes<FeedForward<Connection>>,
     pub recurrent: Genes<Recurrent<Connection>>,
+    #[serde(default)]
+    pub mutation_rates: MutationRates,
+    // sizes of consecutive output groups in order of output ids, see parameters.setup.output_heads
+    #[serde(default)]
+    pub output_heads: Vec<usize>,
+    #[serde(default)]
+    pub parallel: Genes<Parallel>,
+    // user metadata of nodes and connections, see Genome::annotate
+    #[serde(default)]
+    pub annotations: Annotations,
+    // genes no mutation alters or removes, see Genome::freeze
+    #[serde(default)]
+    pub frozen: BTreeSet<AnnotatedGene>,
+    // connections evolving one weight together, see Genome::share_weight
+    #[serde(default)]
+    pub weight_groups: Vec<WeightGroup>,
+    // connections by input and output that are kept but not expressed, see Genome::toggle_connection
+    #[serde(default)]
+    pub disabled: BTreeSet<(Id, Id)>,
+}
+
+// weight of every listed connection, members join as soon as mutation adds them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightGroup {
+    pub connections: BTreeSet<(Id, Id)>,
+    pub weight: Weight,
+}
+
+// mutation parameters carried by the genome, only used if parameters.mutation.self_adaptive is set
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MutationRates {
+    pub weight_perturbation_std_dev: f64,
+    pub new_node_chance: f64,
+    pub new_connection_chance: f64,
+    pub change_activation_function_chance: f64,
+}
+
+// rates of the default parameters, i.e. for built or loaded genomes, zero rates would never adapt
+impl Default for MutationRates {
+    fn default() -> Self {
+        MutationRates::new(&Parameters::default())
+    }
+}
+
+impl MutationRates {
+    pub fn new(parameters: &Parameters) -> Self {
+        Self {
+            weight_perturbation_std_dev: parameters.mutation.weight_perturbation_std_dev,
+            new_node_chance: parameters.mutation.new_node_chance,
+            new_connection_chance: parameters.mutation.new_connection_chance,
+            change_activation_function_chance: parameters
+                .mutation
+                .change_activation_function_chance,
+        }
+    }
+
+    // log-normal self-adaptation as in evolution strategies
+    pub fn mutate(&mut self, rng: &mut NeatRng, learning_rate: f64) {
+        let mut adapt = |rate: &mut f64| *rate *= rng.perturbation(learning_rate).exp();
+
+        adapt(&mut self.weight_perturbation_std_dev);
+        adapt(&mut self.new_node_chance);
+        adapt(&mut self.new_connection_chance);
+        adapt(&mut self.change_activation_function_chance);
+
+        self.new_node_chance = self.new_node_chance.min(1.0);
+        self.new_connection_chance = self.new_connection_chance.min(1.0);
+        self.change_activation_function_chance = self.change_activation_function_chance.min(1.0);
+    }
+
+    // intermediate recombination
+    pub fn cross_in(&self, other: &Self) -> Self {
+        Self {
+            weight_perturbation_std_dev: (self.weight_perturbation_std_dev
+                + other.weight_perturbation_std_dev)
+                / 2.0,
+            new_node_chance: (self.new_node_chance + other.new_node_chance) / 2.0,
+            new_connection_chance: (self.new_connection_chance + other.new_connection_chance) / 2.0,
+            change_activation_function_chance: (self.change_activation_function_chance
+                + other.change_activation_function_chance)
+                / 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GenomeBuilder {
+    genome: Genome,
+}
+
+impl GenomeBuilder {
+    // starts from an existing genome
+    pub fn from_genome(genome: Genome) -> Self {
+        GenomeBuilder { genome }
+    }
+
+    pub fn input(mut self, id: Id) -> Self {
+        self.genome
+            .inputs
+            .replace(Input(Node::new(id, Activation::Linear)));
+        self
+    }
+
+    pub fn hidden(mut self, node: Node) -> Self {
+        self.genome.hidden.replace(Hidden(node));
+        self
+    }
+
+    pub fn output(mut self, node: Node) -> Self {
+        self.genome.outputs.replace(Output(node));
+        self
+    }
+
+    // replaces the weight if both nodes are connected already
+    pub fn connection(mut self, input: Id, weight: f64, output: Id) -> Self {
+        self.genome.feed_forward.replace(FeedForward(Connection(
+            input,
+            Weight::new(weight),
+            output,
+        )));
+        self
+    }
+
+    pub fn recurrent(mut self, input: Id, weight: f64, output: Id) -> Self {
+        self.genome
+            .recurrent
+            .replace(Recurrent(Connection(input, Weight::new(weight), output)));
+        self
+    }
+
+    pub fn annotate(mut self, gene: AnnotatedGene, annotation: Annotation) -> Self {
+        self.genome.annotate(gene, annotation);
+        self
+    }
+
+    pub fn freeze(mut self, gene: AnnotatedGene) -> Self {
+        self.genome.freeze(gene);
+        self
+    }
+
+    // freezes every gene added so far, i.e. a hand-designed core evolution only extends
+    pub fn freeze_all(mut self) -> Self {
+        self.genome.freeze_all();
+        self
+    }
+
+    pub fn share_weight(mut self, connections: &[(Id, Id)], weight: f64) -> Self {
+        self.genome.share_weight(connections, weight);
+        self
+    }
+
+    // fails on dangling connections, duplicate ids and feed forward cycles
+    pub fn build(mut self) -> Result<Genome, Vec<Violation>> {
+        self.genome.apply_shared_weights();
+        let defects = self.genome.defects();
+        if defects.is_empty() {
+            Ok(self.genome)
+        } else {
+            Err(defects)
+        }
+    }
 }
 
 impl Genome {
     pub fn new(id_gen: &mut IdGenerator, parameters: &Parameters) -> Self {
+        let inputs: Vec<Node> = (0..parameters.setup.input_dimension)
+            .map(|_| Node::new(id_gen.next_id(), Activation::Linear))
+            .collect();
+        let outputs: Vec<Node> = (0..parameters.setup.output_dimension)
+            .map(|index| Node::new(id_gen.next_id(), parameters.activations.output(index)))
+            .collect();
+
+        let annotations = inputs
+            .iter()
+            .zip(&parameters.setup.input_annotations)
+            .chain(outputs.iter().zip(&parameters.setup.output_annotations))
+            .map(|(node, annotation)| (AnnotatedGene::Node(node.id()), annotation.clone()))
+            .collect::<Vec<_>>()
+            .into();
+
         Genome {
-            inputs: (0..parameters.setup.input_dimension)
-                .map(|_| Input(Node(id_gen.next_id(), Activation::Linear)))
-                .collect(),
-            outputs: (0..parameters.setup.output_dimension)
-                .map(|_| Output(Node(id_gen.next_id(), parameters.activations.output_nodes)))
+            inputs: inputs.into_iter().map(Input).collect(),
+            outputs: outputs.into_iter().map(Output).collect(),
+            hidden: Self::initial_hidden(id_gen, parameters),
+            mutation_rates: MutationRates::new(parameters),
+            output_heads: parameters.setup.output_heads.clone(),
+            annotations,
+            // weights are drawn by Genome::init
+            weight_groups: parameters
+                .setup
+                .weight_groups
+                .iter()
+                .map(|connections| WeightGroup {
+                    connections: connections.iter().cloned().collect(),
+                    weight: Weight(0.0),
+                })
                 .collect(),
             ..Default::default()
         }
     }
 
+    // unconnected genome sharing input and output ids with self, i.e. to refill a population
+    pub fn fresh(&self, id_gen: &mut IdGenerator, parameters: &Parameters) -> Self {
+        let mut genome = Genome {
+            inputs: self
+                .inputs
+                .iterate_unwrapped()
+                .map(|node| Input(Node::new(node.id(), Activation::Linear)))
+                .collect(),
+            outputs: self
+                .outputs
+                .as_sorted_vec()
+                .into_iter()
+                .enumerate()
+                .map(|(index, node)| {
+                    Output(Node::new(node.id(), parameters.activations.output(index)))
+                })
+                .collect(),
+            hidden: Self::initial_hidden(id_gen, parameters),
+            mutation_rates: MutationRates::new(parameters),
+            output_heads: self.output_heads.clone(),
+            // annotations and freezing of inputs and outputs remain
+            annotations: self.annotations.clone(),
+            frozen: self.frozen.clone(),
+            weight_groups: self.weight_groups.clone(),
+            ..Default::default()
+        };
+        genome.prune_metadata();
+        genome
+    }
+
+    // nodes of parameters.setup.initial_hidden_layers
+    fn initial_hidden(id_gen: &mut IdGenerator, parameters: &Parameters) -> Genes<Hidden<Node>> {
+        (0..parameters.setup.initial_hidden_layers.iter().sum::<usize>())
+            .map(|_| {
+                Hidden(Node::new(
+                    id_gen.next_id(),
+                    parameters
+                        .activations
+                        .hidden_nodes
+                        .first()
+                        .cloned()
+                        .unwrap_or_default(),
+                ))
+            })
+            .collect()
+    }
+
     pub fn nodes(&self) -> impl Iterator<Item = &Node> {
         self.inputs
             .iterate_unwrapped()
@@ -43,23 +289,451 @@ impl Genome {
             .chain(self.outputs.iterate_unwrapped())
     }
 
+    // what changed from self to other, i.e. by mutation or crossover
+    pub fn diff(&self, other: &Genome) -> GenomeDiff {
+        GenomeDiff::new(self, other)
+    }
+
+    // every broken invariant, empty for a sound genome
+    pub fn validate(&self) -> Vec<Violation> {
+        validation::validate(self)
+    }
+
+    // custom activations loaded by name take the function registered under it, i.e. after loading a checkpoint
+    pub fn resolve_activations(
+        &mut self,
+        activations: &ActivationRegistry,
+    ) -> Result<(), NeatError> {
+        let resolve = |mut node: Node| -> Result<Node, NeatError> {
+            node.1 = activations.resolve(node.1)?;
+            Ok(node)
+        };
+
+        self.inputs = self
+            .inputs
+            .drain()
+            .map(|Input(node)| resolve(node).map(Input))
+            .collect::<Result<_, _>>()?;
+        self.hidden = self
+            .hidden
+            .drain()
+            .map(|Hidden(node)| resolve(node).map(Hidden))
+            .collect::<Result<_, _>>()?;
+        self.outputs = self
+            .outputs
+            .drain()
+            .map(|Output(node)| resolve(node).map(Output))
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    // violations that break evaluation, unreachable outputs might be intended
+    pub(crate) fn defects(&self) -> Vec<Violation> {
+        self.validate()
+            .into_iter()
+            .filter(|violation| !matches!(violation, Violation::UnreachableOutput(_)))
+            .collect()
+    }
+
+    // catches crate bugs in debug builds
+    pub(crate) fn debug_validate(&self) {
+        if cfg!(debug_assertions) {
+            let violations = self.defects();
+            assert!(violations.is_empty(), "invalid genome: {:?}", violations);
+        }
+    }
+
+    // assembles a genome with explicit ids, i.e. to hand craft or edit networks outside of evolution
+    pub fn builder() -> GenomeBuilder {
+        GenomeBuilder::default()
+    }
+
+    // positions of the outputs of every head within the network output
+    pub fn heads(&self) -> Vec<Range<usize>> {
+        if self.output_heads.is_empty() {
+            return vec![0..self.outputs.len()];
+        }
+
+        let mut start = 0;
+        self.output_heads
+            .iter()
+            .map(|&size| {
+                start += size;
+                start - size..start
+            })
+            .collect()
+    }
+
+    // network output split by head, i.e. to compute per head fitness
+    pub fn split_heads<'o>(&self, outputs: &'o [f64]) -> Vec<&'o [f64]> {
+        self.heads()
+            .into_iter()
+            .map(|head| &outputs[head.start.min(outputs.len())..head.end.min(outputs.len())])
+            .collect()
+    }
+
+    // hash of topology and every node and weight parameter, i.e. to recognize evaluated genomes,
+    // parallel connections count separately, so fingerprint expressed genomes to compare behavior
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        for (group, nodes) in [
+            self.inputs.as_sorted_vec(),
+            self.hidden.as_sorted_vec(),
+            self.outputs.as_sorted_vec(),
+        ]
+        .iter()
+        .enumerate()
+        {
+            group.hash(&mut hasher);
+            for node in nodes {
+                node.id().hash(&mut hasher);
+                node.1.hash(&mut hasher);
+                node.2 .0.to_bits().hash(&mut hasher);
+                node.3 .0.to_bits().hash(&mut hasher);
+                node.4 .0.to_bits().hash(&mut hasher);
+                node.5 .0.to_bits().hash(&mut hasher);
+                node.6.hash(&mut hasher);
+            }
+        }
+
+        let mut parallel: Vec<&Parallel> = self.parallel.iter().collect();
+        parallel.sort_unstable();
+        let parallel: Vec<&Connection> = parallel.into_iter().map(|parallel| &parallel.1).collect();
+        for (group, connections) in [
+            self.feed_forward.as_sorted_vec(),
+            self.recurrent.as_sorted_vec(),
+            parallel,
+        ]
+        .iter()
+        .enumerate()
+        {
+            group.hash(&mut hasher);
+            for connection in connections {
+                connection.input().hash(&mut hasher);
+                connection.output().hash(&mut hasher);
+                connection.1.value().to_bits().hash(&mut hasher);
+            }
+        }
+
+        self.output_heads.hash(&mut hasher);
+        self.disabled.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // hash of node ids and connection endpoints only, equal for genomes that differ in parameters
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        for (group, nodes) in [
+            self.inputs.as_sorted_vec(),
+            self.hidden.as_sorted_vec(),
+            self.outputs.as_sorted_vec(),
+        ]
+        .iter()
+        .enumerate()
+        {
+            group.hash(&mut hasher);
+            for node in nodes {
+                node.id().hash(&mut hasher);
+            }
+        }
+
+        for (group, connections) in [
+            self.feed_forward.as_sorted_vec(),
+            self.recurrent.as_sorted_vec(),
+        ]
+        .iter()
+        .enumerate()
+        {
+            group.hash(&mut hasher);
+            for connection in connections {
+                connection.input().hash(&mut hasher);
+                connection.output().hash(&mut hasher);
+            }
+        }
+
+        hasher.finish()
+    }
+
+    pub fn max_id(&self) -> Option<Id> {
+        self.nodes().map(|node| node.id()).max()
+    }
+
+    // value ranges of outputs ordered by node id, i.e. the order of the network outputs
+    pub fn output_ranges(&self) -> Vec<(f64, f64)> {
+        self.outputs
+            .as_sorted_vec()
+            .iter()
+            .map(|node| node.1.output_range())
+            .collect()
+    }
+
     pub fn init(&mut self, rng: &mut NeatRng, parameters: &Parameters) {
-        for input in self.inputs.iterate_with_random_offset(&mut rng.small).take(
-            (rng.small.gen::<f64>() * parameters.setup.input_dimension as f64).ceil() as usize,
-        ) {
+        for group in &mut self.weight_groups {
+            group.weight = Weight::new(rng.initial_weight(
+                parameters.mutation.weight_initialization,
+                group.connections.len(),
+            ));
+        }
+
+        if !parameters.setup.initial_hidden_layers.is_empty() {
+            self.init_layers(rng, parameters);
+            self.apply_shared_weights();
+            return;
+        }
+
+        let connected_inputs =
+            (rng.small.gen::<f64>() * parameters.setup.input_dimension as f64).ceil() as usize;
+        let initialization = parameters.mutation.weight_initialization;
+
+        for input in self
+            .inputs
+            .iterate_with_random_offset(&mut rng.small)
+            .take(connected_inputs)
+        {
             // connect to every output
             for output in self.outputs.iter() {
                 assert!(self.feed_forward.insert(FeedForward(Connection(
                     input.id(),
-                    Weight(rng.weight_perturbation()),
+                    Weight::new(rng.initial_weight(initialization, connected_inputs)),
                     output.id()
                 ))));
             }
         }
+        self.apply_shared_weights();
+    }
+
+    // fully connects inputs, hidden layers and outputs in order, hidden ids are assigned layer by layer
+    fn init_layers(&mut self, rng: &mut NeatRng, parameters: &Parameters) {
+        let hidden: Vec<Id> = self.hidden.as_sorted_vec().iter().map(|n| n.id()).collect();
+        let outputs: Vec<Id> = self
+            .outputs
+            .as_sorted_vec()
+            .iter()
+            .map(|n| n.id())
+            .collect();
+
+        let mut layers = Vec::new();
+        let mut offset = 0;
+        for &size in &parameters.setup.initial_hidden_layers {
+            layers.push(hidden[offset..offset + size].to_vec());
+            offset += size;
+        }
+        layers.push(outputs);
+
+        let mut previous: Vec<Id> = self.inputs.as_sorted_vec().iter().map(|n| n.id()).collect();
+        for layer in layers {
+            for &input in &previous {
+                for &output in &layer {
+                    assert!(self.feed_forward.insert(FeedForward(Connection(
+                        input,
+                        Weight::new(rng.initial_weight(
+                            parameters.mutation.weight_initialization,
+                            previous.len()
+                        )),
+                        output
+                    ))));
+                }
+            }
+            previous = layer;
+        }
     }
 
     pub fn len(&self) -> usize {
-        self.feed_forward.len() + self.recurrent.len()
+        self.feed_forward.len() + self.recurrent.len() + self.parallel.len()
+    }
+
+    // parallel connections summed into their feed forward connection, as seen by evaluation
+    pub fn expressed(&self) -> Genome {
+        let mut expressed = self.clone();
+        for parallel in self.parallel.iter() {
+            let base = FeedForward(parallel.1.clone());
+            if let Some(mut connection) = expressed.feed_forward.take(&base) {
+                connection.adjust_weight(parallel.1.value());
+                expressed.feed_forward.insert(connection);
+            }
+        }
+        expressed.parallel.clear();
+
+        for &(input, output) in &self.disabled {
+            expressed
+                .feed_forward
+                .remove(&FeedForward(Connection(input, Weight(0.0), output)));
+            expressed
+                .recurrent
+                .remove(&Recurrent(Connection(input, Weight(0.0), output)));
+        }
+        expressed.disabled.clear();
+        expressed
+    }
+
+    // parallel connections only exist next to their feed forward connection
+    fn prune_parallel(&mut self) {
+        let feed_forward = &self.feed_forward;
+        self.parallel
+            .retain(|parallel| feed_forward.contains(&FeedForward(parallel.1.clone())));
+    }
+
+    // adds to an existing annotation of the gene, kept as long as the gene exists
+    pub fn annotate(&mut self, gene: AnnotatedGene, annotation: Annotation) {
+        self.annotations.annotate(gene, annotation);
+    }
+
+    pub fn annotation(&self, gene: AnnotatedGene) -> Option<&Annotation> {
+        self.annotations.get(&gene)
+    }
+
+    pub fn freeze(&mut self, gene: AnnotatedGene) {
+        self.frozen.insert(gene);
+    }
+
+    pub fn freeze_all(&mut self) {
+        let genes: Vec<AnnotatedGene> = self
+            .nodes()
+            .map(|node| AnnotatedGene::Node(node.id()))
+            .chain(
+                self.feed_forward
+                    .iterate_unwrapped()
+                    .chain(self.recurrent.iterate_unwrapped())
+                    .map(connection_gene),
+            )
+            .collect();
+        self.frozen.extend(genes);
+    }
+
+    pub fn is_frozen(&self, gene: AnnotatedGene) -> bool {
+        self.frozen.contains(&gene)
+    }
+
+    // renames hidden nodes and parallel connections along with everything referring to them, other ids stay
+    pub(crate) fn rename_ids(&mut self, ids: &HashMap<Id, Id>) {
+        let rename = |id: Id| ids.get(&id).cloned().unwrap_or(id);
+        let rename_connection = |connection: &Connection| {
+            Connection(rename(connection.0), connection.1, rename(connection.2))
+        };
+
+        self.hidden = self
+            .hidden
+            .drain()
+            .map(|mut node| {
+                (node.0).0 = rename(node.id());
+                node
+            })
+            .collect();
+        self.feed_forward = self
+            .feed_forward
+            .drain()
+            .map(|connection| FeedForward(rename_connection(&*connection)))
+            .collect();
+        self.recurrent = self
+            .recurrent
+            .drain()
+            .map(|connection| Recurrent(rename_connection(&*connection)))
+            .collect();
+        self.parallel = self
+            .parallel
+            .drain()
+            .map(|parallel| Parallel(rename(parallel.0), rename_connection(&parallel.1)))
+            .collect();
+
+        let rename_gene = |gene: AnnotatedGene| match gene {
+            AnnotatedGene::Node(id) => AnnotatedGene::Node(rename(id)),
+            AnnotatedGene::Connection(input, output) => {
+                AnnotatedGene::Connection(rename(input), rename(output))
+            }
+        };
+        let annotations: Vec<(AnnotatedGene, Annotation)> = self
+            .annotations
+            .iter()
+            .map(|(&gene, annotation)| (rename_gene(gene), annotation.clone()))
+            .collect();
+        self.annotations = annotations.into();
+        self.frozen = self.frozen.iter().map(|&gene| rename_gene(gene)).collect();
+        for group in &mut self.weight_groups {
+            group.connections = group
+                .connections
+                .iter()
+                .map(|&(input, output)| (rename(input), rename(output)))
+                .collect();
+        }
+    }
+
+    // connections leave any previous group and evolve one weight from now on
+    pub fn share_weight(&mut self, connections: &[(Id, Id)], weight: f64) {
+        for group in &mut self.weight_groups {
+            for connection in connections {
+                group.connections.remove(connection);
+            }
+        }
+        self.weight_groups
+            .retain(|group| !group.connections.is_empty());
+        self.weight_groups.push(WeightGroup {
+            connections: connections.iter().cloned().collect(),
+            weight: Weight::new(weight),
+        });
+        self.apply_shared_weights();
+    }
+
+    // group of the connection, if any
+    pub fn weight_group(&self, connection: (Id, Id)) -> Option<&WeightGroup> {
+        self.weight_groups
+            .iter()
+            .find(|group| group.connections.contains(&connection))
+    }
+
+    // sets the weight of every present group member, frozen ones included as their group is frozen along
+    pub(crate) fn apply_shared_weights(&mut self) {
+        for group in &self.weight_groups {
+            for &(input, output) in &group.connections {
+                let feed_forward = FeedForward(Connection(input, group.weight, output));
+                if self.feed_forward.contains(&feed_forward) {
+                    self.feed_forward.replace(feed_forward);
+                }
+                let recurrent = Recurrent(Connection(input, group.weight, output));
+                if self.recurrent.contains(&recurrent) {
+                    self.recurrent.replace(recurrent);
+                }
+            }
+        }
+    }
+
+    // same choice as Genes::random as long as nothing is frozen
+    fn random_unfrozen<'a, T: Gene + Deref<Target = U>, U: 'a>(
+        &self,
+        genes: &'a Genes<T>,
+        rng: &mut NeatRng,
+        gene: impl Fn(&U) -> AnnotatedGene,
+    ) -> Option<&'a T> {
+        if self.frozen.is_empty() {
+            genes.random(&mut rng.small)
+        } else {
+            genes
+                .iter()
+                .filter(|candidate| !self.is_frozen(gene(&***candidate)))
+                .choose(&mut rng.small)
+        }
+    }
+
+    // annotations and frozen flags of genes not in self
+    fn prune_metadata(&mut self) {
+        let nodes: HashSet<Id> = self.nodes().map(Node::id).collect();
+        let connections: HashSet<(Id, Id)> = self
+            .feed_forward
+            .iterate_unwrapped()
+            .chain(self.recurrent.iterate_unwrapped())
+            .map(Connection::id)
+            .collect();
+
+        let exists = |gene: &AnnotatedGene| match *gene {
+            AnnotatedGene::Node(id) => nodes.contains(&id),
+            AnnotatedGene::Connection(input, output) => connections.contains(&(input, output)),
+        };
+
+        self.annotations.retain(|gene, _| exists(gene));
+        self.frozen.retain(|gene| exists(gene));
+        self.disabled
+            .retain(|connection| connections.contains(connection));
     }
 
     pub fn is_empty(&self) -> bool {
@@ -73,44 +747,157 @@ impl Genome {
 
         let hidden = self.hidden.cross_in(&other.hidden, rng);
 
-        Genome {
+        let mut genome = Genome {
             feed_forward,
             recurrent,
             hidden,
-            // use input and outputs from fitter, but they should be identical with weaker
+            // use inputs from fitter, but they should be identical with weaker
             inputs: self.inputs.clone(),
-            outputs: self.outputs.clone(),
+            // outputs match by id, cross them to inherit biases and responses
+            outputs: self.outputs.cross_in(&other.outputs, rng),
+            mutation_rates: self.mutation_rates.cross_in(&other.mutation_rates),
+            output_heads: self.output_heads.clone(),
+            parallel: self.parallel.cross_in(&other.parallel, rng),
+            annotations: self.annotations.cross_in(&other.annotations),
+            frozen: self.frozen.union(&other.frozen).cloned().collect(),
+            weight_groups: self.weight_groups.clone(),
+            disabled: self.disabled.clone(),
+        };
+        genome.prune_parallel();
+        genome.prune_metadata();
+        genome.apply_shared_weights();
+        genome
+    }
+
+    // breaks every feed forward cycle at the connection closing it, or the last one not frozen before it,
+    // returns the number of connections removed
+    pub fn repair_cycles(&mut self, repair: CycleRepair) -> usize {
+        let mut repaired = 0;
+
+        while let Some(cycle) = validation::feed_forward_cycle(self) {
+            let edges: Vec<(Id, Id)> = cycle
+                .iter()
+                .cloned()
+                .zip(cycle.iter().skip(1).chain(cycle.first()).cloned())
+                .collect();
+
+            let closing = match edges
+                .iter()
+                .rev()
+                .filter(|&&(input, output)| {
+                    !self.is_frozen(AnnotatedGene::Connection(input, output))
+                })
+                .find_map(|&(input, output)| {
+                    self.feed_forward.iter().find(|connection| {
+                        connection.input() == input && connection.output() == output
+                    })
+                }) {
+                Some(connection) => connection.clone(),
+                None => break,
+            };
+
+            self.feed_forward.remove(&closing);
+            self.prune_parallel();
+            if repair == CycleRepair::MakeRecurrent {
+                let recurrent = Recurrent(closing.0);
+                if !self.recurrent.contains(&recurrent) {
+                    self.recurrent.insert(recurrent);
+                }
+            }
+            repaired += 1;
         }
+
+        repaired
     }
 
-    pub fn mutate(&mut self, rng: &mut NeatRng, id_gen: &mut IdGenerator, parameters: &Parameters) {
+    // returns the number of structural mutations skipped for exceeding a cap
+    pub fn mutate(
+        &mut self,
+        rng: &mut NeatRng,
+        id_gen: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> usize {
+        let rates = self.mutate_parameters(rng, parameters);
+        self.mutate_structure(rng, id_gen, parameters, rates)
+    }
+
+    // mutations that need no new ids, returns the rates to use for structural mutations
+    pub fn mutate_parameters(
+        &mut self,
+        rng: &mut NeatRng,
+        parameters: &Parameters,
+    ) -> MutationRates {
+        // genome carried rates evolve themselves before being applied
+        let rates = if parameters.mutation.self_adaptive {
+            self.mutation_rates
+                .mutate(rng, parameters.mutation.self_adaptation_learning_rate);
+            self.mutation_rates
+        } else {
+            MutationRates::new(parameters)
+        };
+
         // mutate weigths
         // if context.gamble(parameters.mutation.weight) {
-        self.change_weights(rng);
+        self.change_weights(rng, rates.weight_perturbation_std_dev);
         // }
 
-        // mutate connection gene
-        if rng.gamble(parameters.mutation.new_connection_chance) {
-            self.add_connection(rng, parameters).unwrap_or_default();
-        }
+        // mutate biases and responses
+        self.change_node_parameters(rng, parameters);
 
-        // mutate node gene
-        if rng.gamble(parameters.mutation.new_node_chance) {
-            self.add_node(rng, id_gen, parameters);
-        }
+        rates
+    }
 
-        // change some activation
-        if rng.gamble(parameters.mutation.change_activation_function_chance) {
-            self.alter_activation(rng, parameters);
+    pub fn mutate_structure(
+        &mut self,
+        rng: &mut NeatRng,
+        id_gen: &mut IdGenerator,
+        parameters: &Parameters,
+        rates: MutationRates,
+    ) -> usize {
+        let budget = parameters.mutation.max_structural_mutations_per_offspring;
+        let mut skipped = 0;
+        let mut applied = 0;
+        // sample structural mutations until as many were applied as the budget allows,
+        // or a whole pass over the pipeline applied none, without a budget in a single pass
+        'sampling: loop {
+            let mut applied_this_pass = false;
+            for operator in mutation::pipeline(parameters) {
+                if budget == Some(applied) {
+                    break 'sampling;
+                }
+                if rng.gamble(operator.probability(&rates, parameters)) {
+                    if mutation::exceeds_caps(operator, self, parameters) {
+                        skipped += 1;
+                        continue;
+                    }
+                    if operator.mutate(self, rng, id_gen, parameters).is_ok() {
+                        applied += 1;
+                        applied_this_pass = true;
+                    }
+                }
+            }
+            if budget.is_none() || !applied_this_pass {
+                break;
+            }
         }
+        // added connections take the weight of their group
+        self.apply_shared_weights();
+        // every structural mutation ends here, i.e. reproduction and duplicate retries
+        self.debug_validate();
+        skipped
     }
 
-    pub fn change_weights(&mut self, rng: &mut NeatRng) {
+    pub fn change_weights(&mut self, rng: &mut NeatRng, std_dev: f64) {
+        let frozen = &self.frozen;
+        let is_frozen = |connection: &Connection| frozen.contains(&connection_gene(connection));
+
         self.feed_forward = self
             .feed_forward
             .drain_into_random(&mut rng.small)
             .map(|mut connection| {
-                connection.adjust_weight(rng.weight_perturbation());
+                if !is_frozen(&connection) {
+                    connection.adjust_weight(rng.perturbation(std_dev));
+                }
                 connection
             })
             .collect();
@@ -119,25 +906,113 @@ impl Genome {
             .recurrent
             .drain_into_random(&mut rng.small)
             .map(|mut connection| {
-                connection.adjust_weight(rng.weight_perturbation());
+                if !is_frozen(&connection) {
+                    connection.adjust_weight(rng.perturbation(std_dev));
+                }
                 connection
             })
             .collect();
+
+        self.parallel = self
+            .parallel
+            .drain_into_random(&mut rng.small)
+            .map(|mut parallel| {
+                parallel.adjust_weight(rng.perturbation(std_dev));
+                parallel
+            })
+            .collect();
+
+        // groups are perturbed once, unless one of their members is frozen
+        for group in &mut self.weight_groups {
+            if !group
+                .connections
+                .iter()
+                .any(|&(input, output)| frozen.contains(&AnnotatedGene::Connection(input, output)))
+            {
+                group.weight = Weight::new(group.weight.value() + rng.perturbation(std_dev));
+            }
+        }
+        self.apply_shared_weights();
+    }
+
+    pub fn change_node_parameters(&mut self, rng: &mut NeatRng, parameters: &Parameters) {
+        let bias_std_dev = parameters.mutation.bias_perturbation_std_dev;
+        let response_std_dev = parameters.mutation.response_perturbation_std_dev;
+        let coefficient_std_dev = parameters.mutation.coefficient_perturbation_std_dev;
+        let time_constant_std_dev = parameters.mutation.time_constant_perturbation_std_dev;
+
+        let mut perturb = |node: &mut Node| {
+            if bias_std_dev > 0.0 {
+                node.adjust_bias(rng.perturbation(bias_std_dev));
+            }
+            if response_std_dev > 0.0 {
+                node.adjust_response(rng.perturbation(response_std_dev));
+            }
+            if coefficient_std_dev > 0.0 && node.1.is_parameterized() {
+                node.adjust_coefficient(rng.perturbation(coefficient_std_dev));
+            }
+            if time_constant_std_dev > 0.0 {
+                node.adjust_time_constant(rng.perturbation(time_constant_std_dev));
+            }
+        };
+
+        let frozen = &self.frozen;
+        let is_frozen = |node: &Node| frozen.contains(&AnnotatedGene::Node(node.id()));
+
+        self.hidden = self
+            .hidden
+            .drain()
+            .map(|mut node| {
+                if !is_frozen(&node) {
+                    perturb(&mut node);
+                }
+                node
+            })
+            .collect();
+
+        self.outputs = self
+            .outputs
+            .drain()
+            .map(|mut node| {
+                if !is_frozen(&node) {
+                    perturb(&mut node);
+                }
+                node
+            })
+            .collect();
     }
 
     pub fn alter_activation(&mut self, rng: &mut NeatRng, parameters: &Parameters) {
-        if let Some(node) = self.hidden.random(&mut rng.small) {
-            let updated = Hidden(Node(
-                node.id(),
-                parameters
-                    .activations
-                    .hidden_nodes
-                    .iter()
-                    .filter(|&&activation| activation != node.1)
-                    .choose(&mut rng.small)
-                    .cloned()
-                    .unwrap_or(node.1),
-            ));
+        if let Some(node) = self.random_unfrozen(&self.hidden, rng, |node: &Node| {
+            AnnotatedGene::Node(node.id())
+        }) {
+            let mut updated = node.clone();
+            updated.1 = parameters
+                .activations
+                .hidden_nodes
+                .iter()
+                .filter(|&&activation| activation != node.1)
+                .choose(&mut rng.small)
+                .cloned()
+                .unwrap_or(node.1);
+
+            self.hidden.replace(updated);
+        }
+    }
+
+    pub fn alter_aggregation(&mut self, rng: &mut NeatRng, parameters: &Parameters) {
+        if let Some(node) = self.random_unfrozen(&self.hidden, rng, |node: &Node| {
+            AnnotatedGene::Node(node.id())
+        }) {
+            let mut updated = node.clone();
+            updated.6 = parameters
+                .activations
+                .aggregations
+                .iter()
+                .filter(|&&aggregation| aggregation != node.6)
+                .choose(&mut rng.small)
+                .cloned()
+                .unwrap_or(node.6);
 
             self.hidden.replace(updated);
         }
@@ -148,29 +1023,73 @@ impl Genome {
         rng: &mut NeatRng,
         id_gen: &mut IdGenerator,
         parameters: &Parameters,
-    ) {
-        // select an connection gene and split
-        let mut random_connection = self.feed_forward.random(&mut rng.small).cloned().unwrap();
+    ) -> Result<(), &'static str> {
+        // select an connection gene and split, frozen ones keep their weight and are never split,
+        // disabled ones were split before or carry nothing
+        let weighted = match parameters.mutation.split_selection {
+            SplitSelection::Uniform => None,
+            SplitSelection::WeightMagnitude => self
+                .feed_forward
+                .as_sorted_vec()
+                .into_iter()
+                .filter(|connection| {
+                    !self.is_frozen(connection_gene(connection))
+                        && !self.disabled.contains(&connection.id())
+                })
+                .collect::<Vec<&Connection>>()
+                .choose_weighted(&mut rng.small, |connection| connection.1.value().abs())
+                .ok()
+                .map(|&connection| FeedForward(connection.clone())),
+        };
+
+        // falls back to uniform choice if all weights are zero
+        let random_connection = match weighted {
+            Some(connection) => connection,
+            None if self.disabled.is_empty() => self
+                .random_unfrozen(&self.feed_forward, rng, connection_gene)
+                .cloned()
+                .ok_or("no connection to split")?,
+            None => self
+                .feed_forward
+                .iter()
+                .filter(|connection| {
+                    !self.is_frozen(connection_gene(connection))
+                        && !self.disabled.contains(&connection.id())
+                })
+                .choose(&mut rng.small)
+                .cloned()
+                .ok_or("no connection to split")?,
+        };
 
         let id = id_gen
             .cached_id_iter(random_connection.id())
             .find(|&id| {
                 self.hidden
-                    .get(&Hidden(Node(id, Activation::Linear)))
+                    .get(&Hidden(Node::new(id, Activation::Linear)))
                     .is_none()
             })
             .unwrap();
 
         // construct new node gene
-        let new_node = Hidden(Node(
-            id,
-            parameters
-                .activations
-                .hidden_nodes
-                .choose(&mut rng.small)
-                .cloned()
-                .unwrap(),
-        ));
+        let new_node = Hidden(
+            Node::new(
+                id,
+                parameters
+                    .activations
+                    .hidden_nodes
+                    .choose(&mut rng.small)
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+            .with_aggregation(
+                parameters
+                    .activations
+                    .aggregations
+                    .choose(&mut rng.small)
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+        );
 
         // insert new connection pointing to new node
         assert!(self.feed_forward.insert(FeedForward(Connection(
@@ -178,18 +1097,38 @@ impl Genome {
             Weight(1.0),
             new_node.id(),
         ))));
+        // parallel connections of the split one are folded into the connection from the new node,
+        // so the split pathway carries nothing anymore
+        let split = random_connection.id();
+        let parallel_weight: f64 = self
+            .parallel
+            .iter()
+            .filter(|parallel| parallel.id() == split)
+            .map(|parallel| parallel.1.value())
+            .sum();
+        self.parallel.retain(|parallel| parallel.id() != split);
+
         // insert new connection pointing from new node
         assert!(self.feed_forward.insert(FeedForward(Connection(
             new_node.id(),
-            random_connection.1,
+            Weight::new(random_connection.1.value() + parallel_weight),
             random_connection.output(),
         ))));
         // insert new node into genome
         assert!(self.hidden.insert(new_node));
 
-        // update weight to zero to 'deactivate' connnection
-        random_connection.1 = Weight(0.0);
-        self.feed_forward.replace(random_connection);
+        // the connection from the new node carries the weight on, so it takes over group membership
+        for group in &mut self.weight_groups {
+            if group.connections.remove(&split) {
+                group.connections.insert((id, random_connection.output()));
+            }
+        }
+
+        // disable instead of zeroing the weight, so the connection takes no part in aggregations
+        // other than sum, i.e. a product of the new node's inputs
+        self.disabled.insert(split);
+
+        Ok(())
     }
 
     pub fn add_connection(
@@ -221,21 +1160,26 @@ impl Genome {
             .take(self.inputs.len() + self.hidden.len())
         {
             if let Some(end_node) = end_node_iterator.clone().find(|&end_node| {
+                // self connections are added by Genome::add_self_connection only
                 end_node != start_node
                     && !self.are_connected(&start_node, end_node, is_recurrent)
                     && (is_recurrent || !self.would_form_cycle(start_node, end_node))
             }) {
+                let weight = Weight::new(rng.initial_weight(
+                    parameters.mutation.weight_initialization,
+                    self.fan_in(end_node.id()) + 1,
+                ));
                 if is_recurrent {
                     assert!(self.recurrent.insert(Recurrent(Connection(
                         start_node.id(),
-                        Weight(rng.weight_perturbation()),
+                        weight,
                         end_node.id(),
                     ))));
                 } else {
                     // add new feed-forward connection
                     assert!(self.feed_forward.insert(FeedForward(Connection(
                         start_node.id(),
-                        Weight(rng.weight_perturbation()),
+                        weight,
                         end_node.id(),
                     ))));
                 }
@@ -246,6 +1190,84 @@ impl Genome {
         Err("no connection possible")
     }
 
+    // hidden or output node without self loop gets one
+    pub fn add_self_connection(
+        &mut self,
+        rng: &mut NeatRng,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        let node = self
+            .hidden
+            .iterate_unwrapped()
+            .chain(self.outputs.iterate_unwrapped())
+            .filter(|&node| !self.are_connected(node, node, true))
+            .map(Node::id)
+            .choose(&mut rng.small)
+            .ok_or("no self connection possible")?;
+
+        let weight = Weight::new(rng.initial_weight(
+            parameters.mutation.weight_initialization,
+            self.fan_in(node) + 1,
+        ));
+        self.recurrent
+            .insert(Recurrent(Connection(node, weight, node)));
+
+        Ok(())
+    }
+
+    // parallel connections get fresh ids, so they are never matched to ones of other genomes by structure
+    pub fn add_parallel_connection(
+        &mut self,
+        rng: &mut NeatRng,
+        id_gen: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        // would alter the expressed weight of a frozen connection
+        let (input, output) = self
+            .random_unfrozen(&self.feed_forward, rng, connection_gene)
+            .map(|connection| connection.id())
+            .ok_or("no connection to add a parallel one to")?;
+
+        let weight = Weight::new(rng.initial_weight(
+            parameters.mutation.weight_initialization,
+            self.fan_in(output) + 1,
+        ));
+        self.parallel.insert(Parallel(
+            id_gen.next_id(),
+            Connection(input, weight, output),
+        ));
+
+        Ok(())
+    }
+
+    // disables an enabled or enables a disabled connection, frozen ones are left alone
+    pub fn toggle_connection(&mut self, rng: &mut NeatRng) -> Result<(), &'static str> {
+        let frozen = &self.frozen;
+        let connection = self
+            .feed_forward
+            .iterate_unwrapped()
+            .chain(self.recurrent.iterate_unwrapped())
+            .filter(|connection| !frozen.contains(&connection_gene(connection)))
+            .map(Connection::id)
+            .choose(&mut rng.small)
+            .ok_or("no connection to toggle")?;
+
+        if !self.disabled.remove(&connection) {
+            self.disabled.insert(connection);
+        }
+        Ok(())
+    }
+
+    // incoming feed forward, recurrent and parallel connections of a node
+    fn fan_in(&self, node: Id) -> usize {
+        self.feed_forward
+            .iterate_unwrapped()
+            .chain(self.recurrent.iterate_unwrapped())
+            .chain(self.parallel.iterate_unwrapped())
+            .filter(|connection| connection.output() == node)
+            .count()
+    }
+
     // check if to nodes are connected
     fn are_connected(&self, start_node: &Node, end_node: &Node, recurrent: bool) -> bool {
         if recurrent {
@@ -264,7 +1286,7 @@ impl Genome {
     }
 
     // can only operate when no cycles present yet, which is assumed
-    fn would_form_cycle(&self, start_node: &Node, end_node: &Node) -> bool {
+    pub(crate) fn would_form_cycle(&self, start_node: &Node, end_node: &Node) -> bool {
         // needs to detect if there is a path from end to start
         let mut possible_paths: Vec<&FeedForward<Connection>> = self
             .feed_forward
@@ -295,8 +1317,511 @@ impl Genome {
     }
 }
 
+fn connection_gene(connection: &Connection) -> AnnotatedGene {
+    AnnotatedGene::Connection(connection.input(), connection.output())
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use super::Genome;
+    use crate::{
+        genes::{
+            connections::{Connection, FeedForward, Recurrent},
+            nodes::{Hidden, Input, Node, Output},
+            Activation, Aggregation, AnnotatedGene, Annotation, Id, IdGenerator, Weight,
+        },
+        parameters::{CycleRepair, Parameters, SplitSelection, WeightInitialization},
+        utility::rng::NeatRng,
+    };
+
+    fn single_hidden_node_genome() -> Genome {
+        Genome {
+            inputs: vec![Input(Node::new(Id(0), Activation::Linear))]
+                .into_iter()
+                .collect(),
+            outputs: vec![Output(Node::new(Id(1), Activation::Linear))]
+                .into_iter()
+                .collect(),
+            hidden: vec![Hidden(Node::new(Id(2), Activation::Tanh))]
+                .into_iter()
+                .collect(),
+            feed_forward: vec![
+                FeedForward(Connection(Id(0), Weight(1.0), Id(2))),
+                FeedForward(Connection(Id(2), Weight(1.0), Id(1))),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn no_self_connection_by_add_connection() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.connection_is_recurrent_chance = 1.0;
+
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut genome = single_hidden_node_genome();
+
+        while genome.add_connection(&mut rng, &parameters).is_ok() {}
+
+        assert!(!genome
+            .recurrent
+            .contains(&Recurrent(Connection(Id(2), Weight(0.0), Id(2)))));
+    }
+
+    #[test]
+    fn split_by_weight_magnitude() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.split_selection = SplitSelection::WeightMagnitude;
+
+        for seed in 0..10 {
+            let mut rng = NeatRng::new(seed, 1.0);
+            let mut id_gen = IdGenerator::default();
+            id_gen.skip_past(Id(2));
+
+            let mut genome = single_hidden_node_genome();
+            // connection without weight is never split
+            genome
+                .feed_forward
+                .replace(FeedForward(Connection(Id(2), Weight(0.0), Id(1))));
+            genome
+                .feed_forward
+                .replace(FeedForward(Connection(Id(0), Weight(2.0), Id(2))));
+
+            assert!(genome.add_node(&mut rng, &mut id_gen, &parameters).is_ok());
+
+            assert!(genome.disabled.contains(&(Id(0), Id(2))));
+            assert_eq!(genome.hidden.len(), 2);
+        }
+    }
+
+    #[test]
+    fn init_hidden_layers() {
+        let mut parameters: Parameters = Default::default();
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+        parameters.setup.initial_hidden_layers = vec![3, 2];
+
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+
+        let mut genome = Genome::new(&mut id_gen, &parameters);
+        genome.init(&mut rng, &parameters);
+
+        assert_eq!(genome.hidden.len(), 5);
+        assert_eq!(genome.feed_forward.len(), 2 * 3 + 3 * 2 + 2 * 1);
+        // ids of hidden nodes are registered
+        assert_eq!(id_gen.next_id(), Id(8));
+    }
+
+    #[test]
+    fn init_uniform_weights() {
+        let mut parameters = Parameters::default();
+        parameters.setup.input_dimension = 4;
+        parameters.setup.output_dimension = 3;
+        parameters.mutation.weight_initialization = WeightInitialization::Uniform(0.5, 0.75);
+
+        let mut rng = NeatRng::new(42, 10.0);
+        let mut genome = Genome::new(&mut IdGenerator::default(), &parameters);
+        genome.init(&mut rng, &parameters);
+
+        assert!(!genome.feed_forward.is_empty());
+        assert!(genome
+            .feed_forward
+            .iter()
+            .all(|connection| (0.5..0.75).contains(&connection.1.value())));
+    }
+
+    #[test]
+    fn add_self_connections() {
+        let mut rng = NeatRng::new(42, 1.0);
+        let parameters = Parameters::default();
+        let mut genome = single_hidden_node_genome();
+
+        // hidden and output node
+        assert!(genome.add_self_connection(&mut rng, &parameters).is_ok());
+        assert!(genome.add_self_connection(&mut rng, &parameters).is_ok());
+        assert!(genome.add_self_connection(&mut rng, &parameters).is_err());
+
+        assert!(genome
+            .recurrent
+            .iter()
+            .all(|connection| connection.input() == connection.output()));
+        assert_eq!(genome.recurrent.len(), 2);
+    }
+
+    #[test]
+    fn build_genome_with_explicit_ids() {
+        let genome = Genome::builder()
+            .input(Id(0))
+            .hidden(Node::new(Id(2), Activation::Tanh).with_bias(0.5))
+            .output(Node::new(Id(1), Activation::Linear))
+            .connection(Id(0), 1.0, Id(2))
+            .connection(Id(2), 0.5, Id(1))
+            .recurrent(Id(2), 0.1, Id(2))
+            .build()
+            .unwrap();
+
+        assert_eq!(genome.len(), 3);
+        assert!(genome.validate().is_empty());
+
+        // dangling connection
+        assert!(Genome::builder()
+            .input(Id(0))
+            .connection(Id(0), 1.0, Id(1))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn split_outputs_by_head() {
+        let mut parameters: Parameters = Default::default();
+        parameters.setup.output_dimension = 3;
+        parameters.setup.output_heads = vec![1, 2];
+
+        let genome = Genome::new(&mut IdGenerator::default(), &parameters);
+
+        assert_eq!(genome.heads(), vec![0..1, 1..3]);
+        assert_eq!(
+            genome.split_heads(&[0.1, 0.2, 0.3]),
+            vec![&[0.1][..], &[0.2, 0.3][..]]
+        );
+    }
+
+    #[test]
+    fn repair_crossover_cycle() {
+        let mut offspring = single_hidden_node_genome();
+        offspring
+            .hidden
+            .insert(Hidden(Node::new(Id(3), Activation::Tanh)));
+
+        // mirrored connections inherited from either parent
+        offspring
+            .feed_forward
+            .insert(FeedForward(Connection(Id(2), Weight(1.0), Id(3))));
+        offspring
+            .feed_forward
+            .insert(FeedForward(Connection(Id(3), Weight(1.0), Id(2))));
+
+        let mut dropped = offspring.clone();
+        assert_eq!(dropped.repair_cycles(CycleRepair::Drop), 1);
+        assert!(dropped.validate().is_empty());
+        assert_eq!(dropped.recurrent.len(), offspring.recurrent.len());
+
+        assert_eq!(offspring.repair_cycles(CycleRepair::MakeRecurrent), 1);
+        assert!(offspring.validate().is_empty());
+        assert!(offspring
+            .recurrent
+            .contains(&Recurrent(Connection(Id(3), Weight(1.0), Id(2)))));
+    }
+
+    #[test]
+    fn sum_parallel_connections() {
+        let parameters: Parameters = Default::default();
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+        id_gen.skip_past(Id(2));
+
+        let mut genome = single_hidden_node_genome();
+        genome
+            .add_parallel_connection(&mut rng, &mut id_gen, &parameters)
+            .unwrap();
+        genome
+            .add_parallel_connection(&mut rng, &mut id_gen, &parameters)
+            .unwrap();
+
+        assert_eq!(genome.parallel.len(), 2);
+        assert_eq!(genome.len(), 4);
+
+        let expressed = genome.expressed();
+        let total = |genome: &Genome| -> f64 {
+            genome
+                .feed_forward
+                .iterate_unwrapped()
+                .chain(genome.parallel.iterate_unwrapped())
+                .map(|connection| connection.1.value())
+                .sum()
+        };
+
+        assert!(expressed.parallel.is_empty());
+        assert_eq!(expressed.feed_forward.len(), 2);
+        assert!((total(&expressed) - total(&genome)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn alter_hidden_aggregation() {
+        let mut parameters: Parameters = Default::default();
+        parameters.activations.aggregations = vec![Aggregation::Sum, Aggregation::Product];
+        let mut rng = NeatRng::new(42, 1.0);
+
+        let mut genome = single_hidden_node_genome();
+        genome.alter_aggregation(&mut rng, &parameters);
+
+        assert_eq!(genome.hidden.iter().next().unwrap().6, Aggregation::Product);
+    }
+
+    #[test]
+    fn keep_annotations_of_existing_genes() {
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut fitter = single_hidden_node_genome();
+        fitter.annotate(
+            AnnotatedGene::Node(Id(0)),
+            Annotation::tagged("sensor group A"),
+        );
+
+        let mut weaker = single_hidden_node_genome();
+        weaker.annotate(
+            AnnotatedGene::Connection(Id(2), Id(1)),
+            Annotation::default().with_value("role", "relay"),
+        );
+        // gene only the weaker parent has
+        weaker.annotate(AnnotatedGene::Node(Id(3)), Annotation::tagged("lost"));
+
+        let offspring = fitter.cross_in(&weaker, &mut rng.small);
+        let offspring: Genome =
+            serde_json::from_str(&serde_json::to_string(&offspring).unwrap()).unwrap();
+
+        assert!(offspring
+            .annotation(AnnotatedGene::Node(Id(0)))
+            .unwrap()
+            .has_tag("sensor group A"));
+        assert_eq!(
+            offspring
+                .annotation(AnnotatedGene::Connection(Id(2), Id(1)))
+                .unwrap()
+                .value("role"),
+            Some("relay")
+        );
+        assert!(offspring.annotation(AnnotatedGene::Node(Id(3))).is_none());
+    }
+
+    #[test]
+    fn never_mutate_frozen_genes() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.new_node_chance = 1.0;
+        parameters.mutation.new_connection_chance = 0.0;
+        parameters.mutation.change_activation_function_chance = 1.0;
+        parameters.mutation.bias_perturbation_std_dev = 1.0;
+        parameters.mutation.parallel_connection_chance = 1.0;
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+        id_gen.skip_past(Id(2));
+
+        let mut genome = single_hidden_node_genome();
+        genome.freeze_all();
+        let frozen = genome.clone();
+
+        for _ in 0..20 {
+            genome.mutate(&mut rng, &mut id_gen, &parameters);
+        }
+
+        for node in frozen.nodes() {
+            let mutated = genome
+                .nodes()
+                .find(|other| other.id() == node.id())
+                .unwrap();
+            assert_eq!((mutated.1, mutated.2 .0), (node.1, node.2 .0));
+        }
+        for connection in frozen.feed_forward.iter() {
+            let mutated = genome.feed_forward.get(connection).unwrap();
+            assert_eq!(mutated.1.value(), connection.1.value());
+        }
+        // nothing unfrozen to split or to add a parallel connection to
+        assert!(genome.parallel.is_empty());
+        assert_eq!(genome.hidden.len(), 1);
+    }
+
+    #[test]
+    fn hash_structure_and_parameters() {
+        let genome = single_hidden_node_genome();
+
+        let mut reweighted = genome.clone();
+        reweighted.change_weights(&mut NeatRng::new(42, 1.0), 1.0);
+        assert_eq!(genome.structural_hash(), reweighted.structural_hash());
+        assert_ne!(genome.fingerprint(), reweighted.fingerprint());
+        assert_eq!(genome.fingerprint(), genome.clone().fingerprint());
+
+        let mut extended = genome.clone();
+        extended
+            .recurrent
+            .insert(Recurrent(Connection(Id(2), Weight(1.0), Id(2))));
+        assert_ne!(genome.structural_hash(), extended.structural_hash());
+    }
+
+    #[test]
+    fn evolve_shared_weights() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.weight_perturbation_std_dev = 1.0;
+        parameters.mutation.new_node_chance = 1.0;
+        parameters.mutation.max_structural_mutations_per_offspring = Some(1);
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+        id_gen.skip_past(Id(3));
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .input(Id(1))
+            .output(Node::new(Id(2), Activation::Tanh))
+            .output(Node::new(Id(3), Activation::Tanh))
+            .connection(Id(0), 1.0, Id(2))
+            .connection(Id(1), 2.0, Id(3))
+            .share_weight(&[(Id(0), Id(2)), (Id(1), Id(3))], 0.5)
+            .build()
+            .unwrap();
+
+        let weights = |genome: &Genome| -> Vec<f64> {
+            genome.weight_groups[0]
+                .connections
+                .iter()
+                .filter_map(|&(input, output)| {
+                    genome
+                        .feed_forward
+                        .get(&FeedForward(Connection(input, Weight(0.0), output)))
+                        .map(|connection| connection.1.value())
+                })
+                .collect()
+        };
+        assert_eq!(weights(&genome), vec![0.5, 0.5]);
+
+        genome.mutate(&mut rng, &mut id_gen, &parameters);
+
+        // one member was split and handed the group on to the connection from the new node
+        let group_weight = genome.weight_groups[0].weight.value();
+        assert_ne!(group_weight, 0.5);
+        assert_eq!(weights(&genome), vec![group_weight, group_weight]);
+        assert_eq!(genome.weight_groups[0].connections.len(), 2);
+        assert!(
+            genome.weight_group((Id(0), Id(2))).is_none()
+                || genome.weight_group((Id(1), Id(3))).is_none()
+        );
+    }
+
+    #[test]
+    fn rename_hidden_ids() {
+        let mut genome = single_hidden_node_genome();
+        genome.freeze(AnnotatedGene::Connection(Id(0), Id(2)));
+
+        genome.rename_ids(&vec![(Id(2), Id(7))].into_iter().collect::<HashMap<_, _>>());
+
+        assert!(genome.validate().is_empty());
+        assert!(genome.hidden.iter().all(|node| node.id() == Id(7)));
+        assert!(genome.is_frozen(AnnotatedGene::Connection(Id(0), Id(7))));
+        assert_eq!(
+            genome.structural_hash(),
+            Genome::builder()
+                .input(Id(0))
+                .output(Node::new(Id(1), Activation::Linear))
+                .hidden(Node::new(Id(7), Activation::Tanh))
+                .connection(Id(0), 1.0, Id(7))
+                .connection(Id(7), 1.0, Id(1))
+                .build()
+                .unwrap()
+                .structural_hash()
+        );
+    }
+
+    #[test]
+    fn split_parallel_connection() {
+        use crate::{genes::connections::Parallel, inference::Controller, Individual};
+
+        let mut parameters: Parameters = Default::default();
+        parameters.activations.hidden_nodes = vec![Activation::Linear];
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .output(Node::new(Id(1), Activation::Linear))
+            .connection(Id(0), 1.0, Id(1))
+            .build()
+            .unwrap();
+        genome
+            .parallel
+            .insert(Parallel(Id(2), Connection(Id(0), Weight::new(0.5), Id(1))));
+
+        let mut id_gen = IdGenerator::default();
+        id_gen.skip_past(Id(2));
+        genome
+            .add_node(&mut NeatRng::new(42, 1.0), &mut id_gen, &parameters)
+            .unwrap();
+
+        // the parallel weight moves onto the new pathway instead of keeping the split one alive
+        assert!(genome.parallel.is_empty());
+        let individual = Individual {
+            genome,
+            ..Default::default()
+        };
+        assert_eq!(Controller::from(&individual).activate(&[2.0]), vec![3.0]);
+    }
+
+    #[test]
+    fn split_keeps_product_aggregation() {
+        use crate::{inference::Controller, Individual};
+
+        let mut parameters: Parameters = Default::default();
+        parameters.activations.hidden_nodes = vec![Activation::Linear];
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .output(Node::new(Id(1), Activation::Linear).with_aggregation(Aggregation::Product))
+            .connection(Id(0), 2.0, Id(1))
+            .build()
+            .unwrap();
+
+        let mut id_gen = IdGenerator::default();
+        id_gen.skip_past(Id(1));
+        genome
+            .add_node(&mut NeatRng::new(42, 1.0), &mut id_gen, &parameters)
+            .unwrap();
+
+        // the split connection no longer feeds a zero into the product
+        let individual = Individual {
+            genome,
+            ..Default::default()
+        };
+        assert_eq!(Controller::from(&individual).activate(&[1.5]), vec![3.0]);
+    }
+
+    #[test]
+    fn fresh_genome_shares_io() {
+        let parameters: Parameters = Default::default();
+        let mut id_gen = IdGenerator::default();
+        id_gen.skip_past(Id(2));
+
+        let genome = single_hidden_node_genome();
+        let fresh = genome.fresh(&mut id_gen, &parameters);
+
+        assert_eq!(fresh.inputs.as_sorted_vec(), genome.inputs.as_sorted_vec());
+        assert_eq!(
+            fresh.outputs.as_sorted_vec(),
+            genome.outputs.as_sorted_vec()
+        );
+        assert!(fresh.hidden.is_empty());
+        assert!(fresh.feed_forward.is_empty());
+    }
+
+    #[test]
+    fn activation_per_output() {
+        let mut parameters: Parameters = Default::default();
+        parameters.setup.output_dimension = 3;
+        parameters.activations.output_nodes = Activation::Tanh;
+        parameters.activations.per_output = vec![Activation::Sigmoid, Activation::Linear];
+
+        let genome = Genome::new(&mut IdGenerator::default(), &parameters);
+
+        let activations: Vec<Activation> = genome
+            .outputs
+            .as_sorted_vec()
+            .iter()
+            .map(|node| node.1)
+            .collect();
+        assert_eq!(
+            activations,
+            vec![Activation::Sigmoid, Activation::Linear, Activation::Tanh]
+        );
+    }
+
     /* use super::Genome;
     use crate::{
         context::{rng::NeatRng, Context},
@@ -572,21 +2097,21 @@ mod tests {
 
         let mut genome_0 = Genome {
             inputs: Genes(
-                vec![Input(Node(Id(0), Activation::Linear))]
+                vec![Input(Node::new(Id(0), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
             ),
             outputs: Genes(
-                vec![Output(Node(Id(1), Activation::Linear))]
+                vec![Output(Node::new(Id(1), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
             ),
             hidden: Genes(
                 vec![
-                    Hidden(Node(Id(2), Activation::Tanh)),
-                    Hidden(Node(Id(3), Activation::Tanh)),
+                    Hidden(Node::new(Id(2), Activation::Tanh)),
+                    Hidden(Node::new(Id(3), Activation::Tanh)),
                 ]
                 .iter()
                 .cloned()
@@ -643,13 +2168,13 @@ mod tests {
     fn compatability_distance_same_genome() {
         let genome_0 = Genome {
             inputs: Genes(
-                vec![Input(Node(Id(0), Activation::Linear))]
+                vec![Input(Node::new(Id(0), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
             ),
             outputs: Genes(
-                vec![Output(Node(Id(1), Activation::Linear))]
+                vec![Output(Node::new(Id(1), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
@@ -675,13 +2200,13 @@ mod tests {
     fn compatability_distance_different_weight_genome() {
         let genome_0 = Genome {
             inputs: Genes(
-                vec![Input(Node(Id(0), Activation::Linear))]
+                vec![Input(Node::new(Id(0), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
             ),
             outputs: Genes(
-                vec![Output(Node(Id(1), Activation::Linear))]
+                vec![Output(Node::new(Id(1), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
@@ -714,13 +2239,13 @@ mod tests {
     fn compatability_distance_different_connection_genome() {
         let genome_0 = Genome {
             inputs: Genes(
-                vec![Input(Node(Id(0), Activation::Linear))]
+                vec![Input(Node::new(Id(0), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
             ),
             outputs: Genes(
-                vec![Output(Node(Id(1), Activation::Linear))]
+                vec![Output(Node::new(Id(1), Activation::Linear))]
                     .iter()
                     .cloned()
                     .collect(),
@@ -0,0 +1,217 @@
+// grows genomes to increased dimensions of parameters.setup, i.e. to carry champions over to a robot with another sensor,
+// added inputs and outputs get higher ids than any existing gene, so existing ones keep their position in
+// the input and output vectors
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::NeatError,
+    genes::{
+        connections::{Connection, FeedForward},
+        nodes::{Input, Node, Output},
+        Activation, AnnotatedGene, Id, IdGenerator, Weight,
+    },
+    parameters::Parameters,
+    utility::rng::NeatRng,
+};
+
+use super::genome::Genome;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IoWiring {
+    // added nodes stay unconnected until mutation connects them
+    Unconnected,
+    // added inputs connect to every output as disabled connections, so the network behaves as before
+    // until mutation enables them, added outputs connect from every previous input,
+    // weights are drawn as in parameters.mutation.weight_initialization
+    Minimal,
+}
+
+impl Default for IoWiring {
+    fn default() -> Self {
+        IoWiring::Unconnected
+    }
+}
+
+// ids of added inputs and outputs, shared by every grown genome to keep them aligned for crossover
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IoGrowth {
+    pub inputs: Vec<Id>,
+    pub outputs: Vec<Id>,
+    pub wiring: IoWiring,
+}
+
+impl IoGrowth {
+    // takes fresh ids for every input and output the genome lacks, id_gen must be past every id in use,
+    // i.e. the one of a checkpoint or one skipped past Genome::max_id of a seed
+    pub fn new(
+        genome: &Genome,
+        id_gen: &mut IdGenerator,
+        parameters: &Parameters,
+        wiring: IoWiring,
+    ) -> Result<Self, NeatError> {
+        let (inputs, outputs) = (genome.inputs.len(), genome.outputs.len());
+        if parameters.setup.input_dimension < inputs || parameters.setup.output_dimension < outputs
+        {
+            return Err(NeatError::Dimension(format!(
+                "genome with {} inputs and {} outputs can not shrink to {} inputs and {} outputs",
+                inputs,
+                outputs,
+                parameters.setup.input_dimension,
+                parameters.setup.output_dimension
+            )));
+        }
+
+        Ok(IoGrowth {
+            inputs: (inputs..parameters.setup.input_dimension)
+                .map(|_| id_gen.next_id())
+                .collect(),
+            outputs: (outputs..parameters.setup.output_dimension)
+                .map(|_| id_gen.next_id())
+                .collect(),
+            wiring,
+        })
+    }
+}
+
+impl Genome {
+    // appends the inputs and outputs of growth, annotations and activations follow their index as in Genome::new
+    pub fn grow_io(
+        &mut self,
+        growth: &IoGrowth,
+        rng: &mut NeatRng,
+        parameters: &Parameters,
+    ) -> Result<(), NeatError> {
+        let (inputs, outputs) = (self.inputs.len(), self.outputs.len());
+        if inputs + growth.inputs.len() != parameters.setup.input_dimension
+            || outputs + growth.outputs.len() != parameters.setup.output_dimension
+        {
+            return Err(NeatError::Dimension(format!(
+                "growth by {} inputs and {} outputs does not lead from {} inputs and {} outputs to {} and {}",
+                growth.inputs.len(),
+                growth.outputs.len(),
+                inputs,
+                outputs,
+                parameters.setup.input_dimension,
+                parameters.setup.output_dimension
+            )));
+        }
+        if growth
+            .inputs
+            .iter()
+            .chain(&growth.outputs)
+            .any(|&id| self.max_id().map_or(false, |max_id| id <= max_id))
+        {
+            return Err(NeatError::Dimension(
+                "ids of growth are not past every id of the genome".to_owned(),
+            ));
+        }
+
+        let previous_inputs: Vec<Id> = self.inputs.iter().map(|node| node.id()).collect();
+
+        for (index, &id) in (inputs..).zip(&growth.inputs) {
+            self.inputs.insert(Input(Node::new(id, Activation::Linear)));
+            if let Some(annotation) = parameters.setup.input_annotations.get(index) {
+                self.annotate(AnnotatedGene::Node(id), annotation.clone());
+            }
+        }
+        for (index, &id) in (outputs..).zip(&growth.outputs) {
+            self.outputs
+                .insert(Output(Node::new(id, parameters.activations.output(index))));
+            if let Some(annotation) = parameters.setup.output_annotations.get(index) {
+                self.annotate(AnnotatedGene::Node(id), annotation.clone());
+            }
+        }
+        if !growth.outputs.is_empty() {
+            self.output_heads = parameters.setup.output_heads.clone();
+        }
+
+        if growth.wiring == IoWiring::Minimal {
+            let all_outputs: Vec<Id> = self.outputs.iter().map(|node| node.id()).collect();
+            let input_count = self.inputs.len();
+            for &input in &growth.inputs {
+                for &output in &all_outputs {
+                    let weight =
+                        rng.initial_weight(parameters.mutation.weight_initialization, input_count);
+                    self.feed_forward.insert(FeedForward(Connection(
+                        input,
+                        Weight::new(weight),
+                        output,
+                    )));
+                    self.disabled.insert((input, output));
+                }
+            }
+            for &output in &growth.outputs {
+                for &input in &previous_inputs {
+                    let weight = rng.initial_weight(
+                        parameters.mutation.weight_initialization,
+                        previous_inputs.len(),
+                    );
+                    self.feed_forward.insert(FeedForward(Connection(
+                        input,
+                        Weight::new(weight),
+                        output,
+                    )));
+                }
+            }
+        }
+
+        self.debug_validate();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IoGrowth, IoWiring};
+    use crate::{
+        genes::{nodes::Node, Activation, Id, IdGenerator},
+        individual::{genome::Genome, Individual},
+        inference::Controller,
+        parameters::Parameters,
+        utility::rng::NeatRng,
+    };
+
+    #[test]
+    fn grow_champion_by_a_sensor() {
+        let mut champion = Genome::builder()
+            .input(Id(0))
+            .output(Node::new(Id(1), Activation::Linear))
+            .connection(Id(0), 0.5, Id(1))
+            .build()
+            .unwrap();
+
+        let mut parameters: Parameters = Default::default();
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 2;
+        parameters.activations.output_nodes = Activation::Linear;
+
+        let mut id_gen = IdGenerator::default();
+        id_gen.skip_past(champion.max_id().unwrap());
+        let growth = IoGrowth::new(&champion, &mut id_gen, &parameters, IoWiring::Minimal).unwrap();
+        assert_eq!(
+            (growth.inputs.clone(), growth.outputs.clone()),
+            (vec![Id(2)], vec![Id(3)])
+        );
+
+        champion
+            .grow_io(&growth, &mut NeatRng::new(42, 1.0), &parameters)
+            .unwrap();
+        assert!(champion.validate().is_empty());
+
+        // the previous output ignores the new sensor until its connections are enabled
+        let individual = Individual {
+            genome: champion.clone(),
+            ..Default::default()
+        };
+        let outputs = Controller::from(&individual).activate(&[1.0, 3.0]);
+        assert_eq!(outputs.len(), 2);
+        assert!((outputs[0] - 0.5).abs() < 1e-6);
+
+        // the growth fits once only
+        assert!(champion
+            .grow_io(&growth, &mut NeatRng::new(42, 1.0), &parameters)
+            .is_err());
+        parameters.setup.input_dimension = 1;
+        assert!(IoGrowth::new(&champion, &mut id_gen, &parameters, IoWiring::Unconnected).is_err());
+    }
+}
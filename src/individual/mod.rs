@@ -1,24 +1,49 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    cmp::Ordering,
+    ops::{Deref, DerefMut},
+};
 
 use rand::prelude::SmallRng;
 use serde::{Deserialize, Serialize};
 
-use crate::{genes::IdGenerator, parameters::Parameters};
+use crate::{
+    genes::IdGenerator,
+    parameters::{Parameters, TieBreaker, TieBreaking},
+    runtime::progress::{Objective, Progress},
+};
 
 use self::scores::{FitnessScore, NoveltyScore, ScoreValue};
 use self::{behavior::Behavior, genome::Genome};
 
 pub mod behavior;
+pub mod bundle;
+pub mod diff;
+pub mod genealogy;
 pub mod genome;
+pub mod io_growth;
+pub mod mutation;
+pub mod neat_python;
 pub mod scores;
+pub mod validation;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Individual {
+    // unique within a run, see Genealogy
+    #[serde(default)]
+    pub id: usize,
     pub genome: Genome,
     pub age: usize,
     pub behavior: Option<Behavior>,
     pub fitness: Option<FitnessScore>,
     pub novelty: Option<NoveltyScore>,
+    // None if no minimal criterion was evaluated
+    #[serde(default)]
+    pub meets_minimal_criterion: Option<bool>,
+    // share of nearest behavioral neighbors outperformed in fitness
+    #[serde(default)]
+    pub local_competition: Option<f64>,
+    #[serde(default)]
+    pub objectives: Option<Vec<Objective>>,
 }
 
 impl Deref for Individual {
@@ -38,14 +63,39 @@ impl DerefMut for Individual {
 impl Individual {
     pub fn initial(id_gen: &mut IdGenerator, parameters: &Parameters) -> Self {
         Self {
+            id: 0,
             genome: Genome::new(id_gen, parameters),
             age: 0,
             behavior: None,
             fitness: None,
             novelty: None,
+            meets_minimal_criterion: None,
+            local_competition: None,
+            objectives: None,
         }
     }
 
+    pub fn is_viable(&self) -> bool {
+        self.meets_minimal_criterion.unwrap_or(true)
+    }
+
+    // reconstructs the results of the last evaluation from assigned values
+    pub fn recorded_progress(&self) -> Progress {
+        if let Some(objectives) = &self.objectives {
+            return Progress::MultiObjective(objectives.clone(), self.behavior.clone())
+                .viable(self.is_viable());
+        }
+
+        let fitness = self.fitness.as_ref().map(|fitness| fitness.raw);
+        let progress = match (fitness, self.behavior.clone()) {
+            (Some(fitness), Some(behavior)) => Progress::Status(fitness, behavior),
+            (Some(fitness), None) => Progress::Fitness(fitness),
+            (None, Some(behavior)) => Progress::Novelty(behavior),
+            (None, None) => Progress::Empty,
+        };
+        progress.viable(self.is_viable())
+    }
+
     // score is combination of fitness & novelty
     pub fn score(&self) -> f64 {
         let novelty = self
@@ -59,7 +109,8 @@ impl Individual {
             .map(|n| n.normalized.value())
             .unwrap_or(0.0);
 
-        novelty.max(fitness)
+        // local competition replaces global fitness as secondary objective
+        novelty.max(self.local_competition.unwrap_or(fitness))
 
         // (novelty + fitness) / 2.0
 
@@ -82,29 +133,118 @@ impl Individual {
         // min * ratio + max * (1.0 - ratio)
     }
 
-    // self is fitter if it has higher score or in case of equal score has fewer genes, i.e. less complexity
-    pub fn is_fitter_than(&self, other: &Self) -> bool {
+    // score used for selection, adjusted by age
+    pub fn selection_score(&self, parameters: &Parameters) -> f64 {
+        self.score() * parameters.setup.age_adjustment.factor(self.age)
+    }
+
+    // Greater if self is preferred by the first deciding criterion, regardless of score
+    pub fn break_tie(&self, other: &Self, tie_breaking: &TieBreaking) -> Ordering {
+        tie_breaking
+            .0
+            .iter()
+            .map(|tie_breaker| match tie_breaker {
+                TieBreaker::FewerGenes => other.genome.len().cmp(&self.genome.len()),
+                TieBreaker::Younger => other.age.cmp(&self.age),
+                TieBreaker::Older => self.age.cmp(&other.age),
+                TieBreaker::LowerId => other.id.cmp(&self.id),
+            })
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+
+    // Greater if self ranks before other, NaN scores rank last
+    pub fn compare_by(
+        &self,
+        other: &Self,
+        score: impl Fn(&Self) -> f64,
+        tie_breaking: &TieBreaking,
+    ) -> Ordering {
+        let key = |individual: &Self| {
+            let score = score(individual);
+            if score.is_nan() {
+                f64::NEG_INFINITY
+            } else {
+                score
+            }
+        };
+
+        key(self)
+            .partial_cmp(&key(other))
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.break_tie(other, tie_breaking))
+    }
+
+    // self is fitter if it has higher score or in case of equal score wins the tie breaking
+    pub fn is_fitter_than(&self, other: &Self, tie_breaking: &TieBreaking) -> bool {
         let score_self = self.score();
         let score_other = other.score();
 
         score_self > score_other
             || ((score_self - score_other).abs() < f64::EPSILON
-                && self.genome.len() < other.genome.len())
+                && self.break_tie(other, tie_breaking) == Ordering::Greater)
     }
 
-    pub fn crossover(&self, other: &Self, rng: &mut SmallRng) -> Self {
-        let (fitter, weaker) = if self.is_fitter_than(other) {
+    pub fn crossover(&self, other: &Self, rng: &mut SmallRng, parameters: &Parameters) -> Self {
+        let (fitter, weaker) = if self.is_fitter_than(other, &parameters.setup.tie_breaking) {
             (&self.genome, &other.genome)
         } else {
             (&other.genome, &self.genome)
         };
 
+        let mut genome = fitter.cross_in(weaker, rng);
+        genome.repair_cycles(parameters.mutation.cycle_repair);
+        genome.debug_validate();
+
         Individual {
-            genome: fitter.cross_in(weaker, rng),
+            id: 0,
+            genome,
             age: 0,
             behavior: None,
             fitness: None,
             novelty: None,
+            meets_minimal_criterion: None,
+            local_competition: None,
+            objectives: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::Individual;
+    use crate::parameters::{TieBreaker, TieBreaking};
+
+    #[test]
+    fn break_ties_in_order() {
+        let mut individual_0 = Individual {
+            id: 1,
+            age: 2,
+            ..Default::default()
+        };
+        let individual_1 = Individual {
+            id: 2,
+            age: 1,
+            ..Default::default()
+        };
+
+        let default = TieBreaking::default();
+        assert!(!individual_0.is_fitter_than(&individual_1, &default));
+        assert_eq!(
+            individual_0.compare_by(&individual_1, |_| f64::NAN, &default),
+            Ordering::Less
+        );
+
+        let by_id = TieBreaking(vec![TieBreaker::LowerId, TieBreaker::Younger]);
+        assert!(individual_0.is_fitter_than(&individual_1, &by_id));
+
+        individual_0.id = 2;
+        individual_0.age = 1;
+        assert_eq!(
+            individual_0.break_tie(&individual_1, &default),
+            Ordering::Equal
+        );
+    }
+}
@@ -0,0 +1,448 @@
+use std::{fmt, ops::Deref, sync::Arc};
+
+use crate::{
+    genes::IdGenerator,
+    individual::genome::{Genome, MutationRates},
+    parameters::Parameters,
+    utility::rng::NeatRng,
+};
+
+// structural mutation, gambled with its probability in every pass over the pipeline until
+// parameters.mutation.max_structural_mutations_per_offspring were applied, or once without a budget,
+// register custom ones via Neat::with_mutation_operator
+pub trait MutationOperator: Send + Sync {
+    fn name(&self) -> &str;
+
+    // rates are the genome carried ones if parameters.mutation.self_adaptive is set
+    fn probability(&self, rates: &MutationRates, parameters: &Parameters) -> f64;
+
+    // errors only mean the operator was not applicable, i.e. nothing left to split
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        id_gen: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str>;
+
+    // upper bound of hidden nodes and connections added by one application, checked against
+    // parameters.mutation.max_hidden_nodes and max_connections, operators adding genes should report them
+    fn added_genes(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+// operator would grow the genome past a cap of parameters.mutation
+pub fn exceeds_caps(
+    operator: &dyn MutationOperator,
+    genome: &Genome,
+    parameters: &Parameters,
+) -> bool {
+    let (hidden_nodes, connections) = operator.added_genes();
+    let exceeds = |current: usize, added: usize, cap: Option<usize>| {
+        added > 0 && cap.map_or(false, |cap| current + added > cap)
+    };
+
+    exceeds(
+        genome.hidden.len(),
+        hidden_nodes,
+        parameters.mutation.max_hidden_nodes,
+    ) || exceeds(
+        genome.len(),
+        connections,
+        parameters.mutation.max_connections,
+    )
+}
+
+// applied in this order, before any custom operator
+pub static BUILT_IN_OPERATORS: [&(dyn MutationOperator + 'static); 7] = [
+    &AddConnection,
+    &AddNode,
+    &AlterActivation,
+    &AlterAggregation,
+    &AddSelfConnection,
+    &AddParallelConnection,
+    &ToggleConnection,
+];
+
+// operators registered in addition to the built-in ones, not part of serialized parameters
+#[derive(Clone, Default)]
+pub struct CustomOperators(Vec<Arc<dyn MutationOperator>>);
+
+impl CustomOperators {
+    pub fn push(&mut self, operator: Arc<dyn MutationOperator>) {
+        self.0.push(operator);
+    }
+}
+
+impl Deref for CustomOperators {
+    type Target = Vec<Arc<dyn MutationOperator>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Debug for CustomOperators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|operator| operator.name()))
+            .finish()
+    }
+}
+
+// built-in and custom operators in order of application
+pub fn pipeline(
+    parameters: &Parameters,
+) -> impl Iterator<Item = &(dyn MutationOperator + 'static)> {
+    BUILT_IN_OPERATORS.iter().copied().chain(
+        parameters
+            .mutation
+            .custom_operators
+            .iter()
+            .map(|operator| operator.as_ref()),
+    )
+}
+
+pub struct AddConnection;
+
+impl MutationOperator for AddConnection {
+    fn name(&self) -> &str {
+        "add_connection"
+    }
+    fn probability(&self, rates: &MutationRates, _: &Parameters) -> f64 {
+        rates.new_connection_chance
+    }
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        _: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        genome.add_connection(rng, parameters)
+    }
+    fn added_genes(&self) -> (usize, usize) {
+        (0, 1)
+    }
+}
+
+pub struct AddNode;
+
+impl MutationOperator for AddNode {
+    fn name(&self) -> &str {
+        "add_node"
+    }
+    fn probability(&self, rates: &MutationRates, _: &Parameters) -> f64 {
+        rates.new_node_chance
+    }
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        id_gen: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        genome.add_node(rng, id_gen, parameters)
+    }
+    fn added_genes(&self) -> (usize, usize) {
+        (1, 2)
+    }
+}
+
+pub struct AlterActivation;
+
+impl MutationOperator for AlterActivation {
+    fn name(&self) -> &str {
+        "alter_activation"
+    }
+    fn probability(&self, rates: &MutationRates, _: &Parameters) -> f64 {
+        rates.change_activation_function_chance
+    }
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        _: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        genome.alter_activation(rng, parameters);
+        Ok(())
+    }
+}
+
+pub struct AlterAggregation;
+
+impl MutationOperator for AlterAggregation {
+    fn name(&self) -> &str {
+        "alter_aggregation"
+    }
+    fn probability(&self, _: &MutationRates, parameters: &Parameters) -> f64 {
+        parameters.mutation.change_aggregation_chance
+    }
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        _: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        genome.alter_aggregation(rng, parameters);
+        Ok(())
+    }
+}
+
+// recurrent loop of a node onto itself
+pub struct AddSelfConnection;
+
+impl MutationOperator for AddSelfConnection {
+    fn name(&self) -> &str {
+        "add_self_connection"
+    }
+    fn probability(&self, _: &MutationRates, parameters: &Parameters) -> f64 {
+        parameters.mutation.self_connection_chance
+    }
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        _: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        genome.add_self_connection(rng, parameters)
+    }
+    fn added_genes(&self) -> (usize, usize) {
+        (0, 1)
+    }
+}
+
+pub struct AddParallelConnection;
+
+impl MutationOperator for AddParallelConnection {
+    fn name(&self) -> &str {
+        "add_parallel_connection"
+    }
+    fn probability(&self, _: &MutationRates, parameters: &Parameters) -> f64 {
+        parameters.mutation.parallel_connection_chance
+    }
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        id_gen: &mut IdGenerator,
+        parameters: &Parameters,
+    ) -> Result<(), &'static str> {
+        genome.add_parallel_connection(rng, id_gen, parameters)
+    }
+    fn added_genes(&self) -> (usize, usize) {
+        (0, 1)
+    }
+}
+
+pub struct ToggleConnection;
+
+impl MutationOperator for ToggleConnection {
+    fn name(&self) -> &str {
+        "toggle_connection"
+    }
+    fn probability(&self, _: &MutationRates, parameters: &Parameters) -> f64 {
+        parameters.mutation.toggle_connection_chance
+    }
+    fn mutate(
+        &self,
+        genome: &mut Genome,
+        rng: &mut NeatRng,
+        _: &mut IdGenerator,
+        _: &Parameters,
+    ) -> Result<(), &'static str> {
+        genome.toggle_connection(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{exceeds_caps, AlterActivation, MutationOperator, BUILT_IN_OPERATORS};
+    use crate::{
+        genes::{nodes::Node, Activation, Id, IdGenerator},
+        individual::genome::{Genome, MutationRates},
+        parameters::Parameters,
+        utility::rng::NeatRng,
+    };
+
+    // sets every node to relu
+    struct Rectify;
+
+    impl MutationOperator for Rectify {
+        fn name(&self) -> &str {
+            "rectify"
+        }
+        fn probability(&self, _: &MutationRates, _: &Parameters) -> f64 {
+            1.0
+        }
+        fn mutate(
+            &self,
+            genome: &mut Genome,
+            _: &mut NeatRng,
+            _: &mut IdGenerator,
+            _: &Parameters,
+        ) -> Result<(), &'static str> {
+            genome.hidden = genome
+                .hidden
+                .drain()
+                .map(|mut node| {
+                    node.1 = Activation::Relu;
+                    node
+                })
+                .collect();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn apply_custom_operator() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.new_node_chance = 1.0;
+        parameters.mutation.custom_operators.push(Arc::new(Rectify));
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .output(Node::new(Id(1), Activation::Tanh))
+            .connection(Id(0), 1.0, Id(1))
+            .build()
+            .unwrap();
+        id_gen.skip_past(Id(1));
+
+        genome.mutate(&mut rng, &mut id_gen, &parameters);
+
+        assert!(!genome.hidden.is_empty());
+        assert!(genome.hidden.iter().all(|node| node.1 == Activation::Relu));
+        assert_eq!(
+            format!("{:?}", parameters.mutation.custom_operators),
+            "[\"rectify\"]"
+        );
+        assert_eq!(BUILT_IN_OPERATORS[1].name(), "add_node");
+    }
+
+    #[test]
+    fn toggle_connection() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.new_node_chance = 0.0;
+        parameters.mutation.new_connection_chance = 0.0;
+        parameters.mutation.change_activation_function_chance = 0.0;
+        parameters.mutation.toggle_connection_chance = 1.0;
+        parameters.mutation.max_structural_mutations_per_offspring = Some(1);
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .output(Node::new(Id(1), Activation::Tanh))
+            .connection(Id(0), 1.0, Id(1))
+            .build()
+            .unwrap();
+
+        genome.mutate(&mut rng, &mut id_gen, &parameters);
+        assert!(genome.disabled.contains(&(Id(0), Id(1))));
+        assert!(genome.expressed().feed_forward.is_empty());
+        assert_eq!(genome.feed_forward.len(), 1);
+
+        genome.mutate(&mut rng, &mut id_gen, &parameters);
+        assert!(genome.disabled.is_empty());
+    }
+
+    #[test]
+    fn skip_mutations_exceeding_caps() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.new_node_chance = 1.0;
+        parameters.mutation.new_connection_chance = 1.0;
+        parameters.mutation.max_structural_mutations_per_offspring = Some(5);
+        parameters.mutation.max_hidden_nodes = Some(1);
+        parameters.mutation.max_connections = Some(3);
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .input(Id(1))
+            .output(Node::new(Id(2), Activation::Tanh))
+            .connection(Id(0), 1.0, Id(2))
+            .build()
+            .unwrap();
+        id_gen.skip_past(Id(2));
+
+        let skipped = genome.mutate(&mut rng, &mut id_gen, &parameters);
+
+        assert!(skipped > 0);
+        assert!(genome.hidden.len() <= 1);
+        assert!(genome.len() <= 3);
+        assert!(!exceeds_caps(&AlterActivation, &genome, &parameters));
+    }
+
+    #[test]
+    fn count_applied_mutations() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.new_node_chance = 0.0;
+        parameters.mutation.new_connection_chance = 1.0;
+        parameters.mutation.connection_is_recurrent_chance = 0.0;
+        parameters.mutation.change_activation_function_chance = 0.0;
+        parameters.mutation.max_structural_mutations_per_offspring = Some(2);
+        let mut rng = NeatRng::new(42, 1.0);
+        let mut id_gen = IdGenerator::default();
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .input(Id(1))
+            .input(Id(2))
+            .output(Node::new(Id(3), Activation::Tanh))
+            .output(Node::new(Id(4), Activation::Tanh))
+            .connection(Id(0), 1.0, Id(3))
+            .build()
+            .unwrap();
+
+        // one connection per pass, so the budget takes two passes
+        genome.mutate(&mut rng, &mut id_gen, &parameters);
+        assert_eq!(genome.feed_forward.len(), 3);
+    }
+
+    #[test]
+    fn gamble_every_operator_once_without_budget() {
+        let mut parameters: Parameters = Default::default();
+        parameters.mutation.new_node_chance = 1.0;
+        parameters.mutation.new_connection_chance = 1.0;
+        parameters.mutation.connection_is_recurrent_chance = 0.0;
+        parameters.mutation.change_activation_function_chance = 1.0;
+        parameters.activations.hidden_nodes = vec![Activation::Tanh, Activation::Relu];
+
+        let mutated = |parameters: &Parameters| {
+            let mut rng = NeatRng::new(42, 1.0);
+            let mut id_gen = IdGenerator::default();
+            id_gen.skip_past(Id(2));
+            let mut genome = Genome::builder()
+                .input(Id(0))
+                .input(Id(1))
+                .output(Node::new(Id(2), Activation::Tanh))
+                .connection(Id(0), 1.0, Id(2))
+                .build()
+                .unwrap();
+            genome.mutate(&mut rng, &mut id_gen, parameters);
+            genome
+        };
+
+        let genome = mutated(&parameters);
+        assert_eq!(genome.hidden.len(), 1);
+        // one added connection, the split one stays disabled next to the two of the new node
+        assert_eq!(genome.feed_forward.len(), 4);
+
+        // same random choices up to the activation change
+        parameters.mutation.change_activation_function_chance = 0.0;
+        let unaltered = mutated(&parameters);
+        assert_ne!(
+            genome.hidden.iter().next().unwrap().1,
+            unaltered.hidden.iter().next().unwrap().1
+        );
+    }
+}
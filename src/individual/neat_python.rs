@@ -0,0 +1,428 @@
+// conversion from and to genomes of neat-python, the dimensions are not part of its genomes and come
+// from parameters.setup on import, conversion rules are
+// - input keys -1, -2, .. become ids 0, 1, .., output and hidden keys follow shifted by the input dimension,
+//   on export inputs, outputs and hidden nodes are keyed in order of their ids
+// - neat-python scales sigmoid, tanh, sin and gauss differently, the node coefficient absorbs the difference,
+//   so they convert exactly as do identity, relu, abs and square
+// - other activations become custom activations of the same name, register them via Neat::with_activation
+// - maxabs and median aggregations have no counterpart and fail the import
+// - disabled connections are kept with weight zero like split connections here, which is exact for sum aggregation only,
+//   exported connections are enabled unless disabled by Genome::toggle_connection
+// - connections closing a cycle become recurrent, note that neat-python's RecurrentNetwork delays every
+//   connection by a step while only recurrent connections are delayed here
+// - on export cosine and inverse are expressed by sin and identity, step has no counterpart and fails,
+//   parallel connections are summed into their connection, time constants, annotations and frozen genes are dropped
+use std::{collections::BTreeMap, f64::consts::PI};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::NeatError,
+    genes::{
+        connections::{Connection, FeedForward, Recurrent},
+        custom_activations::ActivationRegistry,
+        nodes::{Hidden, Input, Node, Output},
+        Activation, Aggregation, Id, Weight,
+    },
+    parameters::Parameters,
+};
+
+use super::genome::{Genome, GenomeBuilder};
+
+// DefaultGenome of neat-python with its nodes and connections as lists
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NeatPythonGenome {
+    #[serde(default)]
+    pub key: i64,
+    #[serde(default)]
+    pub fitness: Option<f64>,
+    pub nodes: Vec<NeatPythonNode>,
+    pub connections: Vec<NeatPythonConnection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NeatPythonNode {
+    pub key: i64,
+    pub bias: f64,
+    pub response: f64,
+    pub activation: String,
+    pub aggregation: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NeatPythonConnection {
+    // input and output node
+    pub key: (i64, i64),
+    pub weight: f64,
+    #[serde(default = "NeatPythonConnection::enabled")]
+    pub enabled: bool,
+}
+
+impl NeatPythonConnection {
+    fn enabled() -> bool {
+        true
+    }
+}
+
+impl NeatPythonGenome {
+    pub fn from_json(json: &str) -> Result<Self, NeatError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, NeatError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+// activations neat-python knows under another name or scale
+const SCALED: [Activation; 8] = [
+    Activation::Linear,
+    Activation::Sigmoid,
+    Activation::Tanh,
+    Activation::Gaussian,
+    Activation::Sine,
+    Activation::Relu,
+    Activation::Absolute,
+    Activation::Squared,
+];
+
+// name in neat-python and factor turning the pre-activation here into the one of neat-python
+fn scale(activation: Activation) -> Option<(&'static str, f64)> {
+    match activation {
+        Activation::Linear => Some(("identity", 1.0)),
+        // 1 / (1 + exp(-5 z))
+        Activation::Sigmoid => Some(("sigmoid", 4.9 / 5.0)),
+        // tanh(2.5 z)
+        Activation::Tanh => Some(("tanh", 4.9 / 2.5)),
+        // exp(-5 z^2)
+        Activation::Gaussian => Some(("gauss", 1.0 / 10f64.sqrt())),
+        // sin(5 z)
+        Activation::Sine => Some(("sin", PI / 5.0)),
+        Activation::Relu => Some(("relu", 1.0)),
+        Activation::Absolute => Some(("abs", 1.0)),
+        Activation::Squared => Some(("square", 1.0)),
+        _ => None,
+    }
+}
+
+// activation and coefficient reproducing neat-python's activation of given name,
+// any other name has to be registered
+fn import_activation(
+    name: &str,
+    activations: &ActivationRegistry,
+) -> Result<(Activation, f64), NeatError> {
+    match SCALED.iter().find_map(|&activation| {
+        scale(activation)
+            .filter(|(scaled_name, _)| *scaled_name == name)
+            .map(|(_, factor)| (activation, 1.0 / factor))
+    }) {
+        Some(scaled) => Ok(scaled),
+        None => Ok((activations.get(name)?, 1.0)),
+    }
+}
+
+// name, factor and offset turning the pre-activation here into the one of neat-python
+fn export_activation(activation: Activation) -> Result<(String, f64, f64), NeatError> {
+    match activation {
+        // cos(x) = sin(x + PI / 2)
+        Activation::Cosine => Ok(("sin".to_owned(), PI / 5.0, PI / 10.0)),
+        Activation::Inverse => Ok(("identity".to_owned(), -1.0, 0.0)),
+        Activation::Step => Err(NeatError::Conversion(
+            "neat-python has no step activation".to_owned(),
+        )),
+        Activation::Custom(custom) => Ok((custom.name().to_owned(), 1.0, 0.0)),
+        scaled => {
+            let (name, factor) = scale(scaled).expect("every other activation is scaled");
+            Ok((name.to_owned(), factor, 0.0))
+        }
+    }
+}
+
+fn import_aggregation(name: &str) -> Result<Aggregation, NeatError> {
+    match name {
+        "sum" => Ok(Aggregation::Sum),
+        "product" => Ok(Aggregation::Product),
+        "max" => Ok(Aggregation::Max),
+        "min" => Ok(Aggregation::Min),
+        "mean" => Ok(Aggregation::Mean),
+        _ => Err(NeatError::Conversion(format!(
+            "aggregation {} has no counterpart",
+            name
+        ))),
+    }
+}
+
+fn export_aggregation(aggregation: Aggregation) -> &'static str {
+    match aggregation {
+        Aggregation::Sum => "sum",
+        Aggregation::Product => "product",
+        Aggregation::Max => "max",
+        Aggregation::Min => "min",
+        Aggregation::Mean => "mean",
+    }
+}
+
+impl Genome {
+    // activations other than neat-python's built-in ones resolve to those registered under their name
+    pub fn from_neat_python(
+        genome: &NeatPythonGenome,
+        parameters: &Parameters,
+        activations: &ActivationRegistry,
+    ) -> Result<Genome, NeatError> {
+        let input_dimension = parameters.setup.input_dimension as i64;
+        let output_dimension = parameters.setup.output_dimension as i64;
+
+        let id = |key: i64| {
+            if key < 0 && -key <= input_dimension {
+                Ok(Id((-key - 1) as usize))
+            } else if key >= 0 {
+                Ok(Id((input_dimension + key) as usize))
+            } else {
+                Err(NeatError::Conversion(format!(
+                    "key {} exceeds {} inputs",
+                    key, input_dimension
+                )))
+            }
+        };
+
+        let mut imported = Genome {
+            inputs: (0..input_dimension as usize)
+                .map(|index| Input(Node::new(Id(index), Activation::Linear)))
+                .collect(),
+            ..Default::default()
+        };
+
+        for node in &genome.nodes {
+            if node.key < 0 {
+                return Err(NeatError::Conversion(format!(
+                    "input {} listed as node",
+                    node.key
+                )));
+            }
+            let (activation, coefficient) = import_activation(&node.activation, activations)?;
+            let imported_node = Node::new(id(node.key)?, activation)
+                .with_bias(node.bias)
+                .with_response(node.response)
+                .with_coefficient(coefficient)
+                .with_aggregation(import_aggregation(&node.aggregation)?);
+
+            if node.key < output_dimension {
+                imported.outputs.insert(Output(imported_node));
+            } else {
+                imported.hidden.insert(Hidden(imported_node));
+            }
+        }
+
+        if imported.outputs.len() as i64 != output_dimension {
+            return Err(NeatError::Conversion(format!(
+                "expected {} outputs but found {}",
+                output_dimension,
+                imported.outputs.len()
+            )));
+        }
+
+        // sorted, so the same genome always splits into the same feed forward and recurrent connections
+        let mut connections: Vec<&NeatPythonConnection> = genome.connections.iter().collect();
+        connections.sort_by_key(|connection| connection.key);
+
+        for connection in connections {
+            let (input, output) = (id(connection.key.0)?, id(connection.key.1)?);
+            let weight = if connection.enabled {
+                connection.weight
+            } else {
+                0.0
+            };
+            let imported_connection = Connection(input, Weight::new(weight), output);
+
+            if input == output
+                || imported.would_form_cycle(
+                    &Node::new(input, Activation::Linear),
+                    &Node::new(output, Activation::Linear),
+                )
+            {
+                imported.recurrent.insert(Recurrent(imported_connection));
+            } else {
+                imported
+                    .feed_forward
+                    .insert(FeedForward(imported_connection));
+            }
+        }
+
+        GenomeBuilder::from_genome(imported)
+            .build()
+            .map_err(|violations| {
+                NeatError::Conversion(
+                    violations
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+            })
+    }
+
+    // key and fitness are left for the caller to fill in
+    pub fn to_neat_python(&self) -> Result<NeatPythonGenome, NeatError> {
+        // disabled connections are exported as such instead of being dropped
+        let expressed = Genome {
+            disabled: Default::default(),
+            ..self.clone()
+        }
+        .expressed();
+
+        let mut keys = BTreeMap::new();
+        for (index, input) in expressed.inputs.as_sorted_vec().into_iter().enumerate() {
+            keys.insert(input.id(), -(index as i64) - 1);
+        }
+        let nodes: Vec<&Node> = expressed
+            .outputs
+            .as_sorted_vec()
+            .into_iter()
+            .chain(expressed.hidden.as_sorted_vec())
+            .collect();
+        for (index, node) in nodes.iter().enumerate() {
+            keys.insert(node.id(), index as i64);
+        }
+
+        let nodes = nodes
+            .into_iter()
+            .map(|node| {
+                let (activation, factor, offset) = export_activation(node.1)?;
+                Ok(NeatPythonNode {
+                    key: keys[&node.id()],
+                    bias: factor * node.effective_bias() + offset,
+                    response: factor * node.effective_response(),
+                    activation,
+                    aggregation: export_aggregation(node.6).to_owned(),
+                })
+            })
+            .collect::<Result<Vec<_>, NeatError>>()?;
+
+        let mut connections: Vec<NeatPythonConnection> = expressed
+            .feed_forward
+            .iterate_unwrapped()
+            .chain(expressed.recurrent.iterate_unwrapped())
+            .map(|connection| NeatPythonConnection {
+                key: (keys[&connection.input()], keys[&connection.output()]),
+                weight: connection.1.value(),
+                enabled: !self.disabled.contains(&connection.id()),
+            })
+            .collect();
+        connections.sort_by_key(|connection| connection.key);
+
+        Ok(NeatPythonGenome {
+            key: 0,
+            fitness: None,
+            nodes,
+            connections,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NeatPythonGenome;
+    use crate::{
+        error::NeatError,
+        genes::ActivationRegistry,
+        individual::{genome::Genome, Individual},
+        parameters::Parameters,
+        Controller,
+    };
+
+    const GENOME: &str = r#"{
+        "key": 7,
+        "fitness": 3.9,
+        "nodes": [
+            {"key": 0, "bias": 0.5, "response": 1.0, "activation": "sigmoid", "aggregation": "sum"},
+            {"key": 4, "bias": -0.2, "response": 2.0, "activation": "tanh", "aggregation": "max"}
+        ],
+        "connections": [
+            {"key": [-1, 4], "weight": 1.5, "enabled": true},
+            {"key": [-2, 4], "weight": -0.7, "enabled": true},
+            {"key": [4, 0], "weight": 2.0, "enabled": true},
+            {"key": [-1, 0], "weight": 3.0, "enabled": false}
+        ]
+    }"#;
+
+    fn sigmoid(z: f64) -> f64 {
+        1.0 / (1.0 + (-5.0 * z).exp())
+    }
+
+    fn tanh(z: f64) -> f64 {
+        (2.5 * z).tanh()
+    }
+
+    fn activate(genome: Genome, inputs: &[f64]) -> f64 {
+        let individual = Individual {
+            genome,
+            ..Default::default()
+        };
+        Controller::from(&individual).activate(inputs)[0]
+    }
+
+    #[test]
+    fn activate_like_neat_python() {
+        let mut parameters: Parameters = Default::default();
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+
+        let genome = Genome::from_neat_python(
+            &NeatPythonGenome::from_json(GENOME).unwrap(),
+            &parameters,
+            &ActivationRegistry::default(),
+        )
+        .unwrap();
+        assert_eq!(genome.hidden.len(), 1);
+        assert_eq!(genome.feed_forward.len(), 4);
+
+        let (a, b) = (0.3, -0.8);
+        let hidden = tanh(-0.2 + 2.0 * (1.5 * a).max(-0.7 * b));
+        let expected = sigmoid(0.5 + 2.0 * hidden);
+
+        let output = activate(genome.clone(), &[a, b]);
+        assert!(
+            (output - expected).abs() < 1e-9,
+            "{} != {}",
+            output,
+            expected
+        );
+
+        // round trip keeps the behavior, the disabled connection comes back enabled with weight zero
+        let exported = genome.to_neat_python().unwrap();
+        assert_eq!(exported.connections.len(), 4);
+        assert!(exported
+            .connections
+            .iter()
+            .all(|connection| connection.enabled));
+        let reimported =
+            Genome::from_neat_python(&exported, &parameters, &ActivationRegistry::default())
+                .unwrap();
+        let output = activate(reimported, &[a, b]);
+        assert!(
+            (output - expected).abs() < 1e-9,
+            "{} != {}",
+            output,
+            expected
+        );
+    }
+
+    #[test]
+    fn import_registered_activation() {
+        let mut parameters: Parameters = Default::default();
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+        let genome =
+            NeatPythonGenome::from_json(&GENOME.replacen("sigmoid", "softsign", 1)).unwrap();
+
+        let mut activations = ActivationRegistry::default();
+        assert!(matches!(
+            Genome::from_neat_python(&genome, &parameters, &activations),
+            Err(NeatError::UnknownActivation(name)) if name == "softsign"
+        ));
+
+        let softsign = activations.register("softsign", |x| x / (1.0 + x.abs()), (-1.0, 1.0));
+        let imported = Genome::from_neat_python(&genome, &parameters, &activations).unwrap();
+        assert!(imported.outputs.iter().any(|output| output.1 == softsign));
+    }
+}
@@ -48,6 +48,12 @@ impl FitnessScore {
             normalized,
         }
     }
+
+    // i.e. rank based fitness, see FitnessShaping
+    pub fn with_normalized(mut self, normalized: f64) -> Self {
+        self.normalized = Normalized(Fitness(normalized));
+        self
+    }
 }
 
 #[derive(Debug, Default, Copy, Clone, Deserialize, Serialize, PartialEq)]
@@ -117,9 +123,23 @@ impl Raw<Fitness> {
     }
 }
 
+// scales shifted values into [0, 1], if all values are equal every value is treated as maximal
+fn normalize_value(value: f64, with: f64) -> f64 {
+    if is_degenerate(with) {
+        1.0
+    } else {
+        value / with
+    }
+}
+
+// shifted maximum of zero means there is no signal to distinguish individuals by
+pub fn is_degenerate(with: f64) -> bool {
+    with <= 0.0 || with.is_nan()
+}
+
 impl Shifted<Fitness> {
     pub fn normalize(self, with: f64) -> Normalized<Fitness> {
-        Normalized(Fitness(self.value() / with.max(1.0)))
+        Normalized(Fitness(normalize_value(self.value(), with)))
     }
 }
 
@@ -134,13 +154,13 @@ impl Raw<Novelty> {
 
 impl Shifted<Novelty> {
     pub fn normalize(self, with: f64) -> Normalized<Novelty> {
-        Normalized(Novelty(self.value() / with.max(1.0)))
+        Normalized(Novelty(normalize_value(self.value(), with)))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Fitness, Normalized, Raw, Shifted};
+    use super::{Fitness, Normalized, Novelty, NoveltyScore, Raw, Shifted};
 
     #[test]
     fn shift_raw() {
@@ -159,4 +179,31 @@ mod tests {
 
         assert_eq!(normalized, Normalized(Fitness(0.5)))
     }
+
+    #[test]
+    fn normalize_below_one() {
+        let shifted = Shifted(Fitness(0.25));
+
+        let normalized = shifted.normalize(0.5);
+
+        assert_eq!(normalized, Normalized(Fitness(0.5)))
+    }
+
+    #[test]
+    fn normalize_degenerate_uniformly() {
+        // all values equal, i.e. shifted maximum is zero
+        let fitness = Shifted(Fitness(0.0)).normalize(0.0);
+        let novelty = Shifted(Novelty(0.0)).normalize(0.0);
+
+        assert_eq!(fitness, Normalized(Fitness(1.0)));
+        assert_eq!(novelty, Normalized(Novelty(1.0)));
+    }
+
+    #[test]
+    fn single_behavior_novelty_score() {
+        // a single behavior has no neighbors and a novelty of zero
+        let score = NoveltyScore::new(0.0, 0.0, 0.0);
+
+        assert_eq!(score.normalized, Normalized(Novelty(1.0)));
+    }
 }
@@ -0,0 +1,293 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use serde::Serialize;
+
+use crate::genes::{connections::Connection, Activation, Id};
+
+use super::genome::Genome;
+
+// broken invariant of a genome, see Genome::validate
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Violation {
+    // connection refers to a node that is not part of the genome
+    DanglingConnection {
+        input: Id,
+        output: Id,
+        recurrent: bool,
+    },
+    // feed forward connection ends in an input node
+    ConnectionIntoInput {
+        input: Id,
+        output: Id,
+    },
+    // same id used by several nodes
+    DuplicateId(Id),
+    // feed forward connections looping back onto their start, listed in order
+    FeedForwardCycle(Vec<Id>),
+    // no path from any input to the output
+    UnreachableOutput(Id),
+    // sizes of output heads do not add up to the number of outputs
+    OutputHeads {
+        declared: usize,
+        outputs: usize,
+    },
+    // custom activation loaded by name but never resolved, see Genome::resolve_activations
+    UnresolvedActivation(Id),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::DanglingConnection {
+                input,
+                output,
+                recurrent,
+            } => write!(
+                f,
+                "{} connection {} -> {} has a missing endpoint",
+                if *recurrent {
+                    "recurrent"
+                } else {
+                    "feed forward"
+                },
+                input.0,
+                output.0
+            ),
+            Violation::ConnectionIntoInput { input, output } => write!(
+                f,
+                "feed forward connection {} -> {} ends in an input",
+                input.0, output.0
+            ),
+            Violation::DuplicateId(id) => write!(f, "id {} is used by several nodes", id.0),
+            Violation::FeedForwardCycle(cycle) => {
+                let ids: Vec<String> = cycle.iter().map(|id| id.0.to_string()).collect();
+                write!(f, "feed forward cycle {}", ids.join(" -> "))
+            }
+            Violation::UnreachableOutput(id) => {
+                write!(f, "output {} is not reachable from any input", id.0)
+            }
+            Violation::OutputHeads { declared, outputs } => write!(
+                f,
+                "output heads cover {} outputs instead of {}",
+                declared, outputs
+            ),
+            Violation::UnresolvedActivation(id) => {
+                write!(f, "node {} has an unresolved custom activation", id.0)
+            }
+        }
+    }
+}
+
+pub fn validate(genome: &Genome) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    let mut seen = HashSet::new();
+    let mut duplicates: Vec<Id> = genome
+        .nodes()
+        .map(|node| node.id())
+        .filter(|&id| !seen.insert(id))
+        .collect();
+    duplicates.sort();
+    duplicates.dedup();
+    violations.extend(duplicates.into_iter().map(Violation::DuplicateId));
+
+    let mut unresolved: Vec<Id> = genome
+        .nodes()
+        .filter(|node| matches!(node.1, Activation::Custom(custom) if !custom.is_resolved()))
+        .map(|node| node.id())
+        .collect();
+    unresolved.sort();
+    violations.extend(unresolved.into_iter().map(Violation::UnresolvedActivation));
+
+    let declared: usize = genome.output_heads.iter().sum();
+    if !genome.output_heads.is_empty() && declared != genome.outputs.len() {
+        violations.push(Violation::OutputHeads {
+            declared,
+            outputs: genome.outputs.len(),
+        });
+    }
+
+    let inputs: HashSet<Id> = genome.inputs.iter().map(|node| node.id()).collect();
+
+    let connections = genome
+        .feed_forward
+        .iterate_unwrapped()
+        .map(|connection| (connection, false))
+        .chain(
+            genome
+                .recurrent
+                .iterate_unwrapped()
+                .map(|connection| (connection, true)),
+        );
+    for (connection, recurrent) in connections {
+        let (input, output) = (connection.input(), connection.output());
+        if !seen.contains(&input) || !seen.contains(&output) {
+            violations.push(Violation::DanglingConnection {
+                input,
+                output,
+                recurrent,
+            });
+        } else if !recurrent && inputs.contains(&output) {
+            violations.push(Violation::ConnectionIntoInput { input, output });
+        }
+    }
+
+    let successors = successors(genome);
+
+    if let Some(cycle) = find_cycle(&successors) {
+        violations.push(Violation::FeedForwardCycle(cycle));
+    }
+
+    // recurrent connections carry values as well
+    let mut reachable: HashSet<Id> = inputs.clone();
+    let mut pending: Vec<Id> = inputs.into_iter().collect();
+    while let Some(id) = pending.pop() {
+        let recurrent_targets = genome
+            .recurrent
+            .iterate_unwrapped()
+            .filter(|connection| connection.input() == id)
+            .map(Connection::output);
+        for target in successors
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .chain(recurrent_targets)
+        {
+            if reachable.insert(target) {
+                pending.push(target);
+            }
+        }
+    }
+
+    let mut unreachable: Vec<Id> = genome
+        .outputs
+        .iter()
+        .map(|node| node.id())
+        .filter(|id| !reachable.contains(id))
+        .collect();
+    unreachable.sort();
+    violations.extend(unreachable.into_iter().map(Violation::UnreachableOutput));
+
+    violations
+}
+
+// successors along feed forward connections, in order of ids
+fn successors(genome: &Genome) -> HashMap<Id, Vec<Id>> {
+    let mut successors: HashMap<Id, Vec<Id>> = HashMap::new();
+    for connection in genome.feed_forward.iterate_unwrapped() {
+        successors
+            .entry(connection.input())
+            .or_default()
+            .push(connection.output());
+    }
+    for targets in successors.values_mut() {
+        targets.sort();
+    }
+    successors
+}
+
+pub(crate) fn feed_forward_cycle(genome: &Genome) -> Option<Vec<Id>> {
+    find_cycle(&successors(genome))
+}
+
+// depth first search, returns the first cycle found in order of ids
+fn find_cycle(successors: &HashMap<Id, Vec<Id>>) -> Option<Vec<Id>> {
+    let mut starts: Vec<Id> = successors.keys().cloned().collect();
+    starts.sort();
+
+    let mut finished = HashSet::new();
+
+    for start in starts {
+        if finished.contains(&start) {
+            continue;
+        }
+
+        // current path with index of the next successor to visit
+        let mut path: Vec<(Id, usize)> = vec![(start, 0)];
+
+        while let Some(&(id, next)) = path.last() {
+            match successors.get(&id).and_then(|targets| targets.get(next)) {
+                Some(&target) => {
+                    if let Some(last) = path.last_mut() {
+                        last.1 += 1;
+                    }
+                    if let Some(position) = path.iter().position(|&(on_path, _)| on_path == target)
+                    {
+                        return Some(path[position..].iter().map(|&(id, _)| id).collect());
+                    }
+                    if !finished.contains(&target) {
+                        path.push((target, 0));
+                    }
+                }
+                None => {
+                    finished.insert(id);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Violation;
+    use crate::{
+        genes::{
+            connections::{Connection, FeedForward},
+            nodes::{Hidden, Input, Node, Output},
+            Activation, Id, Weight,
+        },
+        individual::genome::Genome,
+    };
+
+    #[test]
+    fn detect_violations() {
+        let mut genome = Genome {
+            inputs: vec![Input(Node::new(Id(0), Activation::Linear))]
+                .into_iter()
+                .collect(),
+            outputs: vec![Output(Node::new(Id(1), Activation::Linear))]
+                .into_iter()
+                .collect(),
+            hidden: vec![
+                Hidden(Node::new(Id(2), Activation::Tanh)),
+                Hidden(Node::new(Id(3), Activation::Tanh)),
+            ]
+            .into_iter()
+            .collect(),
+            feed_forward: vec![FeedForward(Connection(Id(0), Weight(1.0), Id(1)))]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+
+        assert!(genome.validate().is_empty());
+
+        genome.feed_forward.clear();
+        genome
+            .feed_forward
+            .insert(FeedForward(Connection(Id(2), Weight(1.0), Id(3))));
+        genome
+            .feed_forward
+            .insert(FeedForward(Connection(Id(3), Weight(1.0), Id(2))));
+        genome
+            .feed_forward
+            .insert(FeedForward(Connection(Id(3), Weight(1.0), Id(4))));
+
+        let violations = genome.validate();
+
+        assert!(violations.contains(&Violation::DanglingConnection {
+            input: Id(3),
+            output: Id(4),
+            recurrent: false
+        }));
+        assert!(violations.contains(&Violation::FeedForwardCycle(vec![Id(2), Id(3)])));
+        assert!(violations.contains(&Violation::UnreachableOutput(Id(1))));
+    }
+}
@@ -0,0 +1,302 @@
+// minimal forward pass of evolved genomes, only needs core and alloc, i.e. for embedded targets
+use alloc::{vec, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::genes::{Activation, Aggregation};
+
+// hidden or output node, inputs are indices into the value buffer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControllerNode {
+    pub activation: Activation,
+    pub bias: f64,
+    pub response: f64,
+    pub inputs: Vec<(usize, f64)>,
+    // read from the values of the previous activation
+    pub recurrent_inputs: Vec<(usize, f64)>,
+    // output moves towards the activation by 1 / time_constant per step, i.e. CTRNN
+    #[serde(default = "default_time_constant")]
+    pub time_constant: f64,
+    #[serde(default)]
+    pub aggregation: Aggregation,
+}
+
+fn default_time_constant() -> f64 {
+    1.0
+}
+
+// value buffer layout: inputs first, then nodes in evaluation order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Controller {
+    pub input_count: usize,
+    pub nodes: Vec<ControllerNode>,
+    pub outputs: Vec<usize>,
+    #[serde(skip)]
+    state: Vec<f64>,
+}
+
+impl Controller {
+    pub fn new(input_count: usize, nodes: Vec<ControllerNode>, outputs: Vec<usize>) -> Self {
+        Self {
+            input_count,
+            nodes,
+            outputs,
+            state: Vec::new(),
+        }
+    }
+
+    // expects at least input_count inputs, further ones are ignored, panics on fewer
+    pub fn activate(&mut self, inputs: &[f64]) -> Vec<f64> {
+        debug_assert!(
+            inputs.len() >= self.input_count,
+            "controller expects {} inputs, got {}",
+            self.input_count,
+            inputs.len()
+        );
+        let size = self.input_count + self.nodes.len();
+
+        if self.state.len() != size {
+            self.state = vec![0.0; size];
+        }
+
+        let mut values = vec![0.0; size];
+        values[..self.input_count].copy_from_slice(&inputs[..self.input_count]);
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let input = node.aggregation.aggregate(
+                node.inputs
+                    .iter()
+                    .map(|&(source, weight)| values[source] * weight)
+                    .chain(
+                        node.recurrent_inputs
+                            .iter()
+                            .map(|&(source, weight)| self.state[source] * weight),
+                    ),
+            );
+
+            let activation = node.activation.function()(node.bias + node.response * input);
+            let previous = self.state[self.input_count + index];
+
+            values[self.input_count + index] =
+                previous + (activation - previous) / node.time_constant;
+        }
+
+        let outputs = self.outputs.iter().map(|&index| values[index]).collect();
+
+        self.state = values;
+
+        outputs
+    }
+
+    // forget values carried by recurrent connections
+    pub fn reset(&mut self) {
+        self.state.clear();
+    }
+}
+
+#[cfg(feature = "std")]
+mod conversion {
+    use std::collections::HashMap;
+
+    use super::{Controller, ControllerNode};
+    use crate::{
+        genes::{nodes::Node, Id},
+        Individual,
+    };
+
+    impl From<&Individual> for Controller {
+        // built from the expressed genome, i.e. the network the runtime evaluates
+        fn from(individual: &Individual) -> Self {
+            let genome = individual.genome.expressed();
+            let inputs = genome.inputs.as_sorted_vec();
+            let outputs = genome.outputs.as_sorted_vec();
+
+            let mut pending: Vec<&Node> = genome
+                .hidden
+                .as_sorted_vec()
+                .into_iter()
+                .chain(outputs.iter().cloned())
+                .collect();
+
+            let mut positions: HashMap<Id, usize> = inputs
+                .iter()
+                .enumerate()
+                .map(|(index, node)| (node.id(), index))
+                .collect();
+
+            // order nodes such that all feed-forward inputs are computed beforehand
+            let mut ordered: Vec<&Node> = Vec::new();
+            while !pending.is_empty() {
+                let (mut ready, blocked): (Vec<&Node>, Vec<&Node>) =
+                    pending.into_iter().partition(|node| {
+                        genome
+                            .feed_forward
+                            .iterate_unwrapped()
+                            .filter(|connection| connection.output() == node.id())
+                            .all(|connection| positions.contains_key(&connection.input()))
+                    });
+
+                // nodes of unreachable cycles are appended as is
+                if ready.is_empty() {
+                    ready = blocked;
+                    pending = Vec::new();
+                } else {
+                    pending = blocked;
+                }
+
+                for node in ready {
+                    positions.insert(node.id(), inputs.len() + ordered.len());
+                    ordered.push(node);
+                }
+            }
+
+            let incoming = |node: &Node, recurrent: bool| -> Vec<(usize, f64)> {
+                let connections = if recurrent {
+                    genome.recurrent.as_sorted_vec()
+                } else {
+                    genome.feed_forward.as_sorted_vec()
+                };
+                connections
+                    .into_iter()
+                    .filter(|connection| connection.output() == node.id())
+                    .flat_map(|connection| {
+                        positions
+                            .get(&connection.input())
+                            .map(|&source| (source, connection.1.value()))
+                    })
+                    .collect()
+            };
+
+            let nodes = ordered
+                .into_iter()
+                .map(|node| ControllerNode {
+                    activation: node.1,
+                    bias: node.effective_bias(),
+                    response: node.effective_response(),
+                    inputs: incoming(node, false),
+                    recurrent_inputs: incoming(node, true),
+                    time_constant: (node.5).0,
+                    aggregation: node.6,
+                })
+                .collect();
+
+            let outputs = outputs.iter().map(|node| positions[&node.id()]).collect();
+
+            Controller::new(inputs.len(), nodes, outputs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Controller, ControllerNode};
+    use crate::genes::{Activation, Aggregation};
+
+    #[test]
+    fn forward_pass() {
+        let mut controller = Controller::new(
+            1,
+            vec![ControllerNode {
+                activation: Activation::Linear,
+                bias: 0.5,
+                response: 1.0,
+                inputs: vec![(0, 2.0)],
+                recurrent_inputs: vec![(1, 1.0)],
+                time_constant: 1.0,
+                aggregation: Aggregation::Sum,
+            }],
+            vec![1],
+        );
+
+        assert_eq!(controller.activate(&[1.0]), vec![2.5]);
+        // previous output is carried by the recurrent connection
+        assert_eq!(controller.activate(&[1.0]), vec![5.0]);
+
+        controller.reset();
+        assert_eq!(controller.activate(&[1.0]), vec![2.5]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "controller expects 2 inputs, got 1")]
+    fn reject_missing_inputs() {
+        Controller::new(2, Vec::new(), Vec::new()).activate(&[1.0]);
+    }
+
+    #[test]
+    fn leaky_integration() {
+        let mut controller = Controller::new(
+            1,
+            vec![ControllerNode {
+                activation: Activation::Linear,
+                bias: 0.0,
+                response: 1.0,
+                inputs: vec![(0, 1.0)],
+                recurrent_inputs: vec![],
+                time_constant: 2.0,
+                aggregation: Aggregation::Sum,
+            }],
+            vec![1],
+        );
+
+        // halfway towards the activation every step
+        assert_eq!(controller.activate(&[1.0]), vec![0.5]);
+        assert_eq!(controller.activate(&[1.0]), vec![0.75]);
+    }
+
+    #[test]
+    fn aggregate_by_maximum() {
+        let mut controller = Controller::new(
+            2,
+            vec![ControllerNode {
+                activation: Activation::Linear,
+                bias: 0.0,
+                response: 1.0,
+                inputs: vec![(0, 1.0), (1, 2.0)],
+                recurrent_inputs: vec![],
+                time_constant: 1.0,
+                aggregation: Aggregation::Max,
+            }],
+            vec![2],
+        );
+
+        assert_eq!(controller.activate(&[3.0, 1.0]), vec![3.0]);
+        assert_eq!(controller.activate(&[1.0, 1.0]), vec![2.0]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn convert_expressed_genome() {
+        use crate::{
+            genes::{
+                connections::{Connection, Parallel},
+                nodes::Node,
+                Id, Weight,
+            },
+            individual::genome::Genome,
+            Individual,
+        };
+
+        let mut genome = Genome::builder()
+            .input(Id(0))
+            .output(Node::new(Id(1), Activation::Linear))
+            .connection(Id(0), 1.0, Id(1))
+            .build()
+            .unwrap();
+        genome
+            .parallel
+            .insert(Parallel(Id(2), Connection(Id(0), Weight::new(0.5), Id(1))));
+        let individual = Individual {
+            genome,
+            ..Default::default()
+        };
+
+        // the parallel connection is summed in as during evaluation
+        assert_eq!(Controller::from(&individual).activate(&[2.0]), vec![3.0]);
+
+        // disabled connections are left out as during evaluation
+        let mut individual = individual;
+        individual.disabled.insert((Id(0), Id(1)));
+        assert_eq!(Controller::from(&individual).activate(&[2.0]), vec![0.0]);
+    }
+}
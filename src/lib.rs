@@ -1,32 +1,293 @@
-pub use individual::Individual;
-use parameters::Parameters;
-pub use runtime::{evaluation::Evaluation, progress::Progress, Runtime};
+#![cfg_attr(not(feature = "std"), no_std)]
 
-mod genes;
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+use futures::future::BoxFuture;
+
+#[cfg(feature = "std")]
+pub use error::NeatError;
+#[cfg(feature = "std")]
+pub use individual::{
+    behavior::{descriptors, Behavior},
+    bundle::Bundle,
+    diff::{ConnectionChange, ConnectionSummary, GenomeDiff, NodeChange, NodeSummary},
+    genealogy::{Ancestry, Genealogy},
+    genome::{Genome, GenomeBuilder, MutationRates, WeightGroup},
+    io_growth::{IoGrowth, IoWiring},
+    mutation::MutationOperator,
+    neat_python::NeatPythonGenome,
+    validation::Violation,
+    Individual,
+};
+pub use inference::Controller;
+#[cfg(feature = "std")]
+pub use parameters::{Parameters, ParametersBuilder};
+#[cfg(feature = "distributed")]
+pub use runtime::distributed::{serve, serve_stdio, DistributedEvaluator, Worker};
+#[cfg(feature = "std")]
+pub use runtime::{
+    artifacts::RunArtifacts,
+    checkpoint::{Archive, ArchiveThreshold, Checkpoint},
+    coevolution::{
+        CoEvolutionFunction, CoEvolutionRuntime, CoEvolutionSchedule, Opponent, Pairing,
+    },
+    evaluation::{EvalError, Evaluation, EvaluationContext},
+    progress::{Objective, Progress, ProgressLike},
+    report::RunReport,
+    OwnedRuntime, Runtime,
+};
+
+#[cfg(feature = "benchmarks")]
+pub mod benchmarks;
+#[cfg(feature = "examples")]
+pub mod environments;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+pub mod export;
+// genes need alloc only, see feature genome
+#[cfg(any(feature = "std", feature = "genome"))]
+pub mod genes;
+// only activations and aggregations are needed for inference without std
+#[cfg(not(any(feature = "std", feature = "genome")))]
+mod genes {
+    pub mod activations;
+    pub mod aggregations;
+    pub use activations::Activation;
+    pub use aggregations::Aggregation;
+}
+#[cfg(feature = "std")]
 mod individual;
+pub mod inference;
+#[cfg(feature = "std")]
 mod parameters;
+#[cfg(feature = "std")]
 mod population;
+#[cfg(feature = "std")]
 mod runtime;
+#[cfg(feature = "std")]
 pub mod utility;
 
+#[cfg(feature = "std")]
+pub(crate) enum ProgressFunction {
+    Blocking(Box<dyn Fn(&Individual) -> Progress + Send + Sync>),
+    Fallible(Box<dyn Fn(&Individual) -> Result<Progress, EvalError> + Send + Sync>),
+    Contextual(Box<dyn Fn(&Individual, &EvaluationContext) -> Progress + Send + Sync>),
+    #[cfg(feature = "async")]
+    Async(Box<dyn Fn(&Individual) -> BoxFuture<'static, Progress> + Send + Sync>),
+    #[cfg(feature = "distributed")]
+    Distributed(DistributedEvaluator),
+}
+
+#[cfg(feature = "std")]
 pub struct Neat {
-    pub parameters: Parameters,
-    progress_function: Box<dyn Fn(&Individual) -> Progress + Send + Sync>,
+    // shared handle, use Arc::make_mut to change parameters before running
+    pub parameters: Arc<Parameters>,
+    progress_function: ProgressFunction,
+    // custom activations of this Neat, see with_activation
+    activations: genes::ActivationRegistry,
+    // evaluations run on rayon's global pool if None
+    #[cfg(feature = "parallel")]
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+    // upper bound of evaluations awaited at the same time, unbounded if None
+    #[cfg(feature = "async")]
+    max_concurrency: Option<usize>,
 }
 
 // public API
+#[cfg(feature = "std")]
 impl Neat {
+    #[cfg(feature = "config-file")]
     pub fn new(
         path: &str,
         progress_function: Box<dyn Fn(&Individual) -> Progress + Send + Sync>,
+    ) -> Result<Self, NeatError> {
+        Ok(Neat::with_progress_function(
+            Arc::new(Parameters::new(path)?),
+            ProgressFunction::Blocking(progress_function),
+        ))
+    }
+
+    // no configuration file involved, i.e. for wasm
+    pub fn from_parameters(
+        mut parameters: Parameters,
+        progress_function: Box<dyn Fn(&Individual) -> Progress + Send + Sync>,
     ) -> Self {
-        Neat {
-            parameters: Parameters::new(path).unwrap(),
-            progress_function,
+        parameters.resolve_seed();
+        Neat::with_progress_function(
+            Arc::new(parameters),
+            ProgressFunction::Blocking(progress_function),
+        )
+    }
+
+    // failed evaluations are handled according to parameters.setup.evaluation_error_policy
+    #[cfg(feature = "config-file")]
+    pub fn new_fallible(
+        path: &str,
+        progress_function: Box<dyn Fn(&Individual) -> Result<Progress, EvalError> + Send + Sync>,
+    ) -> Result<Self, NeatError> {
+        Ok(Neat::with_progress_function(
+            Arc::new(Parameters::new(path)?),
+            ProgressFunction::Fallible(progress_function),
+        ))
+    }
+
+    pub fn from_parameters_fallible(
+        mut parameters: Parameters,
+        progress_function: Box<dyn Fn(&Individual) -> Result<Progress, EvalError> + Send + Sync>,
+    ) -> Self {
+        parameters.resolve_seed();
+        Neat::with_progress_function(
+            Arc::new(parameters),
+            ProgressFunction::Fallible(progress_function),
+        )
+    }
+
+    // progress function additionally receives the generation and its seed
+    #[cfg(feature = "config-file")]
+    pub fn new_with_context(
+        path: &str,
+        progress_function: Box<dyn Fn(&Individual, &EvaluationContext) -> Progress + Send + Sync>,
+    ) -> Result<Self, NeatError> {
+        Ok(Neat::with_progress_function(
+            Arc::new(Parameters::new(path)?),
+            ProgressFunction::Contextual(progress_function),
+        ))
+    }
+
+    pub fn from_parameters_with_context(
+        mut parameters: Parameters,
+        progress_function: Box<dyn Fn(&Individual, &EvaluationContext) -> Progress + Send + Sync>,
+    ) -> Self {
+        parameters.resolve_seed();
+        Neat::with_progress_function(
+            Arc::new(parameters),
+            ProgressFunction::Contextual(progress_function),
+        )
+    }
+
+    // progress function returns a future, evaluations are awaited concurrently via Runtime::next_async
+    #[cfg(all(feature = "async", feature = "config-file"))]
+    pub fn new_async(
+        path: &str,
+        progress_function: Box<dyn Fn(&Individual) -> BoxFuture<'static, Progress> + Send + Sync>,
+    ) -> Result<Self, NeatError> {
+        Ok(Neat::with_progress_function(
+            Arc::new(Parameters::new(path)?),
+            ProgressFunction::Async(progress_function),
+        ))
+    }
+
+    // individuals are evaluated by worker processes, see runtime::distributed
+    #[cfg(all(feature = "distributed", feature = "config-file"))]
+    pub fn new_distributed(
+        path: &str,
+        evaluator: DistributedEvaluator,
+    ) -> Result<Self, NeatError> {
+        Ok(Neat::with_progress_function(
+            Arc::new(Parameters::new(path)?),
+            ProgressFunction::Distributed(evaluator),
+        ))
+    }
+
+    // cheap handle to the resolved parameters, i.e. for worker threads
+    pub fn parameters(&self) -> Arc<Parameters> {
+        Arc::clone(&self.parameters)
+    }
+
+    // makes a named activation available, list it in parameters.activations to be used by mutation,
+    // genes of loaded parameters, seeds or checkpoints refering to the name resolve to the given function
+    pub fn with_activation(mut self, name: &str, function: fn(f64) -> f64) -> Self {
+        self.activations
+            .register(name, function, (f64::NEG_INFINITY, f64::INFINITY));
+        // names registered later resolve then, the runtime reports those never registered
+        let _ = Arc::make_mut(&mut self.parameters)
+            .activations
+            .resolve(&self.activations);
+        self
+    }
+
+    // i.e. to resolve genomes loaded for inference, see Genome::resolve_activations
+    pub fn activations(&self) -> &genes::ActivationRegistry {
+        &self.activations
+    }
+
+    // structural mutation applied after the built-in ones, with its own probability
+    pub fn with_mutation_operator(mut self, operator: impl MutationOperator + 'static) -> Self {
+        Arc::make_mut(&mut self.parameters)
+            .mutation
+            .custom_operators
+            .push(Arc::new(operator));
+        self
+    }
+
+    // evaluates on given pool instead of rayon's global one
+    #[cfg(feature = "parallel")]
+    pub fn with_thread_pool(mut self, thread_pool: Arc<rayon::ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    // at most given number of individuals are evaluated at the same time,
+    // builds a dedicated thread pool of that size
+    #[cfg_attr(
+        not(any(feature = "parallel", feature = "async")),
+        allow(unused_mut, unused_variables)
+    )]
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Result<Self, NeatError> {
+        let max_concurrency = max_concurrency.max(1);
+        #[cfg(feature = "parallel")]
+        {
+            self.thread_pool = Some(Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(max_concurrency)
+                    .build()?,
+            ));
+        }
+        #[cfg(feature = "async")]
+        {
+            self.max_concurrency = Some(max_concurrency);
         }
+        Ok(self)
     }
 
     pub fn run(&self) -> Runtime {
-        Runtime::new(&self)
+        Runtime::new(self)
+    }
+
+    // initial population is derived from given individual, i.e. a previous champion
+    pub fn run_from_seed(&self, seed: &Individual) -> Runtime {
+        Runtime::from_seed(self, seed)
+    }
+
+    pub fn resume(&self, checkpoint: Checkpoint) -> Runtime {
+        Runtime::from_checkpoint(self, checkpoint)
+    }
+
+    // runtime without lifetime, use Runtime::new(Arc<Neat>) to keep a handle to Neat
+    pub fn into_runtime(self) -> OwnedRuntime {
+        Runtime::new(Arc::new(self))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Neat {
+    fn with_progress_function(
+        parameters: Arc<Parameters>,
+        progress_function: ProgressFunction,
+    ) -> Self {
+        Neat {
+            parameters,
+            progress_function,
+            activations: Default::default(),
+            #[cfg(feature = "parallel")]
+            thread_pool: None,
+            #[cfg(feature = "async")]
+            max_concurrency: None,
+        }
     }
 }
@@ -1,28 +1,701 @@
-use crate::genes::Activation;
+use std::collections::HashMap;
+
+use crate::{
+    error::NeatError,
+    genes::{Activation, ActivationRegistry, Aggregation, Annotation, Id},
+    individual::mutation::CustomOperators,
+};
+#[cfg(feature = "config-file")]
 use config::{Config, ConfigError, File};
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
 pub struct Parameters {
     pub setup: Setup,
     pub activations: Activations,
     pub mutation: Mutation,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
 pub struct Setup {
+    #[serde(default)]
     pub seed: u64,
     pub survival_rate: f64,
     pub population_size: usize,
     pub input_dimension: usize,
     pub output_dimension: usize,
     pub novelty_nearest_neighbors: usize,
+    #[serde(default)]
+    pub novelty_neighbors_scaling: NeighborsScaling,
+    // influence of archived behaviors over the generations since they were archived
+    #[serde(default)]
+    pub archive_decay: ArchiveDecay,
+    #[serde(default)]
+    pub archive_addition: ArchiveAddition,
+    #[serde(default)]
+    pub offspring_allocation: OffspringAllocation,
+    #[serde(default)]
+    pub evaluation_error_policy: EvaluationErrorPolicy,
+    #[serde(default)]
+    pub solution_selection: SolutionSelection,
+    // evaluates a reported solution again before the run ends, i.e. in noisy environments
+    #[serde(default)]
+    pub solution_verification: Option<SolutionVerification>,
+    #[serde(default)]
+    pub innovation_scope: InnovationScope,
+    // rank fitness only against the nearest behavioral neighbors
+    #[serde(default)]
+    pub local_competition: bool,
+    #[serde(default)]
+    pub survival_scheme: SurvivalScheme,
+    // overrides whether survivors are evaluated again, follows survival_scheme if None
+    #[serde(default)]
+    pub reevaluate_survivors: Option<SurvivorReevaluation>,
+    #[serde(default)]
+    pub behavior_alignment: BehaviorAlignment,
+    // shrinks long behaviors before novelty distances are computed
+    #[serde(default)]
+    pub behavior_reduction: BehaviorReduction,
+    #[serde(default)]
+    pub snapshot: Snapshot,
+    #[serde(default)]
+    pub selection: Selection,
+    // attached to input and output i of every initial genome, i.e. to group sensors
+    #[serde(default)]
+    pub input_annotations: Vec<Annotation>,
+    #[serde(default)]
+    pub output_annotations: Vec<Annotation>,
+    // connections by input and output id that share one weight in every initial genome, i.e. mirrored legs,
+    // inputs are numbered from 0 and outputs follow
+    #[serde(default)]
+    pub weight_groups: Vec<Vec<(Id, Id)>>,
+    // sizes of fully connected hidden layers every initial genome starts with
+    #[serde(default)]
+    pub initial_hidden_layers: Vec<usize>,
+    // weights to scalarize named objectives into fitness, missing ones weigh 1.0
+    #[serde(default)]
+    pub objective_weights: HashMap<String, f64>,
+    #[serde(default)]
+    pub age_adjustment: AgeAdjustment,
+    #[serde(default)]
+    pub survival_selection: SurvivalSelection,
+    // record parents of every individual, see Runtime::genealogy
+    #[serde(default)]
+    pub track_lineage: bool,
+    #[serde(default)]
+    pub extinction: Option<Extinction>,
+    #[serde(default)]
+    pub islands: Option<IslandModel>,
+    // sizes of consecutive groups of outputs, empty for a single head with all outputs
+    #[serde(default)]
+    pub output_heads: Vec<usize>,
+    // combines per head fitness of Progress::PerHead into one fitness
+    #[serde(default)]
+    pub head_aggregation: HeadAggregation,
+    // decides between individuals of equal score, wherever individuals are ranked
+    #[serde(default)]
+    pub tie_breaking: TieBreaking,
+    #[serde(default)]
+    pub fitness_shaping: FitnessShaping,
+    // evaluations of every individual in noisy environments, values below two evaluate once
+    #[serde(default)]
+    pub trials_per_individual: usize,
+    #[serde(default)]
+    pub trial_fitness: TrialFitness,
+    #[serde(default)]
+    pub trial_behavior: TrialBehavior,
+    // evaluations remembered by genome fingerprint, only for deterministic progress functions, zero disables caching
+    #[serde(default)]
+    pub evaluation_cache_size: usize,
+    // calls of the progress function after which the run stops, see Evaluation::BudgetExhausted
+    #[serde(default)]
+    pub max_evaluations: Option<usize>,
+}
+
+// restarts most of the population when fitness stagnates, the novelty archive is preserved
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Extinction {
+    // generations without improvement of the best raw fitness ever seen
+    pub stagnation_generations: usize,
+    // best individuals surviving the extinction, the rest is replaced by fresh genomes
+    pub survivors: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum HeadAggregation {
+    Sum,
+    Minimum,
+    Mean,
+    // weighted sum, heads without weight count once
+    Weighted(Vec<f64>),
+}
+
+impl Default for HeadAggregation {
+    fn default() -> Self {
+        HeadAggregation::Sum
+    }
+}
+
+impl HeadAggregation {
+    pub fn aggregate(&self, fitnesses: &[f64]) -> f64 {
+        match self {
+            HeadAggregation::Sum => fitnesses.iter().sum(),
+            HeadAggregation::Minimum => fitnesses.iter().cloned().fold(f64::INFINITY, f64::min),
+            HeadAggregation::Mean => fitnesses.iter().sum::<f64>() / fitnesses.len().max(1) as f64,
+            HeadAggregation::Weighted(weights) => fitnesses
+                .iter()
+                .enumerate()
+                .map(|(index, fitness)| fitness * weights.get(index).unwrap_or(&1.0))
+                .sum(),
+        }
+    }
+}
+
+// combines the fitness of repeated trials, see Setup::trials_per_individual
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum TrialFitness {
+    Mean,
+    Median,
+    // worst case, favors individuals that are robust to noise
+    Minimum,
+}
+
+impl Default for TrialFitness {
+    fn default() -> Self {
+        TrialFitness::Mean
+    }
+}
+
+impl TrialFitness {
+    pub fn aggregate(&self, fitnesses: &[f64]) -> f64 {
+        match self {
+            TrialFitness::Mean => fitnesses.iter().sum::<f64>() / fitnesses.len().max(1) as f64,
+            // NaN trials are left out, as with the minimum
+            TrialFitness::Median => {
+                let mut sorted: Vec<f64> = fitnesses
+                    .iter()
+                    .cloned()
+                    .filter(|fitness| !fitness.is_nan())
+                    .collect();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                match sorted.len() {
+                    0 => 0.0,
+                    len if len % 2 == 0 => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+                    len => sorted[len / 2],
+                }
+            }
+            TrialFitness::Minimum => fitnesses.iter().cloned().fold(f64::INFINITY, f64::min),
+        }
+    }
+}
+
+// combines the behaviors of repeated trials
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum TrialBehavior {
+    // element wise, behaviors are cut to the shortest one
+    Mean,
+    // behaviors one after another in order of trials
+    Concatenation,
+}
+
+impl Default for TrialBehavior {
+    fn default() -> Self {
+        TrialBehavior::Mean
+    }
+}
+
+impl TrialBehavior {
+    pub fn aggregate(&self, behaviors: &[Vec<f64>]) -> Vec<f64> {
+        match self {
+            TrialBehavior::Mean => {
+                let length = behaviors.iter().map(Vec::len).min().unwrap_or(0);
+                (0..length)
+                    .map(|index| {
+                        behaviors
+                            .iter()
+                            .map(|behavior| behavior[index])
+                            .sum::<f64>()
+                            / behaviors.len() as f64
+                    })
+                    .collect()
+            }
+            TrialBehavior::Concatenation => behaviors.concat(),
+        }
+    }
+}
+
+// how normalized fitness is derived from raw fitness
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum FitnessShaping {
+    // shifted by the minimum and scaled by the maximum
+    Proportional,
+    // evenly spaced by rank from zero for the worst to one for the best, equal fitness shares the better rank
+    LinearRank,
+    // one for the best, multiplied by given base in (0, 1) for every rank below
+    ExponentialRank(f64),
+}
+
+impl Default for FitnessShaping {
+    fn default() -> Self {
+        FitnessShaping::Proportional
+    }
+}
+
+impl FitnessShaping {
+    // normalized fitness of every raw fitness, in order
+    pub fn shape(&self, raw: &[f64]) -> Option<Vec<f64>> {
+        if let FitnessShaping::Proportional = self {
+            return None;
+        }
+
+        let mut sorted = raw.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let worst_rank = sorted.len().saturating_sub(1);
+
+        Some(
+            raw.iter()
+                .map(|value| {
+                    // number of other values this one is at least as good as
+                    let rank = sorted
+                        .partition_point(|other| other <= value)
+                        .saturating_sub(1);
+                    match self {
+                        FitnessShaping::LinearRank if worst_rank == 0 => 1.0,
+                        FitnessShaping::LinearRank => rank as f64 / worst_rank as f64,
+                        FitnessShaping::ExponentialRank(base) => {
+                            base.powi((worst_rank - rank) as i32)
+                        }
+                        FitnessShaping::Proportional => unreachable!(),
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+// criteria applied in order until one prefers either individual
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct TieBreaking(pub Vec<TieBreaker>);
+
+impl Default for TieBreaking {
+    fn default() -> Self {
+        TieBreaking(vec![
+            TieBreaker::FewerGenes,
+            TieBreaker::Younger,
+            TieBreaker::LowerId,
+        ])
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum TieBreaker {
+    // less complexity
+    FewerGenes,
+    Younger,
+    Older,
+    // created earlier
+    LowerId,
+}
+
+// several populations evolve side by side and exchange their best individuals in a ring
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct IslandModel {
+    // population_size applies to every island
+    pub count: usize,
+    // generations between migrations, zero disables migration
+    pub migration_interval: usize,
+    // best survivors of every island copied to the next island
+    pub migration_size: usize,
+}
+
+// which individuals compete with each other for survival
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum SurvivalSelection {
+    // best of the whole population survive
+    Global,
+    // population is split into given number of behavioral niches, best of each niche survive
+    PerNiche(usize),
+}
+
+impl Default for SurvivalSelection {
+    fn default() -> Self {
+        SurvivalSelection::Global
+    }
+}
+
+// multiplies the selection score depending on age, gives new structure time to optimize
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct AgeAdjustment {
+    // individuals younger than this get their score multiplied by young_age_boost
+    pub young_age_threshold: usize,
+    pub young_age_boost: f64,
+    // individuals older than this get their score multiplied by old_age_penalty
+    pub old_age_threshold: usize,
+    pub old_age_penalty: f64,
+}
+
+impl AgeAdjustment {
+    pub fn factor(&self, age: usize) -> f64 {
+        if age < self.young_age_threshold {
+            self.young_age_boost
+        } else if age > self.old_age_threshold {
+            self.old_age_penalty
+        } else {
+            1.0
+        }
+    }
+}
+
+impl Default for AgeAdjustment {
+    fn default() -> Self {
+        Self {
+            young_age_threshold: 0,
+            young_age_boost: 1.0,
+            old_age_threshold: usize::MAX,
+            old_age_penalty: 1.0,
+        }
+    }
+}
+
+// how the number of nearest neighbors for novelty follows the size of the behavior pool,
+// i.e. population plus archive
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum NeighborsScaling {
+    // always novelty_nearest_neighbors
+    Fixed,
+    // given fraction of the pool, at least novelty_nearest_neighbors
+    Fraction(f64),
+    // square root of the pool, at least novelty_nearest_neighbors
+    SquareRoot,
+}
+
+impl Default for NeighborsScaling {
+    fn default() -> Self {
+        NeighborsScaling::Fixed
+    }
+}
+
+// weight of an archived behavior by its age in the archive, distances to it are divided by its weight,
+// so decayed behaviors seem farther away and matter less as nearest neighbors
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveDecay {
+    // archived behaviors count fully forever
+    None,
+    // weight is multiplied by factor every generation, entries below minimum_weight are evicted
+    Exponential { factor: f64, minimum_weight: f64 },
+    // weight falls linearly to zero over given number of generations, then the entry is evicted
+    Linear(usize),
+}
+
+impl ArchiveDecay {
+    pub fn weight(&self, age: usize) -> f64 {
+        match *self {
+            ArchiveDecay::None => 1.0,
+            ArchiveDecay::Exponential { factor, .. } => factor.powi(age as i32),
+            ArchiveDecay::Linear(generations) => {
+                1.0 - (age as f64 / generations.max(1) as f64).min(1.0)
+            }
+        }
+    }
+
+    pub fn evicts(&self, age: usize) -> bool {
+        match *self {
+            ArchiveDecay::None => false,
+            ArchiveDecay::Exponential { minimum_weight, .. } => self.weight(age) < minimum_weight,
+            ArchiveDecay::Linear(generations) => age >= generations,
+        }
+    }
+}
+
+impl Default for ArchiveDecay {
+    fn default() -> Self {
+        ArchiveDecay::None
+    }
+}
+
+// which individuals enter the novelty archive every generation
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveAddition {
+    // the most novel individual of the generation
+    MostNovel,
+    // every individual whose raw novelty exceeds a threshold as in the original novelty search,
+    // after every interval generations the threshold is multiplied by raise_factor if more than max_additions
+    // entered the archive and by lower_factor if fewer than min_additions did
+    Threshold {
+        initial: f64,
+        interval: usize,
+        min_additions: usize,
+        max_additions: usize,
+        raise_factor: f64,
+        lower_factor: f64,
+    },
+}
+
+impl ArchiveAddition {
+    // threshold for the next interval given the additions during the last one
+    pub fn adjust(&self, threshold: f64, additions: usize) -> f64 {
+        match *self {
+            ArchiveAddition::MostNovel => threshold,
+            ArchiveAddition::Threshold {
+                min_additions,
+                max_additions,
+                raise_factor,
+                lower_factor,
+                ..
+            } => {
+                if additions > max_additions {
+                    threshold * raise_factor
+                } else if additions < min_additions {
+                    threshold * lower_factor
+                } else {
+                    threshold
+                }
+            }
+        }
+    }
+}
+
+impl Default for ArchiveAddition {
+    fn default() -> Self {
+        ArchiveAddition::MostNovel
+    }
+}
+
+impl Setup {
+    // effective k for a pool of given size, scaled k never exceeds the other behaviors in the pool
+    pub fn nearest_neighbors(&self, pool_size: usize) -> usize {
+        let scaled = match self.novelty_neighbors_scaling {
+            NeighborsScaling::Fixed => return self.novelty_nearest_neighbors,
+            NeighborsScaling::Fraction(fraction) => (pool_size as f64 * fraction).ceil(),
+            NeighborsScaling::SquareRoot => (pool_size as f64).sqrt().ceil(),
+        };
+        (scaled as usize)
+            .max(self.novelty_nearest_neighbors)
+            .min(pool_size.saturating_sub(1))
+            .max(1)
+    }
+
+    // survivors not evaluated again keep the scores of their last evaluation
+    pub fn reevaluates_survivors(&self, generation: usize) -> bool {
+        let policy = self
+            .reevaluate_survivors
+            .unwrap_or(match self.survival_scheme {
+                SurvivalScheme::Generational => SurvivorReevaluation::Always,
+                SurvivalScheme::MuPlusLambda => SurvivorReevaluation::Never,
+            });
+        match policy {
+            SurvivorReevaluation::Always => true,
+            SurvivorReevaluation::Never => false,
+            SurvivorReevaluation::Every(generations) => generation % generations.max(1) == 0,
+        }
+    }
+}
+
+// how survivors are ranked
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum Selection {
+    // by combined score of novelty and fitness
+    Score,
+    // by pareto front over novelty and local competition, ties broken by score
+    NoveltyLocalCompetition,
+    // by pareto front over the objectives of Progress::MultiObjective, ties broken by score
+    Pareto,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Selection::Score
+    }
+}
+
+// individuals included in the statistics of each generation, ranked by score
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum Snapshot {
+    Disabled,
+    Top(usize),
+    Full,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Snapshot::Disabled
+    }
+}
+
+// how behaviors of different length are made comparable
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum BehaviorAlignment {
+    // cut every behavior to the shortest length
+    Truncate,
+    // fill every behavior with zeros up to the longest length
+    ZeroPad,
+    // interpolate every behavior onto given number of points
+    Resample(usize),
+    // compare raw behaviors by dynamic time warping distance
+    DynamicTimeWarping,
+}
+
+impl Default for BehaviorAlignment {
+    fn default() -> Self {
+        BehaviorAlignment::ZeroPad
+    }
+}
+
+// how behaviors are shrunk before alignment, see Behaviors::with_reduction
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum BehaviorReduction {
+    None,
+    // keep given number of evenly spaced values, shorter behaviors stay as they are
+    Subsample(usize),
+    // average consecutive values into given number of bins, shorter behaviors stay as they are
+    Bins(usize),
+    // multiply with a gaussian random matrix drawn from seed
+    RandomProjection { dimension: usize, seed: u64 },
+}
+
+impl Default for BehaviorReduction {
+    fn default() -> Self {
+        BehaviorReduction::None
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum SurvivalScheme {
+    // every individual is evaluated each generation, survivors included
+    Generational,
+    // only offspring are evaluated, survivors keep their scores and compete with them, i.e. (mu+lambda)
+    MuPlusLambda,
+}
+
+impl Default for SurvivalScheme {
+    fn default() -> Self {
+        SurvivalScheme::Generational
+    }
+}
+
+// i.e. for noisy evaluations, where scores of survivors turn stale
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum SurvivorReevaluation {
+    Always,
+    Never,
+    // in every generation that is a multiple of given number
+    Every(usize),
+}
+
+// connections are identified by their endpoints, so identical new connections always align,
+// the scope determines how long split connections keep mapping to the same node ids
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum InnovationScope {
+    // innovation table persists for the whole run
+    Run,
+    // innovation table is reset every generation, as in classic NEAT
+    Generation,
+}
+
+impl Default for InnovationScope {
+    fn default() -> Self {
+        InnovationScope::Run
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum EvaluationErrorPolicy {
+    // stop the run by yielding Evaluation::Error
+    Abort,
+    // evaluate again up to given times, then abort
+    Retry(usize),
+    // treat as lowest fitness of the generation
+    AssignMinimum,
+}
+
+impl Default for EvaluationErrorPolicy {
+    fn default() -> Self {
+        EvaluationErrorPolicy::Abort
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum OffspringAllocation {
+    // proportional to shifted and normalized score
+    Score,
+    // proportional to reversed rank, i.e. best gets n, worst gets 1
+    LinearRank,
+    // proportional to base^rank, base in (0, 1)
+    ExponentialRank(f64),
+}
+
+impl Default for OffspringAllocation {
+    fn default() -> Self {
+        OffspringAllocation::Score
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum SolutionSelection {
+    // first reported solution
+    First,
+    // solution with fewest genes
+    Simplest,
+    // solution with fewest genes, among those the one with most novel behavior
+    SimplestThenMostNovel,
+}
+
+impl Default for SolutionSelection {
+    fn default() -> Self {
+        SolutionSelection::First
+    }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+// a solution is only accepted if enough of its repeated evaluations report a solution as well,
+// otherwise evolution continues
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct SolutionVerification {
+    pub evaluations: usize,
+    // fraction of evaluations that need to succeed
+    pub success_rate: f64,
+}
+
+impl SolutionVerification {
+    pub fn passes(&self, successes: usize) -> bool {
+        successes as f64 >= self.success_rate * self.evaluations as f64
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Activations {
     pub output_nodes: Activation,
     pub hidden_nodes: Vec<Activation>,
+    // activation of output i, outputs beyond the list use output_nodes
+    #[serde(default)]
+    pub per_output: Vec<Activation>,
+    // aggregations hidden nodes choose from, summation only if empty
+    #[serde(default)]
+    pub aggregations: Vec<Aggregation>,
+}
+
+impl Activations {
+    pub fn output(&self, index: usize) -> Activation {
+        self.per_output
+            .get(index)
+            .cloned()
+            .unwrap_or(self.output_nodes)
+    }
+
+    // custom activations known to given registry take its functions, the first unknown one is reported
+    pub fn resolve(&mut self, activations: &ActivationRegistry) -> Result<(), NeatError> {
+        let mut unknown = None;
+        for activation in std::iter::once(&mut self.output_nodes)
+            .chain(self.hidden_nodes.iter_mut())
+            .chain(self.per_output.iter_mut())
+        {
+            match activations.resolve(*activation) {
+                Ok(resolved) => *activation = resolved,
+                Err(error) => unknown = unknown.or(Some(error)),
+            }
+        }
+        unknown.map_or(Ok(()), Err)
+    }
 }
 
 impl Default for Activations {
@@ -41,17 +714,168 @@ impl Default for Activations {
                 Activation::Absolute,
                 Activation::Relu,
             ],
+            per_output: Vec::new(),
+            aggregations: Vec::new(),
         }
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Mutation {
     pub new_node_chance: f64,
     pub new_connection_chance: f64,
     pub connection_is_recurrent_chance: f64,
     pub change_activation_function_chance: f64,
+    // picks another of activations.aggregations for a hidden node
+    #[serde(default)]
+    pub change_aggregation_chance: f64,
     pub weight_perturbation_std_dev: f64,
+    // shrinks weight_perturbation_std_dev over generations, see Mutation::weight_std_dev
+    #[serde(default)]
+    pub weight_decay: WeightDecay,
+    // weights of new connections, see WeightInitialization
+    #[serde(default)]
+    pub weight_initialization: WeightInitialization,
+    #[serde(default)]
+    pub bias_perturbation_std_dev: f64,
+    #[serde(default)]
+    pub response_perturbation_std_dev: f64,
+    // coefficients of parameterized activations, see Activation::is_parameterized
+    #[serde(default)]
+    pub coefficient_perturbation_std_dev: f64,
+    // time constants of leaky integrating nodes, stay at 1.0 (no integration) unless perturbed
+    #[serde(default)]
+    pub time_constant_perturbation_std_dev: f64,
+    // chance of a dedicated mutation adding a recurrent loop from a node to itself,
+    // the only way self connections are added
+    #[serde(default)]
+    pub self_connection_chance: f64,
+    // chance of adding another connection between the nodes of an existing feed forward connection
+    #[serde(default)]
+    pub parallel_connection_chance: f64,
+    // chance of disabling an enabled or enabling a disabled connection, see Genome::toggle_connection
+    #[serde(default)]
+    pub toggle_connection_chance: f64,
+    // genomes carry and evolve their own mutation rates
+    #[serde(default)]
+    pub self_adaptive: bool,
+    #[serde(default)]
+    pub self_adaptation_learning_rate: f64,
+    // structural mutations applied to one offspring at most, sampled in repeated passes over
+    // every operator that stop early once a pass applies none,
+    // without a budget every operator is gambled once
+    #[serde(default)]
+    pub max_structural_mutations_per_offspring: Option<usize>,
+    #[serde(default)]
+    pub split_selection: SplitSelection,
+    // crossover can combine connections of both parents into a feed forward cycle
+    #[serde(default)]
+    pub cycle_repair: CycleRepair,
+    // structural mutations that would exceed a cap are skipped, see MutationOperator::added_genes
+    #[serde(default)]
+    pub max_hidden_nodes: Option<usize>,
+    // counts feed forward, recurrent and parallel connections
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+    // mutation attempts per offspring identical to an earlier genome, 0 keeps duplicates
+    #[serde(default)]
+    pub duplicate_offspring_retries: usize,
+    // structural mutations applied after the built-in ones, see Neat::with_mutation_operator
+    #[serde(skip)]
+    pub custom_operators: CustomOperators,
+}
+
+// schedule of weight perturbation, starting at weight_perturbation_std_dev in generation zero
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum WeightDecay {
+    None,
+    // interpolates to final_std_dev within given generations, then stays there
+    Linear {
+        final_std_dev: f64,
+        generations: usize,
+    },
+    // multiplies by rate every generation, never below minimum
+    Exponential {
+        rate: f64,
+        minimum: f64,
+    },
+}
+
+impl Default for WeightDecay {
+    fn default() -> Self {
+        WeightDecay::None
+    }
+}
+
+// distribution of weights of new connections, by init and add_connection
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum WeightInitialization {
+    // same distribution as weight perturbations
+    Perturbation,
+    // uniform within lower and upper bound
+    Uniform(f64, f64),
+    // zero centered with given standard deviation
+    Gaussian(f64),
+    // zero centered with standard deviation sqrt(1 / fan-in) of the target node
+    Xavier,
+}
+
+impl Default for WeightInitialization {
+    fn default() -> Self {
+        WeightInitialization::Perturbation
+    }
+}
+
+// what happens to the connection closing a feed forward cycle in an offspring
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum CycleRepair {
+    Drop,
+    // dropped as well if the recurrent connection exists already
+    MakeRecurrent,
+}
+
+impl Default for CycleRepair {
+    fn default() -> Self {
+        CycleRepair::MakeRecurrent
+    }
+}
+
+// how the connection split by a new node is chosen
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum SplitSelection {
+    Uniform,
+    // proportional to absolute weight, i.e. favors influential connections
+    WeightMagnitude,
+}
+
+impl Default for SplitSelection {
+    fn default() -> Self {
+        SplitSelection::Uniform
+    }
+}
+
+impl Mutation {
+    // weight perturbation in given generation, according to weight_decay
+    pub fn weight_std_dev(&self, generation: usize) -> f64 {
+        let start = self.weight_perturbation_std_dev;
+        match self.weight_decay {
+            WeightDecay::None => start,
+            WeightDecay::Linear {
+                final_std_dev,
+                generations,
+            } => {
+                let progress = if generations == 0 {
+                    1.0
+                } else {
+                    (generation as f64 / generations as f64).min(1.0)
+                };
+                start + (final_std_dev - start) * progress
+            }
+            WeightDecay::Exponential { rate, minimum } => {
+                (start * rate.powi(generation.min(i32::MAX as usize) as i32)).max(minimum)
+            }
+        }
+    }
 }
 
 impl Default for Mutation {
@@ -61,12 +885,32 @@ impl Default for Mutation {
             new_connection_chance: 0.1,
             connection_is_recurrent_chance: 0.3,
             change_activation_function_chance: 0.05,
+            change_aggregation_chance: 0.0,
             weight_perturbation_std_dev: 1.0,
+            weight_decay: WeightDecay::default(),
+            weight_initialization: WeightInitialization::default(),
+            bias_perturbation_std_dev: 0.0,
+            response_perturbation_std_dev: 0.0,
+            coefficient_perturbation_std_dev: 0.0,
+            time_constant_perturbation_std_dev: 0.0,
+            self_connection_chance: 0.0,
+            parallel_connection_chance: 0.0,
+            toggle_connection_chance: 0.0,
+            self_adaptive: false,
+            self_adaptation_learning_rate: 0.2,
+            max_structural_mutations_per_offspring: None,
+            split_selection: SplitSelection::default(),
+            cycle_repair: CycleRepair::default(),
+            max_hidden_nodes: None,
+            max_connections: None,
+            duplicate_offspring_retries: 0,
+            custom_operators: CustomOperators::default(),
         }
     }
 }
 
 impl Parameters {
+    #[cfg(feature = "config-file")]
     pub fn new(path: &str) -> Result<Self, ConfigError> {
         let mut s = Config::new();
 
@@ -74,13 +918,112 @@ impl Parameters {
         s.merge(File::with_name(path))?;
 
         // You can deserialize (and thus freeze) the entire configuration as
-        s.try_into()
+        let mut parameters: Self = s.try_into()?;
+
+        parameters.resolve_seed();
+
+        Ok(parameters)
+    }
+
+    pub fn from_json_str(json: &str) -> Result<Self, NeatError> {
+        let mut parameters: Self = serde_json::from_str(json)?;
+        parameters.resolve_seed();
+        Ok(parameters)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<Self, NeatError> {
+        let mut parameters: Self = toml::from_str(toml)?;
+        parameters.resolve_seed();
+        Ok(parameters)
+    }
+
+    pub fn builder() -> ParametersBuilder {
+        ParametersBuilder::default()
+    }
+
+    // replaces missing (zero) seed with a random one, so unseeded runs can be reproduced
+    pub fn resolve_seed(&mut self) {
+        if self.setup.seed == 0 {
+            self.setup.seed = loop {
+                let seed = rand::random::<u64>();
+                if seed != 0 {
+                    break seed;
+                }
+            };
+            println!("no seed configured, using generated seed {}", self.setup.seed);
+        }
+    }
+}
+
+// assembles parameters in code, unset values keep their defaults
+#[derive(Debug, Clone, Default)]
+pub struct ParametersBuilder {
+    parameters: Parameters,
+}
+
+impl ParametersBuilder {
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.parameters.setup.seed = seed;
+        self
+    }
+
+    pub fn population_size(mut self, population_size: usize) -> Self {
+        self.parameters.setup.population_size = population_size;
+        self
+    }
+
+    pub fn survival_rate(mut self, survival_rate: f64) -> Self {
+        self.parameters.setup.survival_rate = survival_rate;
+        self
+    }
+
+    pub fn dimensions(mut self, input_dimension: usize, output_dimension: usize) -> Self {
+        self.parameters.setup.input_dimension = input_dimension;
+        self.parameters.setup.output_dimension = output_dimension;
+        self
+    }
+
+    pub fn novelty_nearest_neighbors(mut self, novelty_nearest_neighbors: usize) -> Self {
+        self.parameters.setup.novelty_nearest_neighbors = novelty_nearest_neighbors;
+        self
+    }
+
+    // access to any other setup value
+    pub fn setup(mut self, configure: impl FnOnce(&mut Setup)) -> Self {
+        configure(&mut self.parameters.setup);
+        self
+    }
+
+    pub fn activations(mut self, activations: Activations) -> Self {
+        self.parameters.activations = activations;
+        self
+    }
+
+    pub fn mutation(mut self, configure: impl FnOnce(&mut Mutation)) -> Self {
+        configure(&mut self.parameters.mutation);
+        self
+    }
+
+    pub fn build(self) -> Parameters {
+        let mut parameters = self.parameters;
+        parameters.resolve_seed();
+        parameters
+    }
+}
+
+impl From<ParametersBuilder> for Parameters {
+    fn from(builder: ParametersBuilder) -> Self {
+        builder.build()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Parameters;
+    use super::{
+        AgeAdjustment, ArchiveAddition, ArchiveDecay, FitnessShaping, NeighborsScaling, Parameters,
+        SolutionVerification, SurvivalScheme, SurvivorReevaluation, TrialFitness, WeightDecay,
+    };
 
     #[test]
     fn read_parameters() {
@@ -89,4 +1032,163 @@ mod tests {
 
         assert_eq!(parameters.reproduction.stale_after, 15) */
     }
+
+    #[test]
+    fn build_parameters() {
+        let parameters: Parameters = Parameters::builder()
+            .seed(42)
+            .population_size(50)
+            .dimensions(2, 1)
+            .mutation(|mutation| mutation.new_node_chance = 0.5)
+            .into();
+
+        assert_eq!(parameters.setup.seed, 42);
+        assert_eq!(parameters.setup.population_size, 50);
+        assert_eq!(parameters.setup.input_dimension, 2);
+        assert_eq!(parameters.mutation.new_node_chance, 0.5);
+    }
+
+    #[test]
+    fn parameters_from_json() {
+        let json = serde_json::to_string(&Parameters::builder().seed(7).build()).unwrap();
+
+        let parameters = Parameters::from_json_str(&json).unwrap();
+
+        assert_eq!(parameters.setup.seed, 7);
+    }
+
+    #[test]
+    fn scale_nearest_neighbors() {
+        let mut parameters = Parameters::default();
+        parameters.setup.novelty_nearest_neighbors = 3;
+
+        assert_eq!(parameters.setup.nearest_neighbors(100), 3);
+
+        parameters.setup.novelty_neighbors_scaling = NeighborsScaling::Fraction(0.1);
+        assert_eq!(parameters.setup.nearest_neighbors(10), 3);
+        assert_eq!(parameters.setup.nearest_neighbors(100), 10);
+        assert_eq!(parameters.setup.nearest_neighbors(2), 1);
+
+        parameters.setup.novelty_neighbors_scaling = NeighborsScaling::SquareRoot;
+        assert_eq!(parameters.setup.nearest_neighbors(400), 20);
+    }
+
+    #[test]
+    fn decay_archive() {
+        assert_eq!(ArchiveDecay::None.weight(100), 1.0);
+        assert!(!ArchiveDecay::None.evicts(100));
+
+        let exponential = ArchiveDecay::Exponential {
+            factor: 0.5,
+            minimum_weight: 0.2,
+        };
+        assert_eq!(exponential.weight(2), 0.25);
+        assert!(!exponential.evicts(2));
+        assert!(exponential.evicts(3));
+
+        let linear = ArchiveDecay::Linear(4);
+        assert_eq!(linear.weight(1), 0.75);
+        assert!(linear.evicts(4));
+    }
+
+    #[test]
+    fn adjust_archive_threshold() {
+        assert_eq!(ArchiveAddition::MostNovel.adjust(2.0, 10), 2.0);
+
+        let threshold = ArchiveAddition::Threshold {
+            initial: 1.0,
+            interval: 5,
+            min_additions: 1,
+            max_additions: 4,
+            raise_factor: 1.2,
+            lower_factor: 0.5,
+        };
+        assert!((threshold.adjust(2.0, 5) - 2.4).abs() < 1e-9);
+        assert_eq!(threshold.adjust(2.0, 0), 1.0);
+        assert_eq!(threshold.adjust(2.0, 4), 2.0);
+    }
+
+    #[test]
+    fn verify_solution() {
+        let verification = SolutionVerification {
+            evaluations: 5,
+            success_rate: 0.8,
+        };
+        assert!(verification.passes(4));
+        assert!(!verification.passes(3));
+    }
+
+    #[test]
+    fn reevaluate_survivors() {
+        let mut parameters = Parameters::default();
+        assert!(parameters.setup.reevaluates_survivors(3));
+
+        parameters.setup.survival_scheme = SurvivalScheme::MuPlusLambda;
+        assert!(!parameters.setup.reevaluates_survivors(3));
+
+        parameters.setup.reevaluate_survivors = Some(SurvivorReevaluation::Every(5));
+        assert!(!parameters.setup.reevaluates_survivors(3));
+        assert!(parameters.setup.reevaluates_survivors(10));
+    }
+
+    #[test]
+    fn age_adjustment_factor() {
+        let adjustment = AgeAdjustment {
+            young_age_threshold: 2,
+            young_age_boost: 1.5,
+            old_age_threshold: 10,
+            old_age_penalty: 0.5,
+        };
+
+        assert_eq!(adjustment.factor(1), 1.5);
+        assert_eq!(adjustment.factor(2), 1.0);
+        assert_eq!(adjustment.factor(10), 1.0);
+        assert_eq!(adjustment.factor(11), 0.5);
+        assert_eq!(AgeAdjustment::default().factor(0), 1.0);
+    }
+
+    #[test]
+    fn decay_weight_std_dev() {
+        let mut parameters = Parameters::default();
+        parameters.mutation.weight_perturbation_std_dev = 1.0;
+
+        assert_eq!(parameters.mutation.weight_std_dev(100), 1.0);
+
+        parameters.mutation.weight_decay = WeightDecay::Linear {
+            final_std_dev: 0.5,
+            generations: 10,
+        };
+        assert_eq!(parameters.mutation.weight_std_dev(0), 1.0);
+        assert_eq!(parameters.mutation.weight_std_dev(5), 0.75);
+        assert_eq!(parameters.mutation.weight_std_dev(20), 0.5);
+
+        parameters.mutation.weight_decay = WeightDecay::Exponential {
+            rate: 0.5,
+            minimum: 0.1,
+        };
+        assert_eq!(parameters.mutation.weight_std_dev(2), 0.25);
+        assert_eq!(parameters.mutation.weight_std_dev(10), 0.1);
+    }
+
+    #[test]
+    fn shape_fitness_by_rank() {
+        let raw = [10.0, 1000.0, 0.0, 10.0];
+
+        assert_eq!(FitnessShaping::Proportional.shape(&raw), None);
+        assert_eq!(
+            FitnessShaping::LinearRank.shape(&raw),
+            Some(vec![2.0 / 3.0, 1.0, 0.0, 2.0 / 3.0])
+        );
+        assert_eq!(
+            FitnessShaping::ExponentialRank(0.5).shape(&raw),
+            Some(vec![0.5, 1.0, 0.125, 0.5])
+        );
+        assert_eq!(FitnessShaping::LinearRank.shape(&[3.0]), Some(vec![1.0]));
+    }
+
+    #[test]
+    fn median_ignores_nan_trials() {
+        assert_eq!(TrialFitness::Median.aggregate(&[3.0, f64::NAN, 1.0]), 2.0);
+        assert_eq!(TrialFitness::Median.aggregate(&[f64::NAN]), 0.0);
+    }
 }
@@ -0,0 +1,536 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{
+    error::NeatError,
+    genes::{Id, IdGenerator},
+    individual::{genealogy::Genealogy, scores::ScoreValue, Individual},
+    parameters::Parameters,
+    runtime::{checkpoint::Checkpoint, progress::Progress},
+    utility::statistics::PopulationStatistics,
+};
+
+use super::Population;
+
+// semi-isolated populations sharing one innovation table, see parameters.setup.islands
+pub struct Islands {
+    islands: Vec<Population>,
+    // survivors of every island followed by offspring of every island, only kept for several islands
+    individuals: Vec<Individual>,
+    // lent to each island while it reproduces
+    id_gen: IdGenerator,
+    genealogy: Genealogy,
+    next_individual_id: usize,
+    generation: usize,
+    statistics: Vec<PopulationStatistics>,
+}
+
+fn island_count(parameters: &Parameters) -> usize {
+    parameters
+        .setup
+        .islands
+        .map_or(1, |islands| islands.count.max(1))
+}
+
+impl Islands {
+    pub fn new(parameters: &Parameters) -> Self {
+        Self::split(Population::new(&Self::combined(parameters)), parameters)
+    }
+
+    pub fn from_seed(seed: &Individual, parameters: &Parameters) -> Self {
+        Self::split(
+            Population::from_seed(seed, &Self::combined(parameters)),
+            parameters,
+        )
+    }
+
+    pub fn from_checkpoint(checkpoint: Checkpoint, parameters: &Parameters) -> Self {
+        let Checkpoint {
+            num_generation,
+            individuals,
+            archive,
+            id_gen,
+            genealogy,
+            islands,
+            archive_thresholds,
+        } = checkpoint;
+
+        let layout = if islands.is_empty() {
+            vec![(individuals.len(), archive.len())]
+        } else {
+            islands
+        };
+
+        let mut individuals = individuals.into_iter();
+        let mut archive = archive.into_iter();
+        // the first island carries the shared bookkeeping
+        let mut id_gen = Some(id_gen);
+        let mut genealogy = Some(genealogy);
+        let mut archive_thresholds = archive_thresholds.into_iter();
+
+        let populations = layout
+            .into_iter()
+            .enumerate()
+            .map(|(index, (size, archived))| {
+                let checkpoint = Checkpoint {
+                    num_generation,
+                    individuals: individuals.by_ref().take(size).collect(),
+                    archive: archive.by_ref().take(archived).collect(),
+                    id_gen: id_gen.take().unwrap_or_default(),
+                    genealogy: genealogy.take().unwrap_or_default(),
+                    islands: Vec::new(),
+                    archive_thresholds: archive_thresholds.next().into_iter().collect(),
+                };
+                Population::from_checkpoint(checkpoint, &Self::seeded(parameters, index))
+            })
+            .collect();
+
+        Self::from_populations(populations, num_generation)
+    }
+
+    // initial individuals are created as one population, so they share their initial ids
+    fn split(population: Population, parameters: &Parameters) -> Self {
+        let count = island_count(parameters);
+        if count == 1 {
+            return Self::from_populations(vec![population], 0);
+        }
+
+        let mut checkpoint = population.checkpoint(0);
+        checkpoint.islands = vec![(parameters.setup.population_size, 0); count];
+        Self::from_checkpoint(checkpoint, parameters)
+    }
+
+    fn combined(parameters: &Parameters) -> Parameters {
+        let mut combined = parameters.clone();
+        combined.setup.population_size *= island_count(parameters);
+        combined
+    }
+
+    // every island draws from its own random source
+    fn seeded(parameters: &Parameters, index: usize) -> Parameters {
+        let mut seeded = parameters.clone();
+        seeded.setup.seed = seeded.setup.seed.wrapping_add(index as u64);
+        seeded
+    }
+
+    fn from_populations(populations: Vec<Population>, generation: usize) -> Self {
+        let mut islands = Islands {
+            islands: populations,
+            individuals: Vec::new(),
+            id_gen: IdGenerator::default(),
+            genealogy: Genealogy::default(),
+            next_individual_id: 0,
+            generation,
+            statistics: Vec::new(),
+        };
+
+        for (index, island) in islands.islands.iter_mut().enumerate() {
+            let mut id_gen = IdGenerator::default();
+            let mut genealogy = Genealogy::default();
+            let mut next_individual_id = 0;
+            island.exchange_bookkeeping(&mut id_gen, &mut genealogy, &mut next_individual_id);
+
+            if index == 0 {
+                islands.id_gen = id_gen;
+                islands.genealogy = genealogy;
+            }
+            islands.next_individual_id = islands.next_individual_id.max(next_individual_id);
+        }
+
+        islands.flatten();
+        islands
+    }
+
+    pub fn checkpoint(&self, num_generation: usize) -> Checkpoint {
+        let mut checkpoint = Checkpoint {
+            num_generation,
+            individuals: Vec::new(),
+            archive: Vec::new(),
+            id_gen: self.id_gen.clone(),
+            genealogy: self.genealogy.clone(),
+            islands: Vec::new(),
+            archive_thresholds: Vec::new(),
+        };
+
+        for island in &self.islands {
+            let Checkpoint {
+                individuals,
+                archive,
+                archive_thresholds,
+                ..
+            } = island.checkpoint(num_generation);
+            if self.islands.len() > 1 {
+                checkpoint.islands.push((individuals.len(), archive.len()));
+            }
+            checkpoint.individuals.extend(individuals);
+            checkpoint.archive.extend(archive);
+            checkpoint.archive_thresholds.extend(archive_thresholds);
+        }
+
+        checkpoint
+    }
+
+    pub fn individuals(&self) -> &[Individual] {
+        match self.islands.as_slice() {
+            [island] => island.individuals(),
+            _ => &self.individuals,
+        }
+    }
+
+    pub fn genealogy(&self) -> &Genealogy {
+        &self.genealogy
+    }
+
+    pub fn id_gen(&self) -> &IdGenerator {
+        &self.id_gen
+    }
+
+    pub fn id_gen_mut(&mut self) -> &mut IdGenerator {
+        &mut self.id_gen
+    }
+
+    // leading individuals that were evaluated before
+    pub fn survivors(&self) -> usize {
+        self.islands.iter().map(Population::survivors).sum()
+    }
+
+    // archives of all islands, in order
+    pub fn export_archive(&self) -> Vec<Individual> {
+        self.islands
+            .iter()
+            .flat_map(Population::export_archive)
+            .collect()
+    }
+
+    // every island starts out knowing all imported behaviors
+    pub fn import_archive(&mut self, archive: Vec<Individual>) {
+        for island in &mut self.islands {
+            island.import_archive(archive.clone());
+        }
+    }
+
+    // appends individuals from outside the run to the offspring of the islands in turn, they keep their genomes
+    // but get fresh individual ids, which are returned, hidden nodes and parallel connections with ids never
+    // handed out by the id generator are renamed, so different immigrants do not share them by chance
+    pub fn inject(
+        &mut self,
+        immigrants: Vec<Individual>,
+        parameters: &Parameters,
+    ) -> Result<Vec<usize>, NeatError> {
+        let io = |individual: &Individual| -> (HashSet<Id>, HashSet<Id>) {
+            (
+                individual.inputs.iter().map(|node| node.id()).collect(),
+                individual.outputs.iter().map(|node| node.id()).collect(),
+            )
+        };
+        let expected = self.individuals().first().map(io);
+
+        for immigrant in &immigrants {
+            let defects = immigrant.defects();
+            if !defects.is_empty() {
+                return Err(NeatError::InvalidGenome(defects));
+            }
+            if expected
+                .as_ref()
+                .map_or(false, |expected| *expected != io(immigrant))
+            {
+                return Err(NeatError::Dimension(
+                    "inputs and outputs of immigrant differ from those of the population"
+                        .to_owned(),
+                ));
+            }
+        }
+
+        let known = self.id_gen.peek();
+        let count = self.islands.len();
+        let mut ids = Vec::new();
+
+        for (index, mut immigrant) in immigrants.into_iter().enumerate() {
+            let unknown: BTreeSet<Id> = immigrant
+                .hidden
+                .iter()
+                .map(|node| node.id())
+                .chain(immigrant.parallel.iter().map(|parallel| parallel.0))
+                .filter(|&id| id >= known)
+                .collect();
+            if !unknown.is_empty() {
+                let renamed: HashMap<Id, Id> = unknown
+                    .into_iter()
+                    .map(|id| (id, self.id_gen.next_id()))
+                    .collect();
+                immigrant.rename_ids(&renamed);
+            }
+
+            let id = self.next_individual_id;
+            self.next_individual_id += 1;
+            if parameters.setup.track_lineage {
+                self.genealogy.record(id, Vec::new(), self.generation);
+            }
+            ids.push(id);
+
+            self.islands[index % count].receive(vec![Individual {
+                id,
+                genome: immigrant.genome,
+                ..Default::default()
+            }]);
+        }
+
+        self.flatten();
+        Ok(ids)
+    }
+
+    // one novelty archive per island
+    pub fn archives_mut(&mut self) -> impl Iterator<Item = &mut Vec<Individual>> {
+        self.islands.iter_mut().map(Population::archive_mut)
+    }
+
+    // statistics of every island of the last generation, empty for a single population
+    pub fn statistics(&self) -> &[PopulationStatistics] {
+        &self.statistics
+    }
+
+    // position of every individual of every island, in order, within the flattened individuals
+    fn order(&self) -> Vec<usize> {
+        let survivors = self.survivors();
+        let mut survivor_offset = 0;
+        let mut offspring_offset = survivors;
+        let mut order = Vec::new();
+
+        for island in &self.islands {
+            let offspring = island.individuals().len() - island.survivors();
+            order.extend(survivor_offset..survivor_offset + island.survivors());
+            order.extend(offspring_offset..offspring_offset + offspring);
+            survivor_offset += island.survivors();
+            offspring_offset += offspring;
+        }
+
+        order
+    }
+
+    fn flatten(&mut self) {
+        if self.islands.len() == 1 {
+            return;
+        }
+
+        let (survivors, offspring): (Vec<&[Individual]>, Vec<&[Individual]>) = self
+            .islands
+            .iter()
+            .map(|island| island.individuals().split_at(island.survivors()))
+            .unzip();
+
+        self.individuals = survivors
+            .into_iter()
+            .chain(offspring)
+            .flatten()
+            .cloned()
+            .collect();
+    }
+
+    // progress is expected in order of individuals and stays in that order
+    pub fn next_generation(
+        &mut self,
+        parameters: &Parameters,
+        progress: &mut Vec<Progress>,
+    ) -> Result<PopulationStatistics, NeatError> {
+        let order = self.order();
+        let mut slots: Vec<Option<Progress>> = progress.drain(..).map(Some).collect();
+        let arranged: Vec<Progress> = order
+            .iter()
+            .map(|&index| {
+                slots
+                    .get_mut(index)
+                    .and_then(Option::take)
+                    .ok_or(NeatError::Internal("missing progress of individual"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut statistics = Vec::new();
+        let mut offset = 0;
+
+        for island in &mut self.islands {
+            let size = island.individuals().len();
+            island.exchange_bookkeeping(
+                &mut self.id_gen,
+                &mut self.genealogy,
+                &mut self.next_individual_id,
+            );
+            statistics.push(island.next_generation(parameters, &arranged[offset..offset + size])?);
+            island.exchange_bookkeeping(
+                &mut self.id_gen,
+                &mut self.genealogy,
+                &mut self.next_individual_id,
+            );
+            offset += size;
+        }
+
+        for (index, progress) in order.into_iter().zip(arranged) {
+            slots[index] = Some(progress);
+        }
+        progress.extend(slots.into_iter().flatten());
+
+        self.generation += 1;
+        if let Some(model) = parameters.setup.islands {
+            if model.migration_interval > 0 && self.generation % model.migration_interval == 0 {
+                self.migrate(model.migration_size);
+            }
+        }
+        self.flatten();
+
+        if statistics.len() == 1 {
+            return Ok(statistics.remove(0));
+        }
+
+        // island holding the best individual represents the whole run
+        let best = statistics
+            .iter()
+            .max_by(|statistics_0, statistics_1| {
+                raw_fitness(statistics_0).total_cmp(&raw_fitness(statistics_1))
+            })
+            .cloned()
+            .unwrap_or_default();
+        self.statistics = statistics;
+        Ok(best)
+    }
+
+    // copies the best survivors of every island to the next one, in a ring
+    fn migrate(&mut self, size: usize) {
+        if self.islands.len() < 2 {
+            return;
+        }
+
+        let emigrants: Vec<Vec<Individual>> = self
+            .islands
+            .iter()
+            .map(|island| island.emigrants(size))
+            .collect();
+
+        let count = self.islands.len();
+        for (index, emigrants) in emigrants.into_iter().enumerate() {
+            self.islands[(index + 1) % count].immigrate(emigrants);
+        }
+    }
+}
+
+// NaN ranks last
+fn raw_fitness(statistics: &PopulationStatistics) -> f64 {
+    statistics
+        .top_performer
+        .fitness
+        .as_ref()
+        .map(|fitness| fitness.raw.value())
+        .filter(|fitness| !fitness.is_nan())
+        .unwrap_or(f64::NEG_INFINITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Islands;
+    use crate::{
+        genes::{nodes::Node, Activation, Id},
+        individual::{
+            genome::{Genome, GenomeBuilder},
+            scores::ScoreValue,
+            Individual,
+        },
+        parameters::{IslandModel, Parameters},
+        runtime::progress::Progress,
+    };
+
+    fn parameters() -> Parameters {
+        let mut parameters = Parameters::default();
+        parameters.setup.population_size = 10;
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+        parameters.setup.survival_rate = 0.5;
+        parameters.setup.islands = Some(IslandModel {
+            count: 3,
+            migration_interval: 1,
+            migration_size: 2,
+        });
+        parameters
+    }
+
+    #[test]
+    fn evolve_islands_with_migration() {
+        let parameters = parameters();
+        let mut islands = Islands::new(&parameters);
+
+        assert_eq!(islands.individuals().len(), 30);
+
+        let mut progress: Vec<Progress> = (0..30)
+            .map(|index| Progress::fitness(index as f64))
+            .collect();
+        islands.next_generation(&parameters, &mut progress).unwrap();
+
+        // progress stays in order of evaluated individuals
+        assert_eq!(
+            progress[29].raw_fitness(&parameters.setup).unwrap().value(),
+            29.0
+        );
+        assert_eq!(islands.statistics().len(), 3);
+        assert_eq!(islands.individuals().len(), 30);
+
+        // no individual id is handed out twice, apart from migrated copies
+        let mut ids: Vec<usize> = islands.individuals().iter().map(|i| i.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 30 - 3 * 2);
+
+        let checkpoint = islands.checkpoint(1);
+        assert_eq!(checkpoint.islands.len(), 3);
+        let restored = Islands::from_checkpoint(checkpoint, &parameters);
+        assert_eq!(restored.individuals().len(), 30);
+    }
+
+    #[test]
+    fn import_archive_into_every_island() {
+        let parameters = parameters();
+        let mut islands = Islands::new(&parameters);
+
+        let archive = islands.individuals()[..2].to_vec();
+        islands.import_archive(archive);
+
+        assert_eq!(islands.export_archive().len(), 3 * 2);
+        assert_eq!(islands.checkpoint(0).islands[1], (10, 2));
+    }
+
+    #[test]
+    fn inject_immigrants() {
+        let parameters = parameters();
+        let mut islands = Islands::new(&parameters);
+        let known = islands.id_gen().peek();
+
+        let genome = GenomeBuilder::from_genome(islands.individuals()[0].genome.clone())
+            .hidden(Node::new(Id(1000), Activation::Tanh))
+            .build()
+            .unwrap();
+        let immigrant = Individual {
+            genome,
+            ..Default::default()
+        };
+
+        let ids = islands
+            .inject(vec![immigrant.clone(), immigrant], &parameters)
+            .unwrap();
+
+        assert_eq!(islands.individuals().len(), 32);
+        assert_eq!(islands.survivors(), 0);
+        assert!(ids.iter().all(|id| *id >= 30));
+        // unknown hidden ids are renamed apart
+        let hidden: Vec<Id> = islands
+            .individuals()
+            .iter()
+            .filter(|individual| ids.contains(&individual.id))
+            .flat_map(|individual| individual.hidden.iter().map(|node| node.id()))
+            .filter(|&id| id >= known)
+            .collect();
+        assert_eq!(hidden.len(), 2);
+        assert_ne!(hidden[0], hidden[1]);
+        assert!(hidden.iter().all(|id| *id < Id(1000)));
+
+        let stranger = Individual {
+            genome: Genome::builder().input(Id(500)).build().unwrap(),
+            ..Default::default()
+        };
+        assert!(islands.inject(vec![stranger], &parameters).is_err());
+    }
+}
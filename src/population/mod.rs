@@ -0,0 +1,1413 @@
+use std::{cmp::Ordering, collections::HashSet, time::Duration};
+
+use rand::{prelude::SliceRandom, Rng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    error::NeatError,
+    genes::IdGenerator,
+    individual::{
+        behavior::{Behavior, Behaviors},
+        genealogy::Genealogy,
+        genome::Genome,
+        scores::{self, Fitness, FitnessScore, NoveltyScore, Raw, ScoreValue},
+        Individual,
+    },
+    parameters::{
+        ArchiveAddition, ArchiveDecay, InnovationScope, OffspringAllocation, Parameters, Selection,
+        Snapshot, SurvivalSelection,
+    },
+    runtime::{
+        checkpoint::{ArchiveThreshold, Checkpoint},
+        progress::{Objective, Progress},
+    },
+    utility::{
+        rng::NeatRng,
+        statistics::{
+            ComplexityStatistics, CountStatistics, ObjectiveStatistics, PopulationStatistics,
+        },
+        time::Instant,
+    },
+};
+
+pub mod islands;
+
+pub struct Population {
+    individuals: Vec<Individual>,
+    archive: Vec<Individual>,
+    population_statistics: PopulationStatistics,
+    rng: NeatRng,
+    id_gen: IdGenerator,
+    // leading individuals that were evaluated before, the rest are offspring
+    survivors: usize,
+    genealogy: Genealogy,
+    next_individual_id: usize,
+    generation: usize,
+    // best raw fitness so far and generations since it improved
+    best_fitness: f64,
+    stagnation: usize,
+    archive_threshold: ArchiveThreshold,
+}
+
+impl Population {
+    pub fn new(parameters: &Parameters) -> Self {
+        // create id book-keeping
+        let mut id_gen = IdGenerator::default();
+
+        // generate genome with initial ids for structure
+        let initial_individual = Individual::initial(&mut id_gen, parameters);
+
+        // create randomn source
+        let mut rng = NeatRng::new(
+            parameters.setup.seed,
+            parameters.mutation.weight_perturbation_std_dev,
+        );
+
+        let mut individuals = Vec::new();
+
+        // generate initial, mutated individuals
+        for _ in 0..parameters.setup.population_size {
+            let mut other_genome = initial_individual.clone();
+            other_genome.init(&mut rng, parameters);
+            other_genome.mutate(&mut rng, &mut id_gen, parameters);
+            individuals.push(other_genome);
+        }
+
+        let mut population = Population {
+            individuals,
+            archive: Vec::new(),
+            rng,
+            id_gen,
+            population_statistics: PopulationStatistics::default(),
+            survivors: 0,
+            genealogy: Genealogy::default(),
+            next_individual_id: 0,
+            generation: 0,
+            best_fitness: f64::NEG_INFINITY,
+            stagnation: 0,
+            archive_threshold: ArchiveThreshold::default(),
+        };
+        population.register_initial(parameters);
+        population
+    }
+
+    // start from an existing network instead of a minimal topology
+    pub fn from_seed(seed: &Individual, parameters: &Parameters) -> Self {
+        // create id book-keeping that does not collide with ids of the seed
+        let mut id_gen = IdGenerator::default();
+        if let Some(max_id) = seed.max_id() {
+            id_gen.skip_past(max_id);
+        }
+
+        // create randomn source
+        let mut rng = NeatRng::new(
+            parameters.setup.seed,
+            parameters.mutation.weight_perturbation_std_dev,
+        );
+
+        let mut individuals = Vec::new();
+
+        // generate mutated copies of the seed, the first one stays untouched
+        for index in 0..parameters.setup.population_size {
+            let mut other_genome = Individual {
+                genome: seed.genome.clone(),
+                ..Default::default()
+            };
+            if index > 0 {
+                other_genome.mutate(&mut rng, &mut id_gen, parameters);
+            }
+            individuals.push(other_genome);
+        }
+
+        let mut population = Population {
+            individuals,
+            archive: Vec::new(),
+            rng,
+            id_gen,
+            population_statistics: PopulationStatistics::default(),
+            survivors: 0,
+            genealogy: Genealogy::default(),
+            next_individual_id: 0,
+            generation: 0,
+            best_fitness: f64::NEG_INFINITY,
+            stagnation: 0,
+            archive_threshold: ArchiveThreshold::default(),
+        };
+        population.register_initial(parameters);
+        population
+    }
+
+    pub fn from_checkpoint(checkpoint: Checkpoint, parameters: &Parameters) -> Self {
+        // continue after any id in use
+        let next_individual_id = checkpoint
+            .individuals
+            .iter()
+            .chain(&checkpoint.archive)
+            .map(|individual| individual.id)
+            .chain(checkpoint.genealogy.max_id())
+            .max()
+            .map_or(0, |id| id + 1);
+
+        Population {
+            individuals: checkpoint.individuals,
+            archive: checkpoint.archive,
+            // derive new seed to not repeat random choices of the checkpointed run
+            rng: NeatRng::new(
+                parameters
+                    .setup
+                    .seed
+                    .wrapping_add(checkpoint.num_generation as u64),
+                parameters
+                    .mutation
+                    .weight_std_dev(checkpoint.num_generation),
+            ),
+            id_gen: checkpoint.id_gen,
+            population_statistics: PopulationStatistics::default(),
+            survivors: 0,
+            genealogy: checkpoint.genealogy,
+            next_individual_id,
+            generation: checkpoint.num_generation,
+            best_fitness: f64::NEG_INFINITY,
+            stagnation: 0,
+            archive_threshold: checkpoint
+                .archive_thresholds
+                .into_iter()
+                .next()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn register_initial(&mut self, parameters: &Parameters) {
+        for index in 0..self.individuals.len() {
+            let id = self.register(Vec::new(), parameters);
+            self.individuals[index].id = id;
+        }
+    }
+
+    // hands out the next individual id and records its parents if lineage is tracked
+    fn register(&mut self, parents: Vec<usize>, parameters: &Parameters) -> usize {
+        let id = self.next_individual_id;
+        self.next_individual_id += 1;
+        if parameters.setup.track_lineage {
+            self.genealogy.record(id, parents, self.generation);
+        }
+        id
+    }
+
+    pub fn checkpoint(&self, num_generation: usize) -> Checkpoint {
+        Checkpoint {
+            num_generation,
+            individuals: self.individuals.clone(),
+            archive: self.archive.clone(),
+            id_gen: self.id_gen.clone(),
+            genealogy: self.genealogy.clone(),
+            islands: Vec::new(),
+            archive_thresholds: vec![self.archive_threshold.clone()],
+        }
+    }
+
+    pub fn individuals(&self) -> &Vec<Individual> {
+        &self.individuals
+    }
+
+    pub fn survivors(&self) -> usize {
+        self.survivors
+    }
+
+    pub fn archive_mut(&mut self) -> &mut Vec<Individual> {
+        &mut self.archive
+    }
+
+    pub fn export_archive(&self) -> Vec<Individual> {
+        self.archive.clone()
+    }
+
+    // entries of a previous run, their behaviors count as explored right away
+    pub fn import_archive(&mut self, archive: Vec<Individual>) {
+        self.archive.extend(archive);
+    }
+
+    // islands lend each other ids and genealogy, so no id is handed out twice
+    pub(crate) fn exchange_bookkeeping(
+        &mut self,
+        id_gen: &mut IdGenerator,
+        genealogy: &mut Genealogy,
+        next_individual_id: &mut usize,
+    ) {
+        std::mem::swap(&mut self.id_gen, id_gen);
+        std::mem::swap(&mut self.genealogy, genealogy);
+        std::mem::swap(&mut self.next_individual_id, next_individual_id);
+    }
+
+    // copies of the best survivors
+    pub(crate) fn emigrants(&self, count: usize) -> Vec<Individual> {
+        self.individuals[..count.min(self.survivors)].to_vec()
+    }
+
+    // individuals from outside the run, evaluated along with the offspring, see Islands::inject
+    pub(crate) fn receive(&mut self, individuals: Vec<Individual>) {
+        self.individuals.extend(individuals);
+    }
+
+    // immigrants replace the last offspring and get evaluated like them
+    pub(crate) fn immigrate(&mut self, immigrants: Vec<Individual>) {
+        let count = immigrants
+            .len()
+            .min(self.individuals.len() - self.survivors);
+        self.individuals.truncate(self.individuals.len() - count);
+        self.individuals.extend(immigrants.into_iter().take(count));
+    }
+
+    fn generate_offspring(&mut self, parameters: &Parameters) -> Result<(), NeatError> {
+        let now = Instant::now();
+
+        // identical splits within this generation still share ids
+        if parameters.setup.innovation_scope == InnovationScope::Generation {
+            self.id_gen.clear_cache();
+        }
+
+        // individuals failing the minimal criterion do not reproduce
+        let viable: Vec<usize> = self
+            .individuals
+            .iter()
+            .enumerate()
+            .filter(|(_, individual)| individual.is_viable())
+            .map(|(index, _)| index)
+            .collect();
+
+        let mut scores = self.allocation_weights(parameters);
+
+        // without any viable individual everyone reproduces as usual
+        let partners: Vec<usize> = if viable.is_empty() {
+            (0..self.individuals.len()).collect()
+        } else {
+            for (score, individual) in scores.iter_mut().zip(&self.individuals) {
+                if !individual.is_viable() {
+                    *score = 0.0;
+                }
+            }
+            viable
+        };
+
+        let mut total_score: f64 = scores.iter().sum();
+
+        // i.e. every weight zeroed, fall back to uniform allocation among viable individuals
+        if total_score <= 0.0 {
+            let any_viable = self.individuals.iter().any(Individual::is_viable);
+            for (score, individual) in scores.iter_mut().zip(&self.individuals) {
+                *score = if !any_viable || individual.is_viable() {
+                    1.0
+                } else {
+                    0.0
+                };
+            }
+            total_score = scores.iter().sum();
+        }
+
+        let offspring_count = parameters
+            .setup
+            .population_size
+            .saturating_sub(self.individuals.len());
+
+        let score_offspring_value = offspring_count as f64 / total_score;
+
+        // parents, partners and seeds are drawn up front, so results only depend on the run seed
+        let mut plans: Vec<(usize, usize, u64)> = Vec::new();
+
+        for (parent_index, score) in scores.iter().enumerate() {
+            for _ in 0..(score * score_offspring_value).round() as usize {
+                let partner_index = *partners
+                    .choose(&mut self.rng.small)
+                    .ok_or(NeatError::Internal("could not select random partner"))?;
+                plans.push((parent_index, partner_index, self.rng.small.gen()));
+            }
+        }
+
+        let individuals = &self.individuals;
+        let weight_std_dev = parameters.mutation.weight_std_dev(self.generation);
+        let breed = |&(parent_index, partner_index, seed): &(usize, usize, u64)| {
+            let mut rng = NeatRng::new(seed, weight_std_dev);
+
+            let crossover_start = Instant::now();
+            let mut offspring = individuals[parent_index].crossover(
+                &individuals[partner_index],
+                &mut rng.small,
+                parameters,
+            );
+            let crossover_time = crossover_start.elapsed();
+
+            let mutation_start = Instant::now();
+            let rates = offspring.mutate_parameters(&mut rng, parameters);
+            let mutation_time = mutation_start.elapsed();
+
+            (offspring, rng, rates, crossover_time, mutation_time)
+        };
+
+        #[cfg(feature = "parallel")]
+        let bred: Vec<_> = plans.par_iter().map(breed).collect();
+        #[cfg(not(feature = "parallel"))]
+        let bred: Vec<_> = plans.iter().map(breed).collect();
+
+        let mut offsprings = Vec::new();
+
+        // summed over all threads
+        let mut crossover_time = Duration::default();
+        let mut mutation_time = Duration::default();
+
+        let retry_duplicates = parameters.mutation.duplicate_offspring_retries > 0;
+        let mut fingerprints: HashSet<u64> = if retry_duplicates {
+            self.individuals.iter().map(|i| i.fingerprint()).collect()
+        } else {
+            HashSet::new()
+        };
+        let mut retries = 0;
+        let mut capped = 0;
+
+        // structural mutations share the innovation table and are applied in order
+        for (
+            (mut offspring, mut rng, rates, crossover, mutation),
+            &(parent_index, partner_index, _),
+        ) in bred.into_iter().zip(&plans)
+        {
+            let mutation_start = Instant::now();
+            capped += offspring.mutate_structure(&mut rng, &mut self.id_gen, parameters, rates);
+
+            if retry_duplicates {
+                let mut attempts = 0;
+                while attempts < parameters.mutation.duplicate_offspring_retries
+                    && fingerprints.contains(&offspring.fingerprint())
+                {
+                    let rates = offspring.mutate_parameters(&mut rng, parameters);
+                    capped +=
+                        offspring.mutate_structure(&mut rng, &mut self.id_gen, parameters, rates);
+                    attempts += 1;
+                }
+                retries += attempts;
+                fingerprints.insert(offspring.fingerprint());
+            }
+
+            let (parent, partner) = (
+                &self.individuals[parent_index],
+                &self.individuals[partner_index],
+            );
+            // fitter parent first, as in crossover
+            let parents = if parent.is_fitter_than(partner, &parameters.setup.tie_breaking) {
+                vec![parent.id, partner.id]
+            } else {
+                vec![partner.id, parent.id]
+            };
+            offspring.id = self.register(parents, parameters);
+
+            crossover_time += crossover;
+            mutation_time += mutation + mutation_start.elapsed();
+
+            offsprings.push(offspring);
+        }
+
+        self.population_statistics.timings.crossover = crossover_time;
+        self.population_statistics.timings.mutation = mutation_time;
+        self.population_statistics.duplicate_offspring_retries = retries;
+        self.population_statistics.capped_mutations = capped;
+
+        /* // generate as many offspring as population size allows
+        for parent in self
+            .individuals
+            .iter()
+            .cycle()
+            .take(parameters.setup.population_size - self.individuals.len())
+        {
+            let mut offspring = parent.crossover(
+                partners
+                    .choose(&mut self.rng.small)
+                    .expect("could not select random partner"),
+                &mut self.rng.small,
+            );
+            offspring.mutate(&mut self.rng, &mut self.id_gen, parameters);
+            offsprings.push(offspring);
+        } */
+
+        self.individuals.extend(offsprings.into_iter());
+
+        // mutate entire population here ?
+
+        self.population_statistics.milliseconds_elapsed_reproducing = now.elapsed().as_millis();
+
+        Ok(())
+    }
+
+    // weights to distribute offspring by, expects individuals to be sorted by score
+    fn allocation_weights(&self, parameters: &Parameters) -> Vec<f64> {
+        match parameters.setup.offspring_allocation {
+            OffspringAllocation::Score => normalize_weights(
+                self.individuals
+                    .iter()
+                    .map(|individual| individual.selection_score(parameters))
+                    .collect(),
+            ),
+            OffspringAllocation::LinearRank => {
+                let count = self.individuals.len();
+                (0..count).map(|rank| (count - rank) as f64).collect()
+            }
+            OffspringAllocation::ExponentialRank(base) => (0..self.individuals.len())
+                .map(|rank| base.powi(rank as i32))
+                .collect(),
+        }
+    }
+
+    fn calculate_novelty(&mut self, parameters: &Parameters) {
+        let decay = parameters.setup.archive_decay;
+        let archive_size = self.archive.len();
+        self.archive.retain(|archived| !decay.evicts(archived.age));
+        self.population_statistics.novelty.archive_evictions = archive_size - self.archive.len();
+
+        // individuals without behavior, i.e. failed evaluations, take no part in novelty
+        let behaving: Vec<usize> = self
+            .individuals
+            .iter()
+            .enumerate()
+            .filter(|(_, individual)| individual.behavior.is_some())
+            .map(|(index, _)| index)
+            .collect();
+
+        if behaving.is_empty() {
+            return;
+        }
+
+        let behaviors: Behaviors = behaving
+            .iter()
+            .flat_map(|&index| self.individuals[index].behavior.as_ref())
+            .chain(
+                self.archive
+                    .iter()
+                    .flat_map(|archived_individual| archived_individual.behavior.as_ref()),
+            )
+            .collect::<Vec<&Behavior>>()
+            .into();
+
+        let nearest_neighbors = parameters.setup.nearest_neighbors(behaviors.len());
+        self.population_statistics.novelty.nearest_neighbors = nearest_neighbors;
+
+        // archived behaviors only serve as neighbors and weigh less as they age
+        let weights = if decay == ArchiveDecay::None {
+            Vec::new()
+        } else {
+            vec![1.0; behaving.len()]
+                .into_iter()
+                .chain(
+                    self.archive
+                        .iter()
+                        .filter(|archived_individual| archived_individual.behavior.is_some())
+                        .map(|archived_individual| decay.weight(archived_individual.age)),
+                )
+                .collect()
+        };
+        let neighborhoods = behaviors
+            .with_alignment(parameters.setup.behavior_alignment)
+            .with_reduction(parameters.setup.behavior_reduction)
+            .with_queries(behaving.len())
+            .with_weights(weights)
+            .compute_neighborhoods();
+
+        let behavior_count = behaving.len() as f64;
+
+        let raw_novelties = Behaviors::novelty(&neighborhoods, nearest_neighbors);
+
+        let local_competitions = if parameters.setup.local_competition
+            || parameters.setup.selection == Selection::NoveltyLocalCompetition
+        {
+            Some(self.calculate_local_competition(
+                &Behaviors::nearest_neighbors(&neighborhoods, nearest_neighbors),
+                &behaving,
+            ))
+        } else {
+            None
+        };
+
+        // archived individuals count their age as generations in the archive from now on
+        let additions: Vec<usize> = match parameters.setup.archive_addition {
+            ArchiveAddition::MostNovel => raw_novelties
+                .iter()
+                .enumerate()
+                .filter(|(_, novelty)| !novelty.is_nan())
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| behaving[index])
+                .into_iter()
+                .collect(),
+            ArchiveAddition::Threshold {
+                initial, interval, ..
+            } => {
+                let threshold = *self.archive_threshold.value.get_or_insert(initial);
+                let additions: Vec<usize> = raw_novelties
+                    .iter()
+                    .zip(&behaving)
+                    .filter(|(novelty, _)| **novelty > threshold)
+                    .map(|(_, &index)| index)
+                    .collect();
+
+                self.archive_threshold.additions += additions.len();
+                self.archive_threshold.generations += 1;
+                if self.archive_threshold.generations >= interval.max(1) {
+                    self.archive_threshold.value = Some(
+                        parameters
+                            .setup
+                            .archive_addition
+                            .adjust(threshold, self.archive_threshold.additions),
+                    );
+                    self.archive_threshold.additions = 0;
+                    self.archive_threshold.generations = 0;
+                }
+                self.population_statistics.novelty.archive_threshold = threshold;
+                additions
+            }
+        };
+
+        self.population_statistics.novelty.archive_additions = additions.len();
+        for index in additions {
+            self.archive.push(Individual {
+                age: 0,
+                ..self.individuals[index].clone()
+            });
+        }
+
+        let mut raw_minimum = f64::INFINITY;
+        let mut raw_sum = 0.0;
+        let mut raw_maximum = f64::NEG_INFINITY;
+
+        // analyse raw novelty values
+        for &novelty in &raw_novelties {
+            if novelty > raw_maximum {
+                raw_maximum = novelty;
+            }
+            if novelty < raw_minimum {
+                raw_minimum = novelty;
+            }
+            raw_sum += novelty;
+        }
+
+        let raw_minimum = Raw::novelty(raw_minimum);
+        let raw_average = Raw::novelty(raw_sum / behavior_count);
+        let raw_maximum = Raw::novelty(raw_maximum);
+
+        let baseline = raw_minimum.value();
+
+        let shifted_minimum = raw_minimum.shift(baseline);
+        let shifted_average = raw_average.shift(baseline);
+        let shifted_maximum = raw_maximum.shift(baseline);
+
+        let with = shifted_maximum.value();
+
+        let normalized_minimum = shifted_minimum.normalize(with);
+        let normalized_average = shifted_average.normalize(with);
+        let normalized_maximum = shifted_maximum.normalize(with);
+
+        for individual in &mut self.individuals {
+            individual.novelty = None;
+            individual.local_competition = None;
+        }
+
+        if let Some(local_competitions) = local_competitions {
+            for (&index, local_competition) in behaving.iter().zip(local_competitions) {
+                self.individuals[index].local_competition = Some(local_competition);
+            }
+        }
+
+        for (&index, &raw_novelty) in behaving.iter().zip(&raw_novelties) {
+            self.individuals[index].novelty = Some(NoveltyScore::new(raw_novelty, baseline, with));
+        }
+
+        self.population_statistics.novelty.raw_maximum = raw_maximum.value();
+        self.population_statistics.novelty.raw_minimum = raw_minimum.value();
+        self.population_statistics.novelty.raw_average = raw_average.value();
+
+        self.population_statistics.novelty.shifted_maximum = shifted_maximum.value();
+        self.population_statistics.novelty.shifted_minimum = shifted_minimum.value();
+        self.population_statistics.novelty.shifted_average = shifted_average.value();
+
+        self.population_statistics.novelty.normalized_maximum = normalized_maximum.value();
+        self.population_statistics.novelty.normalized_minimum = normalized_minimum.value();
+        self.population_statistics.novelty.normalized_average = normalized_average.value();
+        self.population_statistics.novelty.degenerate = scores::is_degenerate(with);
+    }
+
+    // share of k nearest behavioral neighbors, including the archive, with lower raw fitness
+    fn calculate_local_competition(
+        &self,
+        nearest_neighbors: &[Vec<usize>],
+        behaving: &[usize],
+    ) -> Vec<f64> {
+        // in order of the compared behaviors, i.e. behaving individuals then archive
+        let raw_fitnesses: Vec<f64> = behaving
+            .iter()
+            .map(|&index| &self.individuals[index])
+            .chain(
+                self.archive
+                    .iter()
+                    .filter(|archived_individual| archived_individual.behavior.is_some()),
+            )
+            .map(|individual| {
+                individual
+                    .fitness
+                    .as_ref()
+                    .map(|fitness| fitness.raw.value())
+                    .unwrap_or(f64::NEG_INFINITY)
+            })
+            .collect();
+
+        nearest_neighbors
+            .iter()
+            .enumerate()
+            .map(|(index, neighbors)| {
+                if neighbors.is_empty() {
+                    return 0.0;
+                }
+                neighbors
+                    .iter()
+                    .filter(|&&neighbor| raw_fitnesses[neighbor] < raw_fitnesses[index])
+                    .count() as f64
+                    / neighbors.len() as f64
+            })
+            .collect()
+    }
+
+    fn assign_behavior(&mut self, progress: &[Progress]) {
+        let behaviors: Vec<(usize, &Behavior)> = progress
+            .iter()
+            .enumerate()
+            .flat_map(|(index, progress)| progress.behavior().map(|raw| (index, raw)))
+            .collect();
+
+        if behaviors.is_empty() {
+            return;
+        }
+
+        for (index, behavior) in behaviors {
+            self.individuals[index].behavior = Some(behavior.clone());
+        }
+    }
+
+    fn assign_viability(&mut self, progress: &[Progress]) {
+        for (individual, progress) in self.individuals.iter_mut().zip(progress) {
+            individual.meets_minimal_criterion = Some(progress.is_viable());
+        }
+    }
+
+    fn assign_objectives(&mut self, progress: &[Progress]) {
+        for (individual, progress) in self.individuals.iter_mut().zip(progress) {
+            individual.objectives = progress.objectives().map(<[Objective]>::to_vec);
+        }
+
+        // per objective name, in order of first appearance
+        let mut objectives: Vec<(String, Vec<f64>)> = Vec::new();
+        for objective in progress.iter().flat_map(|p| p.objectives()).flatten() {
+            match objectives
+                .iter_mut()
+                .find(|(name, _)| name == &objective.name)
+            {
+                Some((_, values)) => values.push(objective.value),
+                None => objectives.push((objective.name.clone(), vec![objective.value])),
+            }
+        }
+
+        self.population_statistics.objectives = objectives
+            .into_iter()
+            .map(|(name, values)| ObjectiveStatistics::new(name, &values))
+            .collect();
+    }
+
+    fn assign_fitness(&mut self, progress: &[Progress], parameters: &Parameters) {
+        let fitnesses: Vec<(usize, Raw<Fitness>)> = progress
+            .iter()
+            .enumerate()
+            .flat_map(|(index, progress)| {
+                progress
+                    .raw_fitness(&parameters.setup)
+                    .map(|raw| (index, raw))
+            })
+            .collect();
+
+        if fitnesses.is_empty() {
+            return;
+        }
+
+        let mut raw_minimum = f64::INFINITY;
+        let mut raw_sum = 0.0;
+        let mut raw_maximum = f64::NEG_INFINITY;
+
+        // analyse raw fitness values
+        for (_, raw_fitness) in &fitnesses {
+            if raw_fitness.value() > raw_maximum {
+                raw_maximum = raw_fitness.value();
+            }
+            if raw_fitness.value() < raw_minimum {
+                raw_minimum = raw_fitness.value();
+            }
+            raw_sum += raw_fitness.value();
+        }
+
+        let raw_minimum = Raw::fitness(raw_minimum);
+        let raw_average = Raw::fitness(raw_sum / fitnesses.len() as f64);
+        let raw_maximum = Raw::fitness(raw_maximum);
+
+        let baseline = raw_minimum.value();
+
+        let shifted_minimum = raw_minimum.shift(baseline);
+        let shifted_average = raw_average.shift(baseline);
+        let shifted_maximum = raw_maximum.shift(baseline);
+
+        let with = shifted_maximum.value();
+
+        let mut normalized_minimum = shifted_minimum.normalize(with).value();
+        let mut normalized_average = shifted_average.normalize(with).value();
+        let mut normalized_maximum = shifted_maximum.normalize(with).value();
+
+        let shaped = parameters.setup.fitness_shaping.shape(
+            &fitnesses
+                .iter()
+                .map(|(_, raw_fitness)| raw_fitness.value())
+                .collect::<Vec<f64>>(),
+        );
+
+        if let Some(shaped) = &shaped {
+            normalized_minimum = shaped.iter().cloned().fold(f64::INFINITY, f64::min);
+            normalized_average = shaped.iter().sum::<f64>() / shaped.len() as f64;
+            normalized_maximum = shaped.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        }
+
+        // shift and normalize fitness
+        for (position, (index, raw_fitness)) in fitnesses.into_iter().enumerate() {
+            let fitness = FitnessScore::new(raw_fitness.value(), baseline, with);
+            self.individuals[index].fitness = Some(match &shaped {
+                Some(shaped) => fitness.with_normalized(shaped[position]),
+                None => fitness,
+            });
+        }
+
+        self.population_statistics.fitness.raw_maximum = raw_maximum.value();
+        self.population_statistics.fitness.raw_minimum = raw_minimum.value();
+        self.population_statistics.fitness.raw_average = raw_average.value();
+
+        self.population_statistics.fitness.shifted_maximum = shifted_maximum.value();
+        self.population_statistics.fitness.shifted_minimum = shifted_minimum.value();
+        self.population_statistics.fitness.shifted_average = shifted_average.value();
+
+        self.population_statistics.fitness.normalized_maximum = normalized_maximum;
+        self.population_statistics.fitness.normalized_minimum = normalized_minimum;
+        self.population_statistics.fitness.normalized_average = normalized_average;
+        self.population_statistics.fitness.degenerate = scores::is_degenerate(with);
+    }
+
+    // keeps the order of individuals, survivors stay in front of the offspring
+    fn top_fitness_performer(&self, parameters: &Parameters) -> Result<Individual, NeatError> {
+        let fitness = |individual: &Individual| {
+            individual
+                .fitness
+                .as_ref()
+                .map(|f| f.normalized.value())
+                .unwrap_or(f64::NEG_INFINITY)
+        };
+
+        self.individuals
+            .iter()
+            .max_by(|individual_0, individual_1| {
+                individual_0.compare_by(individual_1, fitness, &parameters.setup.tie_breaking)
+            })
+            .cloned()
+            .ok_or(NeatError::Internal("individuals are empty"))
+    }
+
+    fn sort_individuals_by_pareto_front(
+        &mut self,
+        objectives: Vec<Vec<f64>>,
+        parameters: &Parameters,
+    ) {
+        let mut ranked: Vec<(usize, Individual)> = pareto_ranks(&objectives)
+            .into_iter()
+            .zip(self.individuals.drain(..))
+            .collect();
+
+        // first front first, within a front highest score first
+        ranked.sort_by(|(rank_0, individual_0), (rank_1, individual_1)| {
+            rank_0.cmp(rank_1).then_with(|| {
+                individual_1.compare_by(
+                    individual_0,
+                    |individual| individual.selection_score(parameters),
+                    &parameters.setup.tie_breaking,
+                )
+            })
+        });
+
+        self.individuals = ranked
+            .into_iter()
+            .map(|(_, individual)| individual)
+            .collect();
+    }
+
+    fn sort_individuals_by_score(&mut self, parameters: &Parameters) {
+        // sort individuals by their score (descending, i.e. highest score first), NaN scores last
+        self.individuals.sort_by(|individual_0, individual_1| {
+            individual_1.compare_by(
+                individual_0,
+                |individual| individual.selection_score(parameters),
+                &parameters.setup.tie_breaking,
+            )
+        });
+    }
+
+    // expects individuals to be sorted by rank
+    fn truncate_to_survivors(&mut self, parameters: &Parameters) {
+        let survivor_count = (parameters.setup.population_size as f64
+            * parameters.setup.survival_rate)
+            .ceil() as usize;
+
+        let niche_count = match parameters.setup.survival_selection {
+            SurvivalSelection::Global => {
+                self.individuals.truncate(survivor_count);
+                return;
+            }
+            SurvivalSelection::PerNiche(niche_count) => niche_count,
+        };
+
+        let behaving: Vec<usize> = self
+            .individuals
+            .iter()
+            .enumerate()
+            .filter(|(_, individual)| individual.behavior.is_some())
+            .map(|(index, _)| index)
+            .collect();
+
+        let neighborhoods = Behaviors::from(
+            behaving
+                .iter()
+                .flat_map(|&index| self.individuals[index].behavior.as_ref())
+                .collect::<Vec<&Behavior>>(),
+        )
+        .with_alignment(parameters.setup.behavior_alignment)
+        .with_reduction(parameters.setup.behavior_reduction)
+        .compute_neighborhoods();
+
+        // individuals without behavior share an extra niche
+        let mut niches = vec![niche_count; self.individuals.len()];
+        for (&index, niche) in behaving
+            .iter()
+            .zip(behavioral_niches(&neighborhoods, niche_count))
+        {
+            niches[index] = niche;
+        }
+
+        let mut niche_sizes = vec![0; niche_count + 1];
+        for &niche in &niches {
+            niche_sizes[niche] += 1;
+        }
+
+        // every niche keeps the same share of its best individuals
+        let quotas = niche_quotas(&niche_sizes, survivor_count);
+        let mut kept = vec![0; niche_count + 1];
+        let mut niches = niches.into_iter();
+        self.individuals.retain(|_| {
+            let niche = niches.next().unwrap();
+            let survives = kept[niche] < quotas[niche];
+            if survives {
+                kept[niche] += 1;
+            }
+            survives
+        });
+    }
+
+    fn track_stagnation(&mut self) {
+        let best = self
+            .individuals
+            .iter()
+            .flat_map(|individual| individual.fitness.as_ref())
+            .map(|fitness| fitness.raw.value())
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        // without any fitness, i.e. pure novelty search, there is nothing to stagnate
+        let best = match best {
+            Some(best) => best,
+            None => return,
+        };
+
+        if best > self.best_fitness {
+            self.best_fitness = best;
+            self.stagnation = 0;
+        } else {
+            self.stagnation += 1;
+        }
+        self.population_statistics.stagnation = self.stagnation;
+    }
+
+    fn refill(&mut self, template: &Genome, parameters: &Parameters) {
+        self.population_statistics.capped_mutations = 0;
+        while self.individuals.len() < parameters.setup.population_size {
+            let mut individual = Individual {
+                genome: template.fresh(&mut self.id_gen, parameters),
+                ..Default::default()
+            };
+            individual.init(&mut self.rng, parameters);
+            self.population_statistics.capped_mutations +=
+                individual.mutate(&mut self.rng, &mut self.id_gen, parameters);
+            individual.id = self.register(Vec::new(), parameters);
+            self.individuals.push(individual);
+        }
+    }
+
+    pub fn next_generation(
+        &mut self,
+        parameters: &Parameters,
+        progress: &[Progress],
+    ) -> Result<PopulationStatistics, NeatError> {
+        // parameters might have changed since last generation
+        self.rng
+            .set_weight_std_dev(parameters.mutation.weight_std_dev(self.generation));
+
+        let now = Instant::now();
+        self.assign_objectives(progress);
+        self.assign_fitness(progress, parameters);
+        self.population_statistics.timings.fitness_assignment = now.elapsed();
+
+        let now = Instant::now();
+        self.assign_viability(progress);
+        self.assign_behavior(progress);
+        self.population_statistics.timings.behavior_assignment = now.elapsed();
+
+        // calculate novelty based on previously assigned behavior
+        let now = Instant::now();
+        self.calculate_novelty(parameters);
+        self.population_statistics.timings.novelty = now.elapsed();
+
+        let now = Instant::now();
+        match parameters.setup.selection {
+            Selection::Score => self.sort_individuals_by_score(parameters),
+            Selection::NoveltyLocalCompetition => {
+                let objectives = self
+                    .individuals
+                    .iter()
+                    .map(|individual| {
+                        vec![
+                            individual
+                                .novelty
+                                .as_ref()
+                                .map(|n| n.normalized.value())
+                                .unwrap_or(0.0),
+                            individual.local_competition.unwrap_or(0.0),
+                        ]
+                    })
+                    .collect();
+                self.sort_individuals_by_pareto_front(objectives, parameters)
+            }
+            Selection::Pareto => {
+                // objectives by name, weighted like during scalarization, negative weights minimize
+                let names: Vec<&str> = self
+                    .population_statistics
+                    .objectives
+                    .iter()
+                    .map(|objective| objective.name.as_str())
+                    .collect();
+                let objectives = self
+                    .individuals
+                    .iter()
+                    .map(|individual| {
+                        names
+                            .iter()
+                            .map(|&name| {
+                                individual
+                                    .objectives
+                                    .iter()
+                                    .flatten()
+                                    .find(|objective| objective.name == name)
+                                    .map(|objective| {
+                                        objective.value
+                                            * parameters
+                                                .setup
+                                                .objective_weights
+                                                .get(name)
+                                                .unwrap_or(&1.0)
+                                    })
+                                    .filter(|value| !value.is_nan())
+                                    .unwrap_or(f64::NEG_INFINITY)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                self.sort_individuals_by_pareto_front(objectives, parameters)
+            }
+        }
+
+        let snapshot_size = match parameters.setup.snapshot {
+            Snapshot::Disabled => 0,
+            Snapshot::Top(size) => size,
+            Snapshot::Full => self.individuals.len(),
+        };
+        self.population_statistics.snapshot = self
+            .individuals
+            .iter()
+            .take(snapshot_size)
+            .cloned()
+            .collect();
+
+        self.track_stagnation();
+
+        let extinction = parameters
+            .setup
+            .extinction
+            .filter(|extinction| self.stagnation >= extinction.stagnation_generations);
+        self.population_statistics.extinction = extinction.is_some();
+
+        // template for fresh genomes, taken before anyone is removed
+        let template = extinction.map(|_| self.individuals[0].genome.clone());
+
+        // remove any individual that does not survive
+        match extinction {
+            Some(extinction) => {
+                self.individuals.truncate(extinction.survivors);
+                self.stagnation = 0;
+            }
+            None => self.truncate_to_survivors(parameters),
+        }
+        self.survivors = self.individuals.len();
+        self.population_statistics.timings.selection = now.elapsed();
+
+        // increment age of surviving individuals and archive entries
+        for individual in self.individuals.iter_mut().chain(self.archive.iter_mut()) {
+            individual.age += 1;
+        }
+
+        self.generation += 1;
+        let now = Instant::now();
+        match template {
+            // refill with fresh genomes instead of offspring
+            Some(template) => {
+                self.population_statistics.duplicate_offspring_retries = 0;
+                self.refill(&template, parameters)
+            }
+            // reproduce from surviving individuals
+            None => self.generate_offspring(parameters)?,
+        }
+        self.population_statistics.timings.reproduction = now.elapsed();
+
+        // return some statistics
+        let now = Instant::now();
+        let mut statistics = self.gather_statistics(parameters)?;
+        statistics.timings.statistics = now.elapsed();
+        self.population_statistics.timings.statistics = statistics.timings.statistics;
+
+        Ok(statistics)
+    }
+
+    fn gather_statistics(
+        &mut self,
+        parameters: &Parameters,
+    ) -> Result<PopulationStatistics, NeatError> {
+        self.population_statistics.top_performer = self.top_fitness_performer(parameters)?;
+
+        // determine maximum age
+        self.population_statistics.age_maximum = self
+            .individuals
+            .iter()
+            .map(|individual| individual.age)
+            .max()
+            .ok_or(NeatError::Internal("cant find max age"))?;
+
+        // determine average age
+        self.population_statistics.age_average = self
+            .individuals
+            .iter()
+            .map(|individual| individual.age as f64)
+            .sum::<f64>()
+            / self.individuals.len() as f64;
+
+        let individuals = &self.individuals;
+        self.population_statistics.complexity = ComplexityStatistics {
+            hidden_nodes: CountStatistics::new(individuals.iter().map(|i| i.hidden.len())),
+            feed_forward_connections: CountStatistics::new(
+                individuals.iter().map(|i| i.feed_forward.len()),
+            ),
+            recurrent_connections: CountStatistics::new(
+                individuals.iter().map(|i| i.recurrent.len()),
+            ),
+            disabled_connections: CountStatistics::new(
+                individuals.iter().map(|i| i.disabled.len()),
+            ),
+        };
+
+        let mut topologies = HashSet::new();
+        self.population_statistics.duplicate_topologies = individuals
+            .iter()
+            .filter(|individual| !topologies.insert(individual.structural_hash()))
+            .count();
+
+        self.population_statistics.clone()
+    }
+}
+
+// shifts and scales scores into [0, 1], equal scores get uniform weights and invalid ones the lowest
+fn normalize_weights(mut scores: Vec<f64>) -> Vec<f64> {
+    let mut minimum_score = f64::INFINITY;
+    let mut maximum_score = f64::NEG_INFINITY;
+
+    // analyse score values
+    for &score in scores.iter().filter(|score| score.is_finite()) {
+        if score > maximum_score {
+            maximum_score = score;
+        }
+        if score < minimum_score {
+            minimum_score = score;
+        }
+    }
+
+    // no finite score at all
+    if minimum_score > maximum_score {
+        return vec![1.0; scores.len()];
+    }
+
+    let range = maximum_score - minimum_score;
+
+    // all finite scores are equal
+    if range <= 0.0 {
+        return scores
+            .iter()
+            .map(|score| if score.is_finite() { 1.0 } else { 0.0 })
+            .collect();
+    }
+
+    // shift and normalize scores
+    for score in &mut scores {
+        *score = if score.is_finite() {
+            (*score - minimum_score) / range
+        } else {
+            0.0
+        };
+    }
+
+    scores
+}
+
+// survivors per niche proportional to its size, summing up to exactly the survivor count,
+// seats left by rounding down go to the largest remainders
+fn niche_quotas(niche_sizes: &[usize], survivor_count: usize) -> Vec<usize> {
+    let total: usize = niche_sizes.iter().sum();
+    let survivor_count = survivor_count.min(total);
+    if total == 0 {
+        return vec![0; niche_sizes.len()];
+    }
+
+    let exact: Vec<f64> = niche_sizes
+        .iter()
+        .map(|&size| (size * survivor_count) as f64 / total as f64)
+        .collect();
+    let mut quotas: Vec<usize> = exact.iter().map(|quota| quota.floor() as usize).collect();
+
+    let mut by_remainder: Vec<usize> = (0..niche_sizes.len()).collect();
+    by_remainder
+        .sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    let missing = survivor_count - quotas.iter().sum::<usize>();
+    for &niche in by_remainder.iter().take(missing) {
+        quotas[niche] += 1;
+    }
+
+    quotas
+}
+
+// assigns every entry to the nearest of given number of centers,
+// centers are picked greedily as far apart as possible starting with the first entry
+fn behavioral_niches(neighborhoods: &[Vec<(usize, f64)>], niche_count: usize) -> Vec<usize> {
+    let count = neighborhoods.len();
+    if count == 0 || niche_count == 0 {
+        return vec![0; count];
+    }
+
+    let mut distances = vec![vec![0.0; count]; count];
+    for (index, neighborhood) in neighborhoods.iter().enumerate() {
+        for &(neighbor, distance) in neighborhood {
+            distances[index][neighbor] = distance;
+        }
+    }
+
+    let mut centers = vec![0];
+    while centers.len() < niche_count.min(count) {
+        let farthest = (0..count)
+            .filter(|index| !centers.contains(index))
+            .max_by(|&a, &b| {
+                let closest = |index: usize| {
+                    centers
+                        .iter()
+                        .map(|&center| distances[index][center])
+                        .fold(f64::INFINITY, f64::min)
+                };
+                closest(a)
+                    .partial_cmp(&closest(b))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap();
+        centers.push(farthest);
+    }
+
+    (0..count)
+        .map(|index| {
+            centers
+                .iter()
+                .enumerate()
+                .min_by(|(_, &a), (_, &b)| {
+                    distances[index][a]
+                        .partial_cmp(&distances[index][b])
+                        .unwrap_or(Ordering::Equal)
+                })
+                .map(|(niche, _)| niche)
+                .unwrap()
+        })
+        .collect()
+}
+
+// index of the non-dominated front every entry belongs to, objectives are maximized
+// missing objectives, i.e. of failed evaluations, count as worst possible
+fn pareto_ranks(objectives: &[Vec<f64>]) -> Vec<usize> {
+    let width = objectives.iter().map(Vec::len).max().unwrap_or(0);
+    let value =
+        |entry: &[f64], index: usize| entry.get(index).cloned().unwrap_or(f64::NEG_INFINITY);
+
+    let dominates = |a: &[f64], b: &[f64]| {
+        (0..width).all(|index| value(a, index) >= value(b, index))
+            && (0..width).any(|index| value(a, index) > value(b, index))
+    };
+
+    let mut ranks = vec![0; objectives.len()];
+    let mut remaining: Vec<usize> = (0..objectives.len()).collect();
+    let mut rank = 0;
+
+    while !remaining.is_empty() {
+        let front: Vec<usize> = remaining
+            .iter()
+            .cloned()
+            .filter(|&index| {
+                !remaining
+                    .iter()
+                    .any(|&other| dominates(&objectives[other], &objectives[index]))
+            })
+            .collect();
+
+        for &index in &front {
+            ranks[index] = rank;
+        }
+
+        remaining.retain(|index| !front.contains(index));
+        rank += 1;
+    }
+
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{behavioral_niches, niche_quotas, normalize_weights, pareto_ranks, Population};
+    use crate::{
+        individual::behavior::{Behavior, Behaviors},
+        parameters::Parameters,
+        runtime::progress::Progress,
+    };
+
+    #[test]
+    fn split_into_behavioral_niches() {
+        let behaviors = vec![
+            Behavior(vec![0.0]),
+            Behavior(vec![10.0]),
+            Behavior(vec![0.5]),
+            Behavior(vec![9.0]),
+        ];
+
+        let neighborhoods =
+            Behaviors::from(behaviors.iter().collect::<Vec<&Behavior>>()).compute_neighborhoods();
+
+        assert_eq!(behavioral_niches(&neighborhoods, 2), vec![0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn rank_pareto_fronts() {
+        let objectives = vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![0.5, 0.5],
+            vec![0.2, 0.2],
+            vec![0.0],
+        ];
+
+        assert_eq!(pareto_ranks(&objectives), vec![0, 0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn split_survivors_by_largest_remainder() {
+        // rounding every niche up would keep 6
+        assert_eq!(niche_quotas(&[3, 3, 4], 4), vec![1, 1, 2]);
+        assert_eq!(niche_quotas(&[1, 1, 1, 0], 2), vec![1, 1, 0, 0]);
+        assert_eq!(niche_quotas(&[2, 1], 5), vec![2, 1]);
+        assert_eq!(niche_quotas(&[0, 0], 3), vec![0, 0]);
+    }
+
+    #[test]
+    fn normalize_spread_weights() {
+        assert_eq!(normalize_weights(vec![1.0, 2.0, 3.0]), vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn normalize_all_equal_weights() {
+        assert_eq!(normalize_weights(vec![0.7, 0.7, 0.7]), vec![1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn normalize_all_zero_weights() {
+        assert_eq!(normalize_weights(vec![0.0, 0.0]), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn normalize_invalid_weights() {
+        let weights = normalize_weights(vec![f64::NAN, 1.0, 2.0]);
+
+        assert_eq!(weights, vec![0.0, 0.0, 1.0]);
+        assert!(normalize_weights(vec![f64::NAN, f64::NAN])
+            .iter()
+            .all(|weight| *weight == 1.0));
+    }
+
+    #[test]
+    fn keep_survivors_in_front_without_fitness() {
+        let mut parameters = Parameters::default();
+        parameters.setup.population_size = 10;
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+        parameters.setup.survival_rate = 0.5;
+        let mut population = Population::new(&parameters);
+
+        for _ in 0..3 {
+            let evaluated: Vec<usize> = population.individuals().iter().map(|i| i.id).collect();
+            let progress: Vec<Progress> = evaluated.iter().map(|_| Progress::Empty).collect();
+            population.next_generation(&parameters, &progress).unwrap();
+
+            let survivors = population.survivors();
+            assert!(survivors > 0);
+            assert!(population.individuals()[..survivors]
+                .iter()
+                .all(|individual| evaluated.contains(&individual.id)));
+            assert!(population.individuals()[survivors..]
+                .iter()
+                .all(|individual| !evaluated.contains(&individual.id)));
+        }
+    }
+
+    #[test]
+    fn keep_archive_threshold_in_checkpoint() {
+        let mut parameters = Parameters::default();
+        parameters.setup.population_size = 4;
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+        let mut population = Population::new(&parameters);
+        population.archive_threshold.value = Some(2.5);
+        population.archive_threshold.additions = 3;
+
+        let checkpoint = serde_json::to_string(&population.checkpoint(1)).unwrap();
+        let restored =
+            Population::from_checkpoint(serde_json::from_str(&checkpoint).unwrap(), &parameters);
+
+        assert_eq!(restored.archive_threshold.value, Some(2.5));
+        assert_eq!(restored.archive_threshold.additions, 3);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    error::NeatError,
+    individual::Individual,
+    parameters::Parameters,
+    utility::{statistics::Statistics, time::unix_timestamp},
+};
+
+use super::checkpoint::Checkpoint;
+
+// files of one run in a directory, every name starts with the seed and the start time of the run:
+// parameters.json, statistics.jsonl with one line per generation, champion_<generation>.json and checkpoint.json
+#[derive(Debug, Clone)]
+pub struct RunArtifacts {
+    directory: PathBuf,
+    prefix: String,
+    // generations between saved champions and checkpoints, zero saves them only when the run ends
+    interval: usize,
+}
+
+impl RunArtifacts {
+    // creates the directory if needed and saves the parameters right away
+    pub fn new(
+        directory: &str,
+        interval: usize,
+        parameters: &Parameters,
+    ) -> Result<Self, NeatError> {
+        fs::create_dir_all(directory)?;
+
+        let artifacts = RunArtifacts {
+            directory: PathBuf::from(directory),
+            prefix: format!("run_{}_{}", parameters.setup.seed, unix_timestamp()),
+            interval,
+        };
+        artifacts.write_json("parameters.json", parameters)?;
+        Ok(artifacts)
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.directory.join(format!("{}_{}", self.prefix, name))
+    }
+
+    fn write_json<T: Serialize>(&self, name: &str, value: &T) -> Result<(), NeatError> {
+        let writer = BufWriter::new(File::create(self.path(name))?);
+        Ok(serde_json::to_writer_pretty(writer, value)?)
+    }
+
+    pub fn due(&self, generation: usize) -> bool {
+        self.interval > 0 && generation % self.interval == 0
+    }
+
+    pub fn append_statistics(&self, statistics: &Statistics) -> Result<(), NeatError> {
+        let mut writer = BufWriter::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path("statistics.jsonl"))?,
+        );
+        serde_json::to_writer(&mut writer, statistics)?;
+        writeln!(writer)?;
+        Ok(writer.flush()?)
+    }
+
+    pub fn save_champion(&self, generation: usize, champion: &Individual) -> Result<(), NeatError> {
+        self.write_json(&format!("champion_{}.json", generation), champion)
+    }
+
+    // overwrites the previous one, so the latest checkpoint is always at hand
+    pub fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<(), NeatError> {
+        let writer = BufWriter::new(File::create(self.path("checkpoint.json"))?);
+        Ok(serde_json::to_writer(writer, checkpoint)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::RunArtifacts;
+    use crate::{individual::Individual, parameters::Parameters, utility::statistics::Statistics};
+
+    #[test]
+    fn write_run_artifacts() {
+        let directory =
+            env::temp_dir().join(format!("novel_set_neat_artifacts_test_{}", process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        let mut parameters = Parameters::default();
+        parameters.setup.seed = 7;
+        let artifacts = RunArtifacts::new(directory.to_str().unwrap(), 5, &parameters).unwrap();
+        assert!(artifacts.path("parameters.json").exists());
+        assert!(artifacts
+            .path("parameters.json")
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("run_7_"));
+
+        artifacts.append_statistics(&Statistics::default()).unwrap();
+        artifacts.append_statistics(&Statistics::default()).unwrap();
+        let statistics = fs::read_to_string(artifacts.path("statistics.jsonl")).unwrap();
+        assert_eq!(statistics.lines().count(), 2);
+
+        assert!(artifacts.due(10) && !artifacts.due(12));
+        artifacts.save_champion(10, &Individual::default()).unwrap();
+        assert!(artifacts.path("champion_10.json").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use crate::{individual::Individual, utility::statistics::CacheStatistics};
+
+use super::progress::Progress;
+
+// least recently used evaluations by genome fingerprint, see parameters.setup.evaluation_cache_size
+pub(crate) struct EvaluationCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    // progress and time of last use by fingerprint
+    entries: HashMap<u64, (Progress, u64)>,
+    // fingerprints by time of last use
+    recency: BTreeMap<u64, u64>,
+    time: u64,
+    hits: usize,
+    misses: usize,
+}
+
+impl CacheState {
+    fn get(&mut self, fingerprint: u64) -> Option<Progress> {
+        self.time += 1;
+        let (progress, last_use) = self.entries.get_mut(&fingerprint)?;
+        self.recency.remove(last_use);
+        *last_use = self.time;
+        self.recency.insert(self.time, fingerprint);
+        Some(progress.cached())
+    }
+
+    fn insert(&mut self, fingerprint: u64, progress: Progress, capacity: usize) {
+        self.time += 1;
+        if let Some((_, last_use)) = self.entries.insert(fingerprint, (progress, self.time)) {
+            self.recency.remove(&last_use);
+        }
+        self.recency.insert(self.time, fingerprint);
+
+        while self.entries.len() > capacity {
+            let oldest = *self
+                .recency
+                .keys()
+                .next()
+                .expect("every entry has a last use");
+            if let Some(fingerprint) = self.recency.remove(&oldest) {
+                self.entries.remove(&fingerprint);
+            }
+        }
+    }
+}
+
+enum Entry {
+    Hit(Progress),
+    // next of the evaluated individuals
+    Evaluate,
+    // same genome as the evaluated individual at given position
+    Duplicate(usize),
+}
+
+// individuals that need evaluation, see EvaluationCache::resolve
+pub(crate) struct Lookup<'i> {
+    entries: Vec<Entry>,
+    fingerprints: Vec<u64>,
+    pub uncached: Cow<'i, [Individual]>,
+}
+
+impl EvaluationCache {
+    pub fn new(capacity: usize) -> Self {
+        EvaluationCache {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    // expects expressed individuals, so parallel connections do not tell equal networks apart
+    pub fn lookup<'i>(&self, individuals: &'i [Individual]) -> Lookup<'i> {
+        if self.capacity == 0 {
+            return Lookup {
+                entries: individuals.iter().map(|_| Entry::Evaluate).collect(),
+                fingerprints: Vec::new(),
+                uncached: Cow::Borrowed(individuals),
+            };
+        }
+
+        let mut state = self.state.lock().expect("evaluation cache poisoned");
+        let mut entries = Vec::with_capacity(individuals.len());
+        let mut fingerprints = Vec::new();
+        let mut uncached = Vec::new();
+        let mut pending: HashMap<u64, usize> = HashMap::new();
+
+        for individual in individuals {
+            let fingerprint = individual.fingerprint();
+            if let Some(progress) = state.get(fingerprint) {
+                state.hits += 1;
+                entries.push(Entry::Hit(progress));
+            } else if let Some(&index) = pending.get(&fingerprint) {
+                state.hits += 1;
+                entries.push(Entry::Duplicate(index));
+            } else {
+                state.misses += 1;
+                pending.insert(fingerprint, uncached.len());
+                fingerprints.push(fingerprint);
+                uncached.push(individual.clone());
+                entries.push(Entry::Evaluate);
+            }
+        }
+
+        Lookup {
+            entries,
+            fingerprints,
+            uncached: Cow::Owned(uncached),
+        }
+    }
+
+    // progress of every looked up individual given the progress of the uncached ones
+    pub fn resolve(&self, lookup: Lookup<'_>, progress: Vec<Progress>) -> Vec<Progress> {
+        if self.capacity == 0 {
+            return progress;
+        }
+
+        let mut state = self.state.lock().expect("evaluation cache poisoned");
+        for (&fingerprint, progress) in lookup.fingerprints.iter().zip(&progress) {
+            state.insert(fingerprint, progress.cached(), self.capacity);
+        }
+        drop(state);
+
+        let duplicates: Vec<Option<Progress>> = lookup
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Duplicate(index) => Some(progress[*index].cached()),
+                _ => None,
+            })
+            .collect();
+
+        let mut progress = progress.into_iter();
+        lookup
+            .entries
+            .into_iter()
+            .zip(duplicates)
+            .map(|(entry, duplicate)| match entry {
+                Entry::Hit(progress) => progress,
+                Entry::Evaluate => progress
+                    .next()
+                    .expect("one progress per uncached individual"),
+                Entry::Duplicate(_) => duplicate.expect("duplicates are copied beforehand"),
+            })
+            .collect()
+    }
+
+    // hits and misses since the last call
+    pub fn take_statistics(&self) -> CacheStatistics {
+        let mut state = self.state.lock().expect("evaluation cache poisoned");
+        let statistics = CacheStatistics {
+            hits: state.hits,
+            misses: state.misses,
+            size: state.entries.len(),
+        };
+        state.hits = 0;
+        state.misses = 0;
+        statistics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EvaluationCache;
+    use crate::{
+        genes::{nodes::Node, Activation, Id},
+        individual::{genome::Genome, scores::ScoreValue, Individual},
+        parameters::Setup,
+        runtime::progress::Progress,
+    };
+
+    fn individual(weight: f64) -> Individual {
+        Individual {
+            genome: Genome::builder()
+                .input(Id(0))
+                .output(Node::new(Id(1), Activation::Tanh))
+                .connection(Id(0), weight, Id(1))
+                .build()
+                .unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn evaluate_each_genome_once() {
+        let cache = EvaluationCache::new(2);
+
+        let individuals = vec![individual(1.0), individual(2.0), individual(1.0)];
+        let lookup = cache.lookup(&individuals);
+        assert_eq!(lookup.uncached.len(), 2);
+        let progress = cache.resolve(lookup, vec![Progress::fitness(1.0), Progress::fitness(2.0)]);
+        let fitnesses: Vec<Option<f64>> = progress
+            .iter()
+            .map(|progress| {
+                progress
+                    .raw_fitness(&Setup::default())
+                    .map(|fitness| fitness.value())
+            })
+            .collect();
+        assert_eq!(fitnesses, vec![Some(1.0), Some(2.0), Some(1.0)]);
+
+        // the least recently used genome is evicted
+        let individuals = vec![individual(1.0), individual(3.0)];
+        let lookup = cache.lookup(&individuals);
+        assert_eq!(lookup.uncached.len(), 1);
+        cache.resolve(lookup, vec![Progress::fitness(3.0)]);
+        assert_eq!(cache.lookup(&[individual(2.0)]).uncached.len(), 1);
+
+        let statistics = cache.take_statistics();
+        assert_eq!((statistics.hits, statistics.misses), (2, 4));
+        assert_eq!(statistics.size, 2);
+    }
+}
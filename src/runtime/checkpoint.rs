@@ -0,0 +1,107 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::NeatError,
+    genes::{ActivationRegistry, IdGenerator},
+    individual::{
+        genealogy::Genealogy,
+        io_growth::{IoGrowth, IoWiring},
+        Individual,
+    },
+    parameters::Parameters,
+    utility::rng::NeatRng,
+};
+
+// state needed to resume a run, the random source is reseeded on resume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub num_generation: usize,
+    pub individuals: Vec<Individual>,
+    pub archive: Vec<Individual>,
+    pub id_gen: IdGenerator,
+    #[serde(default)]
+    pub genealogy: Genealogy,
+    // individuals and archive entries per island, in order, empty for a single population
+    #[serde(default)]
+    pub islands: Vec<(usize, usize)>,
+    // per island, in order, missing ones start over from the initial threshold
+    #[serde(default)]
+    pub archive_thresholds: Vec<ArchiveThreshold>,
+}
+
+// state of parameters.setup.archive_addition
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveThreshold {
+    pub value: Option<f64>,
+    // additions and generations of the current interval
+    pub additions: usize,
+    pub generations: usize,
+}
+
+// novelty archive on its own, i.e. to start another run with the behaviors explored so far
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Archive(pub Vec<Individual>);
+
+impl Archive {
+    pub fn save(&self, path: &str) -> Result<(), NeatError> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+
+    pub fn load(path: &str) -> Result<Self, NeatError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+impl Checkpoint {
+    // archives of all islands
+    pub fn archive(&self) -> Archive {
+        Archive(self.archive.clone())
+    }
+
+    // grows individuals and archive to the dimensions of parameters.setup before resuming, see Genome::grow_io
+    pub fn grow_io(&mut self, parameters: &Parameters, wiring: IoWiring) -> Result<(), NeatError> {
+        let template = match self.individuals.first() {
+            Some(individual) => individual,
+            None => return Ok(()),
+        };
+        let growth = IoGrowth::new(template, &mut self.id_gen, parameters, wiring)?;
+
+        let mut rng = NeatRng::new(
+            parameters.setup.seed,
+            parameters.mutation.weight_perturbation_std_dev,
+        );
+        for individual in self.individuals.iter_mut().chain(self.archive.iter_mut()) {
+            individual.grow_io(&growth, &mut rng, parameters)?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), NeatError> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+
+    pub fn load(path: &str) -> Result<Self, NeatError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    // custom activations are saved by name, see Genome::resolve_activations
+    pub fn resolve_activations(
+        &mut self,
+        activations: &ActivationRegistry,
+    ) -> Result<(), NeatError> {
+        self.individuals
+            .iter_mut()
+            .chain(self.archive.iter_mut())
+            .try_for_each(|individual| individual.resolve_activations(activations))
+    }
+}
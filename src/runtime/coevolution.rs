@@ -0,0 +1,351 @@
+// evolves several populations side by side, i.e. solvers and problem generators, where individuals of one
+// population are evaluated against opponents drawn from the others
+use rand::{prelude::SmallRng, seq::SliceRandom, SeedableRng};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::ParallelIterator;
+
+use crate::{
+    individual::{scores::ScoreValue, Individual},
+    parameters::Parameters,
+    population::islands::Islands,
+    utility::{
+        rng::generation_seed,
+        statistics::Statistics,
+        time::{unix_timestamp, Instant},
+    },
+};
+
+use super::{checkpoint::Checkpoint, evaluation::Evaluation, progress::Progress, Runtime};
+
+// called with the index of the evaluated population, the individual and its opponents of every other population,
+// once per individual and generation: unlike Runtime there are no trials, no evaluation cache and no error policy,
+// since the result depends on the drawn opponents and the function can not fail
+pub type CoEvolutionFunction =
+    Box<dyn Fn(usize, &Individual, &[Opponent<'_>]) -> Progress + Send + Sync>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Opponent<'o> {
+    // index of the population the opponent belongs to
+    pub population: usize,
+    pub individual: &'o Individual,
+}
+
+// how opponents are drawn from every other population, once per generation and shared by all evaluated individuals
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pairing {
+    // every current individual
+    All,
+    // given number of current individuals, chosen at random
+    Random(usize),
+    // champions of the given number of latest generations, random individuals as long as there are none
+    Champions(usize),
+}
+
+impl Default for Pairing {
+    fn default() -> Self {
+        Pairing::Random(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoEvolutionSchedule {
+    // every population advances each step, opponents are drawn before any of them reproduces
+    Simultaneous,
+    // one population advances each step in turn, the others stay fixed meanwhile
+    Alternating,
+}
+
+impl Default for CoEvolutionSchedule {
+    fn default() -> Self {
+        CoEvolutionSchedule::Simultaneous
+    }
+}
+
+const DEFAULT_CHAMPION_HISTORY: usize = 100;
+
+pub struct CoEvolutionRuntime {
+    parameters: Vec<Parameters>,
+    populations: Vec<Islands>,
+    statistics: Vec<Statistics>,
+    // best individual of the latest evaluated generations by population, see champion_history
+    champions: Vec<Vec<Individual>>,
+    champion_history: usize,
+    progress_function: CoEvolutionFunction,
+    pairing: Pairing,
+    schedule: CoEvolutionSchedule,
+    rng: SmallRng,
+    step: usize,
+}
+
+impl CoEvolutionRuntime {
+    // one population per parameters, opponents are drawn with the seed of the first ones
+    pub fn new(parameters: Vec<Parameters>, progress_function: CoEvolutionFunction) -> Self {
+        let parameters: Vec<Parameters> = parameters
+            .into_iter()
+            .map(|mut parameters| {
+                parameters.resolve_seed();
+                parameters
+            })
+            .collect();
+
+        CoEvolutionRuntime {
+            populations: parameters.iter().map(Islands::new).collect(),
+            statistics: parameters
+                .iter()
+                .map(|parameters| Statistics {
+                    seed: parameters.setup.seed,
+                    ..Default::default()
+                })
+                .collect(),
+            champions: vec![Vec::new(); parameters.len()],
+            champion_history: DEFAULT_CHAMPION_HISTORY,
+            progress_function,
+            pairing: Pairing::default(),
+            schedule: CoEvolutionSchedule::default(),
+            rng: SmallRng::seed_from_u64(
+                parameters
+                    .first()
+                    .map_or(0, |parameters| parameters.setup.seed),
+            ),
+            step: 0,
+            parameters,
+        }
+    }
+
+    pub fn with_pairing(mut self, pairing: Pairing) -> Self {
+        self.pairing = pairing;
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: CoEvolutionSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    // number of latest champions kept by population, at least as many as Pairing::Champions draws from
+    pub fn with_champion_history(mut self, champion_history: usize) -> Self {
+        self.champion_history = champion_history;
+        self
+    }
+
+    pub fn population(&self, population: usize) -> &[Individual] {
+        self.populations[population].individuals()
+    }
+
+    pub fn statistics(&self, population: usize) -> &Statistics {
+        &self.statistics[population]
+    }
+
+    pub fn champions(&self, population: usize) -> &[Individual] {
+        &self.champions[population]
+    }
+
+    pub fn checkpoint(&self, population: usize) -> Checkpoint {
+        self.populations[population].checkpoint(self.statistics[population].num_generation)
+    }
+
+    // expressed opponents drawn from given population according to pairing
+    fn opponents(&mut self, population: usize) -> Vec<Individual> {
+        let individuals = self.populations[population].individuals();
+        let champions = &self.champions[population];
+
+        let opponents: Vec<Individual> = match self.pairing {
+            Pairing::All => individuals.to_vec(),
+            Pairing::Champions(count) if !champions.is_empty() => {
+                champions[champions.len().saturating_sub(count)..].to_vec()
+            }
+            Pairing::Random(count) | Pairing::Champions(count) => individuals
+                .choose_multiple(&mut self.rng, count)
+                .cloned()
+                .collect(),
+        };
+
+        Runtime::expressed(&opponents).into_owned()
+    }
+
+    fn evaluate(&self, population: usize, opponents: &[Vec<Individual>]) -> Vec<Progress> {
+        let opponents: Vec<Opponent> = opponents
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != population)
+            .flat_map(|(other, individuals)| {
+                individuals.iter().map(move |individual| Opponent {
+                    population: other,
+                    individual,
+                })
+            })
+            .collect();
+
+        let progress_function = &self.progress_function;
+        let individuals = Runtime::expressed(self.populations[population].individuals());
+        Runtime::iterate(&individuals)
+            .map(|individual| progress_function(population, individual, &opponents))
+            .collect()
+    }
+
+    fn finish_generation(
+        &mut self,
+        population: usize,
+        now: Instant,
+        mut progress: Vec<Progress>,
+    ) -> Evaluation {
+        let parameters = &self.parameters[population];
+        let statistics = &mut self.statistics[population];
+        statistics.time_stamp = unix_timestamp();
+        statistics.generation_seed =
+            generation_seed(parameters.setup.seed, statistics.num_generation);
+        statistics.num_generation += 1;
+        let evaluation_time = now.elapsed();
+        statistics.milliseconds_elapsed_evaluation = evaluation_time.as_millis();
+
+        if let Some(solution) = progress.iter().find_map(Progress::is_solution) {
+            return Evaluation::Solution(solution.clone());
+        }
+
+        let best = progress
+            .iter()
+            .enumerate()
+            .flat_map(|(index, progress)| {
+                progress
+                    .raw_fitness(&parameters.setup)
+                    .map(|raw| (index, raw.value()))
+            })
+            .filter(|(_, fitness)| !fitness.is_nan())
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index);
+        if let Some(best) = best {
+            let limit = match self.pairing {
+                Pairing::Champions(count) => count.max(self.champion_history),
+                _ => self.champion_history,
+            };
+            let champions = &mut self.champions[population];
+            champions.push(self.populations[population].individuals()[best].clone());
+            let excess = champions.len().saturating_sub(limit);
+            champions.drain(..excess);
+        }
+
+        statistics.population =
+            match self.populations[population].next_generation(parameters, &mut progress) {
+                Ok(population) => population,
+                Err(error) => return Evaluation::Error(error),
+            };
+        statistics.population.timings.evaluation = evaluation_time;
+        statistics.islands = self.populations[population].statistics().to_vec();
+        statistics.population.timings.total = now.elapsed();
+
+        Evaluation::Progress(statistics.clone())
+    }
+}
+
+impl Iterator for CoEvolutionRuntime {
+    // evaluation of every population that advanced this step by its index
+    type Item = Vec<(usize, Evaluation)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let count = self.populations.len();
+        if count == 0 {
+            return None;
+        }
+
+        let advancing: Vec<usize> = match self.schedule {
+            CoEvolutionSchedule::Simultaneous => (0..count).collect(),
+            CoEvolutionSchedule::Alternating => vec![self.step % count],
+        };
+        self.step += 1;
+
+        let opponents: Vec<Vec<Individual>> = (0..count)
+            .map(|population| {
+                if advancing == [population] {
+                    Vec::new()
+                } else {
+                    self.opponents(population)
+                }
+            })
+            .collect();
+
+        let evaluated: Vec<(usize, Instant, Vec<Progress>)> = advancing
+            .into_iter()
+            .map(|population| {
+                let now = Instant::now();
+                (population, now, self.evaluate(population, &opponents))
+            })
+            .collect();
+
+        Some(
+            evaluated
+                .into_iter()
+                .map(|(population, now, progress)| {
+                    (
+                        population,
+                        self.finish_generation(population, now, progress),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoEvolutionRuntime, CoEvolutionSchedule, Pairing};
+    use crate::{
+        parameters::Parameters,
+        runtime::{evaluation::Evaluation, progress::Progress},
+    };
+
+    fn parameters(seed: u64) -> Parameters {
+        let mut parameters = Parameters::default();
+        parameters.setup.seed = seed;
+        parameters.setup.population_size = 10;
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+        parameters.setup.survival_rate = 0.5;
+        parameters
+    }
+
+    #[test]
+    fn alternate_populations() {
+        let mut runtime = CoEvolutionRuntime::new(
+            vec![parameters(1), parameters(2)],
+            Box::new(|population, individual, opponents| {
+                // champions while there are any, random individuals before
+                assert!(!opponents.is_empty() && opponents.len() <= 3);
+                assert!(opponents
+                    .iter()
+                    .all(|opponent| opponent.population != population));
+                Progress::fitness(individual.len() as f64)
+            }),
+        )
+        .with_pairing(Pairing::Champions(3))
+        .with_schedule(CoEvolutionSchedule::Alternating);
+
+        for step in 0..3 {
+            let evaluations = runtime.next().unwrap();
+            assert_eq!(evaluations.len(), 1);
+            assert_eq!(evaluations[0].0, step % 2);
+            assert!(matches!(evaluations[0].1, Evaluation::Progress(_)));
+        }
+
+        assert_eq!(runtime.statistics(0).num_generation, 2);
+        assert_eq!(runtime.statistics(1).num_generation, 1);
+        assert_eq!(runtime.champions(0).len(), 2);
+        assert_eq!(runtime.population(1).len(), 10);
+    }
+
+    #[test]
+    fn keep_latest_champions() {
+        let mut runtime = CoEvolutionRuntime::new(
+            vec![parameters(1), parameters(2)],
+            Box::new(|_, individual, _| Progress::fitness(individual.len() as f64)),
+        )
+        .with_champion_history(2);
+
+        for _ in 0..3 {
+            runtime.next().unwrap();
+        }
+
+        assert_eq!(runtime.champions(0).len(), 2);
+        assert_eq!(runtime.champions(1).len(), 2);
+    }
+}
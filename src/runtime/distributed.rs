@@ -0,0 +1,196 @@
+use std::{
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    process::{Command, Stdio},
+    thread,
+};
+
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{error::NeatError, individual::Individual};
+
+use super::progress::Progress;
+
+// a worker receives newline delimited json individuals and answers with one json progress per line
+#[derive(Debug, Clone)]
+pub enum Worker {
+    // address of a process running `serve`
+    Tcp(String),
+    // program and arguments of a process running `serve_stdio`
+    Process(String, Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct DistributedEvaluator {
+    workers: Vec<Worker>,
+}
+
+impl DistributedEvaluator {
+    pub fn new(workers: Vec<Worker>) -> Result<Self, NeatError> {
+        if workers.is_empty() {
+            return Err(NeatError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "distributed evaluation needs workers",
+            )));
+        }
+        Ok(Self { workers })
+    }
+
+    pub fn evaluate(&self, individuals: &[Individual]) -> io::Result<Vec<Progress>> {
+        // distribute individuals round robin over workers
+        let mut batches: Vec<Vec<usize>> = vec![Vec::new(); self.workers.len()];
+        for index in 0..individuals.len() {
+            batches[index % self.workers.len()].push(index);
+        }
+
+        let results = self
+            .workers
+            .iter()
+            .zip(batches.iter())
+            .collect::<Vec<_>>()
+            .par_iter()
+            .map(|(worker, batch)| {
+                let batch = batch.iter().map(|&index| &individuals[index]);
+                match worker {
+                    Worker::Tcp(address) => evaluate_via_tcp(address, batch),
+                    Worker::Process(program, arguments) => {
+                        evaluate_via_process(program, arguments, batch)
+                    }
+                }
+            })
+            .collect::<io::Result<Vec<Vec<Progress>>>>()?;
+
+        // restore original order of individuals
+        let mut progress: Vec<Option<Progress>> = (0..individuals.len()).map(|_| None).collect();
+        for (batch, batch_progress) in batches.iter().zip(results) {
+            if batch.len() != batch_progress.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "worker returned incomplete progress",
+                ));
+            }
+            for (&index, single_progress) in batch.iter().zip(batch_progress) {
+                progress[index] = Some(single_progress);
+            }
+        }
+
+        Ok(progress.into_iter().flatten().collect())
+    }
+}
+
+fn evaluate_via_tcp<'a>(
+    address: &str,
+    individuals: impl Iterator<Item = &'a Individual>,
+) -> io::Result<Vec<Progress>> {
+    let stream = TcpStream::connect(address)?;
+
+    let mut writer = stream.try_clone()?;
+    let batch = serialize_individuals(individuals)?;
+    // the worker answers while the batch is still being sent, reading meanwhile keeps both buffers from filling up
+    let sender = thread::spawn(move || {
+        writer.write_all(&batch)?;
+        // signal end of batch
+        writer.shutdown(Shutdown::Write)
+    });
+
+    let progress = receive_progress(BufReader::new(stream));
+    join_sender(sender)?;
+    progress
+}
+
+fn evaluate_via_process<'a>(
+    program: &str,
+    arguments: &[String],
+    individuals: impl Iterator<Item = &'a Individual>,
+) -> io::Result<Vec<Progress>> {
+    let mut child = Command::new(program)
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().expect("stdin of worker is piped");
+    let batch = serialize_individuals(individuals)?;
+    // written from another thread as in evaluate_via_tcp, dropping stdin after sending signals end of batch
+    let sender = thread::spawn(move || stdin.write_all(&batch));
+
+    let progress = receive_progress(BufReader::new(
+        child.stdout.take().expect("stdout of worker is piped"),
+    ));
+    join_sender(sender)?;
+    let progress = progress?;
+
+    child.wait()?;
+
+    Ok(progress)
+}
+
+// newline delimited json of every individual
+fn serialize_individuals<'a>(
+    individuals: impl Iterator<Item = &'a Individual>,
+) -> io::Result<Vec<u8>> {
+    let mut batch = Vec::new();
+    for individual in individuals {
+        serde_json::to_writer(&mut batch, individual)?;
+        batch.push(b'\n');
+    }
+    Ok(batch)
+}
+
+fn join_sender(sender: thread::JoinHandle<io::Result<()>>) -> io::Result<()> {
+    sender
+        .join()
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "sending batch panicked"))?
+}
+
+fn receive_progress(reader: impl BufRead) -> io::Result<Vec<Progress>> {
+    reader
+        .lines()
+        .map(|line| serde_json::from_str(&line?).map_err(io::Error::from))
+        .collect()
+}
+
+// custom activations arrive by name, progress functions resolve them via Genome::resolve_activations
+fn handle_batch(
+    reader: impl BufRead,
+    mut writer: impl Write,
+    progress_function: &dyn Fn(&Individual) -> Progress,
+) -> io::Result<()> {
+    for line in reader.lines() {
+        let individual: Individual = serde_json::from_str(&line?)?;
+        serde_json::to_writer(&mut writer, &progress_function(&individual))?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+// worker side, evaluates batches sent over tcp connections
+pub fn serve(
+    address: impl ToSocketAddrs,
+    progress_function: &dyn Fn(&Individual) -> Progress,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+
+    // a failing connection only loses its own batch, the runtime sees it as an incomplete answer
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| {
+            handle_batch(
+                BufReader::new(stream.try_clone()?),
+                BufWriter::new(stream),
+                progress_function,
+            )
+        });
+        if let Err(error) = result {
+            eprintln!("worker connection failed: {}", error);
+        }
+    }
+    Ok(())
+}
+
+// worker side, evaluates one batch read from stdin and answers on stdout
+pub fn serve_stdio(progress_function: &dyn Fn(&Individual) -> Progress) -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    handle_batch(stdin.lock(), stdout.lock(), progress_function)
+}
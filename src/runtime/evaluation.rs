@@ -1,6 +1,39 @@
-use crate::{individual::Individual, utility::statistics::Statistics};
+use std::{error::Error, fmt};
+
+use crate::{error::NeatError, individual::Individual, utility::statistics::Statistics};
+
+// handed to contextual progress functions, see Neat::from_parameters_with_context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvaluationContext {
+    // number of the generation being evaluated, starting at zero
+    pub generation: usize,
+    // same for every individual of the generation, i.e. to share start states of a simulation
+    pub seed: u64,
+    // index of the repeated evaluation, see parameters.setup.trials_per_individual
+    pub trial: usize,
+}
 
 pub enum Evaluation {
     Progress(Statistics),
     Solution(Individual),
+    Error(NeatError),
+    // last evaluation once parameters.setup.max_evaluations is reached, holds the fittest individual so far
+    BudgetExhausted(Option<Individual>),
 }
+
+#[derive(Debug, Clone)]
+pub struct EvalError(pub String);
+
+impl EvalError {
+    pub fn new(message: impl Into<String>) -> Self {
+        EvalError(message.into())
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "evaluation failed: {}", self.0)
+    }
+}
+
+impl Error for EvalError {}
@@ -1,48 +1,906 @@
-use std::time::{Instant, SystemTime};
+use std::{
+    borrow::Cow,
+    iter,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use crate::{
-    individual::Individual, population::Population, utility::statistics::Statistics, Neat,
+    error::NeatError,
+    genes::IdGenerator,
+    individual::{
+        behavior::{Behavior, Behaviors},
+        genealogy::Genealogy,
+        scores::ScoreValue,
+        Individual,
+    },
+    parameters::{EvaluationErrorPolicy, Parameters, Setup, SolutionSelection, WeightDecay},
+    population::islands::Islands,
+    utility::{
+        rng::generation_seed,
+        statistics::{History, Statistics},
+        time::{unix_timestamp, Instant},
+    },
+    Neat, ProgressFunction,
 };
 
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "async")]
+use futures::StreamExt;
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-use self::{evaluation::Evaluation, progress::Progress};
+use self::{
+    artifacts::RunArtifacts,
+    cache::EvaluationCache,
+    checkpoint::{Archive, Checkpoint},
+    evaluation::{EvalError, Evaluation, EvaluationContext},
+    progress::Progress,
+    report::RunReport,
+};
 
+pub mod artifacts;
+mod cache;
+pub mod checkpoint;
+pub mod coevolution;
+#[cfg(feature = "distributed")]
+pub mod distributed;
 pub mod evaluation;
 pub mod progress;
+pub mod report;
+
+// called once per generation with the novelty archive of every island and latest statistics
+pub type ArchiveHook<'a> = Box<dyn FnMut(&mut Vec<Individual>, &Statistics) + Send + 'a>;
+
+// called once per generation with the generation number, changes apply to the upcoming reproduction
+pub type ParameterSchedule<'a> = Box<dyn FnMut(usize, &mut Parameters) + Send + 'a>;
+
+// called once per generation with the generation number, returns the weight perturbation std dev
+// to use from then on, replaces parameters.mutation.weight_decay
+pub type WeightStdDevSchedule<'a> = Box<dyn FnMut(usize) -> f64 + Send + 'a>;
+
+// called whenever an individual finished evaluating with the number of finished and total evaluations
+// of the current generation, possibly from several threads at once
+pub type EvaluationCallback<'a> = Box<dyn Fn(usize, usize) + Send + Sync + 'a>;
+
+// borrowed or shared Neat, the latter allows an owned Runtime<'static>
+pub enum NeatHandle<'a> {
+    Borrowed(&'a Neat),
+    Shared(Arc<Neat>),
+}
+
+impl<'a> Deref for NeatHandle<'a> {
+    type Target = Neat;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            NeatHandle::Borrowed(neat) => neat,
+            NeatHandle::Shared(neat) => neat,
+        }
+    }
+}
+
+impl<'a> From<&'a Neat> for NeatHandle<'a> {
+    fn from(neat: &'a Neat) -> Self {
+        NeatHandle::Borrowed(neat)
+    }
+}
+
+impl<'a> From<Arc<Neat>> for NeatHandle<'a> {
+    fn from(neat: Arc<Neat>) -> Self {
+        NeatHandle::Shared(neat)
+    }
+}
+
+// can be stored in structs or moved into threads and tasks
+pub type OwnedRuntime = Runtime<'static>;
 
 pub struct Runtime<'a> {
-    neat: &'a Neat,
-    population: Population,
+    neat: NeatHandle<'a>,
+    parameters: Arc<Parameters>,
+    population: Islands,
     statistics: Statistics,
+    cache: EvaluationCache,
+    archive_hook: Option<ArchiveHook<'a>>,
+    parameter_schedule: Option<ParameterSchedule<'a>>,
+    weight_std_dev_schedule: Option<WeightStdDevSchedule<'a>>,
+    evaluation_callback: Option<EvaluationCallback<'a>>,
+    progress: Vec<Progress>,
+    // only filled once record_history was called
+    history: History,
+    record_history: bool,
+    // bookkeeping of the run report, see Runtime::finalize
+    started: Instant,
+    evaluations: AtomicUsize,
+    best: Option<(f64, Individual)>,
+    solution: Option<Individual>,
+    artifacts: Option<RunArtifacts>,
+    // custom activations that could not be resolved, reported by the first call of next
+    unresolved: Option<NeatError>,
+}
+
+// counts finished evaluations of one generation and every call of the progress function of the run
+struct EvaluationCounter<'c> {
+    callback: Option<&'c (dyn Fn(usize, usize) + Send + Sync)>,
+    finished: AtomicUsize,
+    total: usize,
+    calls: &'c AtomicUsize,
+}
+
+impl<'c> EvaluationCounter<'c> {
+    // once per call of the progress function, retries included, see parameters.setup.max_evaluations
+    fn add_calls(&self, count: usize) {
+        self.calls.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn finish(&self) {
+        if let Some(callback) = self.callback {
+            callback(
+                self.finished.fetch_add(1, Ordering::Relaxed) + 1,
+                self.total,
+            );
+        }
+    }
 }
 
 impl<'a> Runtime<'a> {
-    pub fn new(neat: &'a Neat) -> Self {
+    pub fn new(neat: impl Into<NeatHandle<'a>>) -> Self {
+        let neat = neat.into();
+        let population = Islands::new(&neat.parameters);
+        Self::with_population(neat, population, 0)
+    }
+
+    pub fn from_seed(neat: impl Into<NeatHandle<'a>>, seed: &Individual) -> Self {
+        let neat = neat.into();
+        let mut seed = seed.clone();
+        let resolved = seed.resolve_activations(&neat.activations);
+        let population = Islands::from_seed(&seed, &neat.parameters);
+        Self::with_population(neat, population, 0).unless_unresolved(resolved)
+    }
+
+    pub fn from_checkpoint(neat: impl Into<NeatHandle<'a>>, mut checkpoint: Checkpoint) -> Self {
+        let neat = neat.into();
+        let num_generation = checkpoint.num_generation;
+        let resolved = checkpoint.resolve_activations(&neat.activations);
+        let population = Islands::from_checkpoint(checkpoint, &neat.parameters);
+        Self::with_population(neat, population, num_generation).unless_unresolved(resolved)
+    }
+
+    fn with_population(neat: NeatHandle<'a>, population: Islands, num_generation: usize) -> Self {
+        // parameters naming custom activations that were never registered
+        let unresolved = neat
+            .parameters
+            .activations
+            .clone()
+            .resolve(&neat.activations)
+            .err();
+
         Self {
+            parameters: neat.parameters(),
+            statistics: Statistics {
+                num_generation,
+                seed: neat.parameters.setup.seed,
+                ..Default::default()
+            },
+            cache: EvaluationCache::new(neat.parameters.setup.evaluation_cache_size),
             neat,
-            population: Population::new(&neat.parameters),
-            statistics: Statistics::default(),
+            population,
+            archive_hook: None,
+            parameter_schedule: None,
+            weight_std_dev_schedule: None,
+            evaluation_callback: None,
+            progress: Vec::new(),
+            history: History::default(),
+            record_history: false,
+            started: Instant::now(),
+            evaluations: AtomicUsize::new(0),
+            best: None,
+            solution: None,
+            artifacts: None,
+            unresolved,
         }
     }
 
-    fn generate_progress(&self) -> Vec<Progress> {
-        let progress_fn = &self.neat.progress_function;
+    fn unless_unresolved(mut self, resolved: Result<(), NeatError>) -> Self {
+        self.unresolved = self.unresolved.or(resolved.err());
+        self
+    }
 
-        // apply progress function to every individual
-        self.population
-            .individuals()
-            .par_iter()
-            .map(progress_fn)
-            .collect::<Vec<Progress>>()
+    // captures population, archive and innovation table
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.population.checkpoint(self.statistics.num_generation)
+    }
+
+    // summary of the run so far, whether a solution was found or iteration was stopped early
+    pub fn finalize(mut self) -> RunReport {
+        // last champion and checkpoint, failures are kept in the statistics of the report
+        if let Some(Err(error)) = self
+            .artifacts
+            .as_ref()
+            .map(|artifacts| self.save_champion_and_checkpoint(artifacts))
+        {
+            self.statistics.artifact_error = Some(error.to_string());
+        }
+
+        let solved = self.solution.is_some();
+        let best = self.best.map(|(_, individual)| individual);
+        RunReport {
+            best: self.solution.or(best),
+            solved,
+            generations: self.statistics.num_generation,
+            evaluations: self.evaluations.into_inner(),
+            wall_time: self.started.elapsed(),
+            archive: Archive(self.population.export_archive()),
+            statistics: self.statistics,
+        }
+    }
+
+    // novelty archive of every island, i.e. to be imported by a subsequent run
+    pub fn export_archive(&self) -> Archive {
+        Archive(self.population.export_archive())
+    }
+
+    // added to the archive of every island, behaviors must be comparable to those of this run
+    pub fn import_archive(&mut self, archive: Archive) {
+        self.population.import_archive(archive.0);
+    }
+
+    // individuals from outside the run are evaluated in the next generation along with the offspring,
+    // returns their new ids, see Islands::inject
+    pub fn inject(&mut self, mut individuals: Vec<Individual>) -> Result<Vec<usize>, NeatError> {
+        for individual in &mut individuals {
+            individual.resolve_activations(&self.neat.activations)?;
+        }
+        self.population.inject(individuals, &self.parameters)
+    }
+
+    // allows custom curation of the novelty archive, i.e. adding or removing entries
+    pub fn set_archive_hook(&mut self, hook: ArchiveHook<'a>) {
+        self.archive_hook = Some(hook);
+    }
+
+    // saves parameters, statistics, champions and checkpoints of the run into directory, see RunArtifacts,
+    // the last champion and checkpoint are saved by finalize
+    pub fn set_artifact_directory(
+        &mut self,
+        directory: &str,
+        interval: usize,
+    ) -> Result<(), NeatError> {
+        self.artifacts = Some(RunArtifacts::new(directory, interval, &self.parameters)?);
+        Ok(())
+    }
+
+    // results of the last evaluation, in order of the individuals evaluated before reproduction
+    pub fn progress(&self) -> &[Progress] {
+        &self.progress
+    }
+
+    // survivors followed by offspring, i.e. the individuals evaluated next
+    pub fn population(&self) -> &[Individual] {
+        self.population.individuals()
+    }
+
+    // parents of every individual, empty unless parameters.setup.track_lineage is set
+    pub fn genealogy(&self) -> &Genealogy {
+        self.population.genealogy()
+    }
+
+    // shared id space of the population, ids taken from here never collide with evolved ones
+    pub fn id_generator(&self) -> &IdGenerator {
+        self.population.id_gen()
+    }
+
+    pub fn id_generator_mut(&mut self) -> &mut IdGenerator {
+        self.population.id_gen_mut()
+    }
+
+    // statistics of the last completed generation
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    // keeps statistics of every upcoming generation, including snapshots and top performers
+    pub fn record_history(&mut self) {
+        self.record_history = true;
+    }
+
+    // statistics of every generation completed since record_history was called
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn parameters(&self) -> Arc<Parameters> {
+        Arc::clone(&self.parameters)
+    }
+
+    // changes apply between generations, the parameters of the Neat instance stay untouched
+    pub fn update_parameters(&mut self, update: impl FnOnce(&mut Parameters)) {
+        update(Arc::make_mut(&mut self.parameters));
+    }
+
+    pub fn set_parameter_schedule(&mut self, schedule: ParameterSchedule<'a>) {
+        self.parameter_schedule = Some(schedule);
+    }
+
+    // i.e. anneal from exploration to fine-tuning by a curve not covered by WeightDecay
+    pub fn set_weight_std_dev_schedule(&mut self, schedule: WeightStdDevSchedule<'a>) {
+        self.weight_std_dev_schedule = Some(schedule);
+    }
+
+    // i.e. to drive a progress bar, survivors that keep their progress are not counted
+    pub fn set_evaluation_callback(&mut self, callback: EvaluationCallback<'a>) {
+        self.evaluation_callback = Some(callback);
+    }
+
+    // generation about to be evaluated, its seed is fixed once the generation started
+    pub fn evaluation_context(&self) -> EvaluationContext {
+        EvaluationContext {
+            generation: self.statistics.num_generation,
+            seed: self.statistics.generation_seed,
+            trial: 0,
+        }
+    }
+
+    fn evaluation_counter(&self, total: usize) -> EvaluationCounter<'_> {
+        EvaluationCounter {
+            callback: self.evaluation_callback.as_deref(),
+            finished: AtomicUsize::new(0),
+            total,
+            calls: &self.evaluations,
+        }
+    }
+
+    // awaits all evaluations of a generation concurrently, works with any executor (i.e. tokio)
+    #[cfg(feature = "async")]
+    pub async fn next_async(&mut self) -> Option<Evaluation> {
+        if let Some(error) = self.unresolved.take() {
+            return Some(Evaluation::Error(error));
+        }
+
+        if self.budget_exhausted() {
+            return None;
+        }
+
+        let now = self.start_generation();
+
+        // generate progress by awaiting progress function for every individual
+        let progress = self.generate_progress_async().await;
+
+        Some(self.finish_generation(now, progress))
+    }
+
+    // survivors keep their recorded progress unless due for reevaluation, see Setup::reevaluates_survivors
+    fn split_evaluated(&self) -> (Vec<Progress>, &[Individual]) {
+        let skip = if self
+            .parameters
+            .setup
+            .reevaluates_survivors(self.statistics.num_generation)
+        {
+            0
+        } else {
+            self.population.survivors()
+        };
+        let (evaluated, pending) = self.population.individuals().split_at(skip);
+        (
+            evaluated
+                .iter()
+                .map(Individual::recorded_progress)
+                .collect(),
+            pending,
+        )
+    }
+
+    fn generate_progress(&self) -> Result<Vec<Progress>, NeatError> {
+        let (mut progress, pending) = self.split_evaluated();
+        let expressed = Self::expressed(pending);
+        let lookup = self.cache.lookup(&expressed);
+        let repeated = self.repeated(&lookup.uncached);
+        let trials = self.evaluate(&repeated)?;
+        progress.extend(self.cache.resolve(lookup, self.combine_trials(trials)));
+        Ok(progress)
+    }
+
+    fn trials(&self) -> usize {
+        self.parameters.setup.trials_per_individual.max(1)
+    }
+
+    // every individual once per trial, trials of an individual are adjacent
+    fn repeated<'i>(&self, individuals: &'i [Individual]) -> Cow<'i, [Individual]> {
+        let trials = self.trials();
+        if trials == 1 {
+            return Cow::Borrowed(individuals);
+        }
+
+        Cow::Owned(
+            individuals
+                .iter()
+                .flat_map(|individual| iter::repeat(individual).take(trials))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    // one progress per individual, see Progress::from_trials
+    fn combine_trials(&self, progress: Vec<Progress>) -> Vec<Progress> {
+        let trials = self.trials();
+        if trials == 1 {
+            return progress;
+        }
+
+        let mut progress = progress.into_iter();
+        let mut combined = Vec::new();
+        loop {
+            let individual_trials: Vec<Progress> = progress.by_ref().take(trials).collect();
+            if individual_trials.is_empty() {
+                break;
+            }
+            combined.push(Progress::from_trials(
+                individual_trials,
+                &self.parameters.setup,
+            ));
+        }
+        combined
+    }
+
+    // parallel connections are summed into their feed forward connection and disabled ones dropped before evaluation
+    fn expressed(individuals: &[Individual]) -> Cow<'_, [Individual]> {
+        if individuals
+            .iter()
+            .all(|individual| individual.parallel.is_empty() && individual.disabled.is_empty())
+        {
+            return Cow::Borrowed(individuals);
+        }
+
+        Cow::Owned(
+            individuals
+                .iter()
+                .map(|individual| Individual {
+                    genome: individual.genome.expressed(),
+                    ..individual.clone()
+                })
+                .collect(),
+        )
+    }
+
+    #[cfg(feature = "parallel")]
+    fn iterate(individuals: &[Individual]) -> rayon::slice::Iter<'_, Individual> {
+        individuals.par_iter()
+    }
+
+    // single-threaded fallback, i.e. for wasm
+    #[cfg(not(feature = "parallel"))]
+    fn iterate(individuals: &[Individual]) -> std::slice::Iter<'_, Individual> {
+        individuals.iter()
+    }
+
+    // runs on the dedicated pool of Neat if there is one
+    #[cfg(feature = "parallel")]
+    fn install<R: Send>(&self, evaluation: impl FnOnce() -> R + Send) -> R {
+        match &self.neat.thread_pool {
+            Some(thread_pool) => thread_pool.install(evaluation),
+            None => evaluation(),
+        }
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn install<R>(&self, evaluation: impl FnOnce() -> R) -> R {
+        evaluation()
+    }
+
+    fn evaluate(&self, individuals: &[Individual]) -> Result<Vec<Progress>, NeatError> {
+        let seed = self.statistics.generation_seed;
+        self.evaluate_with_seeds(individuals, move |_| seed)
+    }
+
+    // seeds handed to contextual progress functions by the index of the repeated evaluation,
+    // i.e. to verify a solution on unseen start states
+    fn evaluate_with_seeds(
+        &self,
+        individuals: &[Individual],
+        seed: impl Fn(usize) -> u64 + Sync,
+    ) -> Result<Vec<Progress>, NeatError> {
+        let counter = self.evaluation_counter(individuals.len());
+
+        match &self.neat.progress_function {
+            ProgressFunction::Blocking(progress_fn) => {
+                // apply progress function to every individual
+                Ok(self.install(|| {
+                    Self::iterate(individuals)
+                        .map(|individual| {
+                            counter.add_calls(1);
+                            let progress = progress_fn(individual);
+                            counter.finish();
+                            progress
+                        })
+                        .collect::<Vec<Progress>>()
+                }))
+            }
+            ProgressFunction::Contextual(progress_fn) => {
+                let context = self.evaluation_context();
+                let trials = self.trials();
+                Ok(self.install(|| {
+                    Self::iterate(individuals)
+                        .enumerate()
+                        .map(|(index, individual)| {
+                            let context = EvaluationContext {
+                                seed: seed(index / trials),
+                                trial: index % trials,
+                                ..context
+                            };
+                            counter.add_calls(1);
+                            let progress = progress_fn(individual, &context);
+                            counter.finish();
+                            progress
+                        })
+                        .collect::<Vec<Progress>>()
+                }))
+            }
+            ProgressFunction::Fallible(progress_fn) => {
+                let policy = self.parameters.setup.evaluation_error_policy;
+
+                // apply progress function to every individual, retrying if allowed
+                let results = self.install(|| {
+                    Self::iterate(individuals)
+                        .map(|individual| {
+                            counter.add_calls(1);
+                            let mut result = progress_fn(individual);
+                            if let EvaluationErrorPolicy::Retry(retries) = policy {
+                                for _ in 0..retries {
+                                    if result.is_ok() {
+                                        break;
+                                    }
+                                    counter.add_calls(1);
+                                    result = progress_fn(individual);
+                                }
+                            }
+                            counter.finish();
+                            result
+                        })
+                        .collect::<Vec<Result<Progress, EvalError>>>()
+                });
+
+                Self::resolve_errors(results, policy, &self.parameters.setup)
+            }
+            #[cfg(feature = "async")]
+            ProgressFunction::Async(_) => {
+                futures::executor::block_on(self.evaluate_async(individuals))
+            }
+            #[cfg(feature = "distributed")]
+            ProgressFunction::Distributed(evaluator) => {
+                // workers report whole batches only
+                counter.add_calls(individuals.len());
+                let progress = evaluator.evaluate(individuals).map_err(NeatError::from);
+                for _ in individuals {
+                    counter.finish();
+                }
+                progress
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    async fn generate_progress_async(&self) -> Result<Vec<Progress>, NeatError> {
+        let (mut progress, pending) = self.split_evaluated();
+        let expressed = Self::expressed(pending);
+        let lookup = self.cache.lookup(&expressed);
+        let repeated = self.repeated(&lookup.uncached);
+        let trials = self.evaluate_async(&repeated).await?;
+        progress.extend(self.cache.resolve(lookup, self.combine_trials(trials)));
+        Ok(progress)
+    }
+
+    #[cfg(feature = "async")]
+    async fn evaluate_async(&self, individuals: &[Individual]) -> Result<Vec<Progress>, NeatError> {
+        match &self.neat.progress_function {
+            ProgressFunction::Async(progress_fn) => {
+                let counter = self.evaluation_counter(individuals.len());
+                let evaluations = individuals.iter().map(|individual| {
+                    counter.add_calls(1);
+                    let evaluation = progress_fn(individual);
+                    let counter = &counter;
+                    async move {
+                        let progress = evaluation.await;
+                        counter.finish();
+                        progress
+                    }
+                });
+
+                match self.neat.max_concurrency {
+                    Some(max_concurrency) => Ok(futures::stream::iter(evaluations)
+                        .buffered(max_concurrency)
+                        .collect()
+                        .await),
+                    None => Ok(futures::future::join_all(evaluations).await),
+                }
+            }
+            _ => self.evaluate(individuals),
+        }
+    }
+
+    fn resolve_errors(
+        results: Vec<Result<Progress, EvalError>>,
+        policy: EvaluationErrorPolicy,
+        setup: &Setup,
+    ) -> Result<Vec<Progress>, NeatError> {
+        match policy {
+            EvaluationErrorPolicy::Abort | EvaluationErrorPolicy::Retry(_) => results
+                .into_iter()
+                .collect::<Result<Vec<Progress>, EvalError>>()
+                .map_err(NeatError::from),
+            EvaluationErrorPolicy::AssignMinimum => {
+                let minimum_fitness = results
+                    .iter()
+                    .flatten()
+                    .flat_map(|progress| progress.raw_fitness(setup))
+                    .map(|raw| raw.value())
+                    .fold(None, |minimum: Option<f64>, fitness| {
+                        Some(minimum.map_or(fitness, |minimum| minimum.min(fitness)))
+                    });
+
+                Ok(results
+                    .into_iter()
+                    .map(|result| {
+                        result.unwrap_or_else(|_| {
+                            minimum_fitness.map_or(Progress::Empty, Progress::fitness)
+                        })
+                    })
+                    .collect())
+            }
+        }
     }
 
     fn check_for_solution(&self, progress: &[Progress]) -> Option<Individual> {
-        progress
+        let solutions: Vec<(usize, &Individual)> = progress
             .iter()
-            .filter_map(|p| p.is_solution())
-            .cloned()
+            .enumerate()
+            .filter_map(|(index, p)| p.is_solution().map(|solution| (index, solution)))
+            .collect();
+
+        let candidates = match self.parameters.setup.solution_selection {
+            SolutionSelection::First => solutions,
+            SolutionSelection::Simplest | SolutionSelection::SimplestThenMostNovel => {
+                let fewest_genes = solutions
+                    .iter()
+                    .map(|(_, solution)| solution.len())
+                    .min()
+                    .unwrap_or(0);
+                solutions
+                    .into_iter()
+                    .filter(|(_, solution)| solution.len() == fewest_genes)
+                    .collect()
+            }
+        };
+
+        let behaviors: Vec<&Behavior> = progress.iter().flat_map(|p| p.behavior()).collect();
+
+        // novelty is only comparable if every individual reported a behavior
+        if self.parameters.setup.solution_selection
+            == SolutionSelection::SimplestThenMostNovel
+            && candidates.len() > 1
+            && behaviors.len() == progress.len()
+        {
+            let nearest_neighbors = self.parameters.setup.nearest_neighbors(behaviors.len());
+            let novelties = Behaviors::from(behaviors)
+                .with_alignment(self.parameters.setup.behavior_alignment)
+                .with_reduction(self.parameters.setup.behavior_reduction)
+                .compute_novelty(nearest_neighbors);
+
+            // NaN novelty ranks last
+            let novelty = |index: usize| {
+                if novelties[index].is_nan() {
+                    f64::NEG_INFINITY
+                } else {
+                    novelties[index]
+                }
+            };
+            return candidates
+                .into_iter()
+                .max_by(|(index_0, _), (index_1, _)| {
+                    novelty(*index_0).total_cmp(&novelty(*index_1))
+                })
+                .map(|(_, solution)| solution.clone());
+        }
+
+        candidates
+            .into_iter()
             .next()
+            .map(|(_, solution)| solution.clone())
+    }
+
+    // repeats the evaluation of a reported solution, see parameters.setup.solution_verification
+    fn verify_solution(&mut self, winner: &Individual) -> Result<bool, NeatError> {
+        let verification = match self.parameters.setup.solution_verification {
+            Some(verification) => verification,
+            None => return Ok(true),
+        };
+
+        // every run on its own seed, with trials combined as in generate_progress
+        let runs = vec![winner.clone(); verification.evaluations];
+        let expressed = Self::expressed(&runs);
+        let repeated = self.repeated(&expressed);
+        let seed = self.statistics.generation_seed;
+        let trials =
+            self.evaluate_with_seeds(&repeated, move |run| generation_seed(seed, run + 1))?;
+        let successes = self
+            .combine_trials(trials)
+            .iter()
+            .filter(|progress| progress.is_solution().is_some())
+            .count();
+
+        let passes = verification.passes(successes);
+        self.statistics.rejected_solution = !passes;
+        Ok(passes)
+    }
+
+    // individual with the highest raw fitness so far, progress is in order of the individuals
+    fn record_best(&mut self, progress: &[Progress]) {
+        let setup = &self.parameters.setup;
+        let best = progress
+            .iter()
+            .enumerate()
+            .flat_map(|(index, progress)| {
+                progress.raw_fitness(setup).map(|raw| (index, raw.value()))
+            })
+            .filter(|(_, fitness)| !fitness.is_nan())
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let champion = &mut self.statistics.champion;
+        match best {
+            Some((index, fitness))
+                if self
+                    .best
+                    .as_ref()
+                    .map_or(true, |(best_fitness, _)| fitness > *best_fitness) =>
+            {
+                let individual = self.population.individuals()[index].clone();
+                champion.improvement = self
+                    .best
+                    .as_ref()
+                    .map_or(0.0, |(best_fitness, _)| fitness - best_fitness);
+                champion.id = individual.id;
+                champion.fitness = fitness;
+                champion.generations_since_improvement = 0;
+                self.best = Some((fitness, individual));
+            }
+            _ if self.best.is_some() => champion.generations_since_improvement += 1,
+            _ => {}
+        }
+    }
+
+    fn budget_exhausted(&self) -> bool {
+        self.parameters
+            .setup
+            .max_evaluations
+            .map_or(false, |max_evaluations| {
+                self.evaluations.load(Ordering::Relaxed) >= max_evaluations
+            })
+    }
+
+    fn start_generation(&mut self) -> Instant {
+        self.statistics.time_stamp = unix_timestamp();
+        self.statistics.generation_seed =
+            generation_seed(self.parameters.setup.seed, self.statistics.num_generation);
+        Instant::now()
+    }
+
+    fn finish_generation(
+        &mut self,
+        now: Instant,
+        progress: Result<Vec<Progress>, NeatError>,
+    ) -> Evaluation {
+        let mut progress = match progress {
+            Ok(progress) => progress,
+            Err(error) => return Evaluation::Error(error),
+        };
+
+        self.statistics.num_generation += 1;
+        self.statistics.evaluation_cache = self.cache.take_statistics();
+        let evaluation_time = now.elapsed();
+        self.statistics.milliseconds_elapsed_evaluation = evaluation_time.as_millis();
+
+        self.statistics.rejected_solution = false;
+        let solution = match self.check_for_solution(&progress) {
+            Some(winner) => match self.verify_solution(&winner) {
+                Ok(true) => Some(winner),
+                Ok(false) => None,
+                Err(error) => return Evaluation::Error(error),
+            },
+            None => None,
+        };
+
+        self.statistics.total_evaluations = self.evaluations.load(Ordering::Relaxed);
+
+        let evaluation = if let Some(winner) = solution {
+            self.solution = Some(winner.clone());
+            self.write_artifacts();
+            Evaluation::Solution(winner)
+        } else if self.budget_exhausted() {
+            self.record_best(&progress);
+            self.write_artifacts();
+            Evaluation::BudgetExhausted(
+                self.best.as_ref().map(|(_, individual)| individual.clone()),
+            )
+        } else {
+            self.record_best(&progress);
+
+            if let Some(schedule) = &mut self.parameter_schedule {
+                schedule(
+                    self.statistics.num_generation,
+                    Arc::make_mut(&mut self.parameters),
+                );
+            }
+
+            if let Some(schedule) = &mut self.weight_std_dev_schedule {
+                let mutation = &mut Arc::make_mut(&mut self.parameters).mutation;
+                mutation.weight_perturbation_std_dev = schedule(self.statistics.num_generation);
+                mutation.weight_decay = WeightDecay::None;
+            }
+
+            self.statistics.population = match self
+                .population
+                .next_generation(&self.parameters, &mut progress)
+            {
+                Ok(population) => population,
+                Err(error) => return Evaluation::Error(error),
+            };
+            self.statistics.population.timings.evaluation = evaluation_time;
+            self.statistics.islands = self.population.statistics().to_vec();
+
+            let archive_start = Instant::now();
+            if let Some(hook) = &mut self.archive_hook {
+                for archive in self.population.archives_mut() {
+                    hook(archive, &self.statistics);
+                }
+            }
+            self.statistics.population.timings.archive_hook = archive_start.elapsed();
+            self.statistics.population.timings.total = now.elapsed();
+            self.write_artifacts();
+
+            if self.record_history {
+                self.history.0.push(self.statistics.clone());
+            }
+
+            Evaluation::Progress(self.statistics.clone())
+        };
+
+        self.progress = progress;
+
+        evaluation
+    }
+
+    // failures are kept in the statistics, the evaluation of the generation stays valid
+    fn write_artifacts(&mut self) {
+        self.statistics.artifact_error = self
+            .try_write_artifacts()
+            .err()
+            .map(|error| error.to_string());
+    }
+
+    // statistics every generation, champion and checkpoint when due, see finalize for the end of the run
+    fn try_write_artifacts(&self) -> Result<(), NeatError> {
+        let artifacts = match &self.artifacts {
+            Some(artifacts) => artifacts,
+            None => return Ok(()),
+        };
+
+        artifacts.append_statistics(&self.statistics)?;
+        if artifacts.due(self.statistics.num_generation) {
+            self.save_champion_and_checkpoint(artifacts)?;
+        }
+        Ok(())
+    }
+
+    fn save_champion_and_checkpoint(&self, artifacts: &RunArtifacts) -> Result<(), NeatError> {
+        let champion = self
+            .solution
+            .as_ref()
+            .or_else(|| self.best.as_ref().map(|(_, individual)| individual));
+        if let Some(champion) = champion {
+            artifacts.save_champion(self.statistics.num_generation, champion)?;
+        }
+        artifacts.save_checkpoint(&self.checkpoint())
     }
 }
 
@@ -50,26 +908,161 @@ impl<'a> Iterator for Runtime<'a> {
     type Item = Evaluation;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.statistics.time_stamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let now = Instant::now();
+        if let Some(error) = self.unresolved.take() {
+            return Some(Evaluation::Error(error));
+        }
+
+        // the run ended with Evaluation::BudgetExhausted
+        if self.budget_exhausted() {
+            return None;
+        }
+
+        let now = self.start_generation();
 
         // generate progress by running progress function for every individual
         let progress = self.generate_progress();
 
-        self.statistics.num_generation += 1;
-        self.statistics.milliseconds_elapsed_evaluation = now.elapsed().as_millis();
+        Some(self.finish_generation(now, progress))
+    }
+}
 
-        if let Some(winner) = self.check_for_solution(&progress) {
-            Some(Evaluation::Solution(winner))
-        } else {
-            self.statistics.population = self
-                .population
-                .next_generation(&self.neat.parameters, &progress);
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    use super::evaluation::{EvalError, Evaluation, EvaluationContext};
+    use crate::{
+        error::NeatError,
+        genes::Activation,
+        parameters::{EvaluationErrorPolicy, Parameters, SolutionVerification},
+        Individual, Neat, Progress,
+    };
+
+    fn parameters() -> Parameters {
+        let mut parameters = Parameters::default();
+        parameters.setup.seed = 42;
+        parameters.setup.population_size = 10;
+        parameters.setup.input_dimension = 2;
+        parameters.setup.output_dimension = 1;
+        parameters.setup.survival_rate = 0.5;
+        parameters
+    }
+
+    #[test]
+    fn verify_solution_on_distinct_seeds() {
+        let mut parameters = parameters();
+        parameters.setup.trials_per_individual = 2;
+        parameters.setup.solution_verification = Some(SolutionVerification {
+            evaluations: 3,
+            success_rate: 1.0,
+        });
+
+        let contexts = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&contexts);
+        let neat = Neat::from_parameters_with_context(
+            parameters,
+            Box::new(move |individual, context: &EvaluationContext| {
+                recorded.lock().unwrap().push(*context);
+                Progress::fitness(1.0).solved(individual.clone())
+            }),
+        );
+
+        assert!(matches!(neat.run().next(), Some(Evaluation::Solution(_))));
 
-            Some(Evaluation::Progress(self.statistics.clone()))
+        // two trials of every individual, then two trials of every verification run
+        let contexts = contexts.lock().unwrap();
+        assert_eq!(contexts.len(), 10 * 2 + 3 * 2);
+        let verification = &contexts[20..];
+        let seeds: HashSet<u64> = verification.iter().map(|context| context.seed).collect();
+        assert_eq!(seeds.len(), 3);
+        assert!(!seeds.contains(&contexts[0].seed));
+        assert_eq!(
+            verification
+                .iter()
+                .filter(|context| context.trial == 1)
+                .count(),
+            3
+        );
+    }
+
+    fn progress(individual: &Individual) -> Progress {
+        Progress::fitness(individual.len() as f64)
+    }
+
+    #[test]
+    fn stop_when_budget_is_exhausted() {
+        let mut parameters = parameters();
+        parameters.setup.max_evaluations = Some(20);
+        let neat = Neat::from_parameters(parameters, Box::new(progress));
+
+        let mut runtime = neat.run();
+        assert!(matches!(runtime.next(), Some(Evaluation::Progress(_))));
+        assert!(matches!(
+            runtime.next(),
+            Some(Evaluation::BudgetExhausted(Some(_)))
+        ));
+        assert!(runtime.next().is_none());
+        assert_eq!(runtime.statistics().total_evaluations, 20);
+    }
+
+    #[test]
+    fn count_retried_evaluations() {
+        let mut parameters = parameters();
+        parameters.setup.evaluation_error_policy = EvaluationErrorPolicy::Retry(2);
+        let neat = Neat::from_parameters_fallible(
+            parameters,
+            Box::new(|_| Err(EvalError::new("always fails"))),
+        );
+
+        let mut runtime = neat.run();
+        assert!(matches!(runtime.next(), Some(Evaluation::Error(_))));
+        assert_eq!(runtime.finalize().evaluations, 10 * 3);
+    }
+
+    #[test]
+    fn track_champion() {
+        let neat = Neat::from_parameters(parameters(), Box::new(progress));
+
+        let mut previous = f64::NEG_INFINITY;
+        let mut unchanged = 0;
+        for evaluation in neat.run().take(5) {
+            let champion = match evaluation {
+                Evaluation::Progress(statistics) => statistics.champion,
+                _ => break,
+            };
+
+            assert!(champion.fitness >= previous);
+            if champion.fitness > previous {
+                if previous.is_finite() {
+                    assert!((champion.improvement - (champion.fitness - previous)).abs() < 1e-9);
+                }
+                unchanged = 0;
+            } else {
+                unchanged += 1;
+            }
+            assert_eq!(champion.generations_since_improvement, unchanged);
+            previous = champion.fitness;
         }
     }
+
+    #[test]
+    fn report_unknown_activation() {
+        let mut parameters = parameters();
+        parameters.activations.hidden_nodes =
+            vec![serde_json::from_str::<Activation>(r#"{"Custom":"softsign"}"#).unwrap()];
+
+        let neat = Neat::from_parameters(parameters.clone(), Box::new(progress));
+        assert!(matches!(
+            neat.run().next(),
+            Some(Evaluation::Error(NeatError::UnknownActivation(_)))
+        ));
+
+        let neat = Neat::from_parameters(parameters, Box::new(progress))
+            .with_activation("softsign", |x| x / (1.0 + x.abs()));
+        assert!(neat.parameters.activations.hidden_nodes[0].function()(1.0) == 0.5);
+        assert!(matches!(neat.run().next(), Some(Evaluation::Progress(_))));
+    }
 }
@@ -1,20 +1,54 @@
-use crate::individual::{
-    behavior::Behavior,
-    scores::{Fitness, Raw},
-    Individual,
+use std::{any::Any, collections::HashMap, fmt::Debug};
+
+use crate::{
+    individual::{
+        behavior::Behavior,
+        scores::{Fitness, Raw, ScoreValue},
+        Individual,
+    },
+    parameters::Setup,
 };
+use serde::{Deserialize, Serialize};
+
+// domain specific evaluation results, accessible after evaluation via Runtime::progress
+pub trait ProgressLike: Debug + Send + Sync + 'static {
+    fn fitness(&self) -> Option<f64>;
+    fn behavior(&self) -> Option<&Behavior>;
+    fn solution(&self) -> Option<&Individual> {
+        None
+    }
+    fn as_any(&self) -> &dyn Any;
+}
+
+// named fitness component
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Objective {
+    pub name: String,
+    pub value: f64,
+}
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Progress {
     Empty,
     Novelty(Behavior),
+    Fitness(Raw<Fitness>),
     Status(Raw<Fitness>, Behavior),
-    Solution(Option<Raw<Fitness>>, Option<Behavior>, Box<Individual>),
+    // wrapped progress solved the task with given individual
+    Solution(Box<Progress>, Box<Individual>),
+    // scalarized by parameters.setup.objective_weights or ranked by Selection::Pareto
+    MultiObjective(Vec<Objective>, Option<Behavior>),
+    // fitness of every output head, aggregated by parameters.setup.head_aggregation
+    PerHead(Vec<f64>, Option<Behavior>),
+    // wrapped progress did not meet the minimal criterion, i.e. must not reproduce
+    Unviable(Box<Progress>),
+    // custom results are evaluated locally only
+    #[serde(skip)]
+    Custom(Box<dyn ProgressLike>),
 }
 
 impl Progress {
     pub fn new(fitness: f64, behavior: Vec<f64>) -> Self {
-        Progress::Status(Raw::fitness(fitness), Behavior(behavior))
+        Progress::Status(Raw::fitness(fitness), Behavior::from(behavior))
     }
 
     pub fn empty() -> Self {
@@ -23,45 +57,347 @@ impl Progress {
 
     pub fn solved(self, solution: Individual) -> Self {
         match self {
-            Progress::Novelty(behavior) => {
-                Progress::Solution(None, Some(behavior), Box::new(solution))
-            }
-            Progress::Status(fitness, behavior) => {
-                Progress::Solution(Some(fitness), Some(behavior), Box::new(solution))
-            }
-            Progress::Solution(fitness, behavior, _) => {
-                Progress::Solution(fitness, behavior, Box::new(solution))
+            Progress::Solution(progress, _) => Progress::Solution(progress, Box::new(solution)),
+            Progress::Unviable(progress) => progress.solved(solution),
+            // custom results are kept as their fitness and behavior, see Progress::cached
+            progress @ Progress::Custom(_) => {
+                Progress::Solution(Box::new(progress.cached()), Box::new(solution))
             }
-            Progress::Empty => Progress::Solution(None, None, Box::new(solution)),
+            progress => Progress::Solution(Box::new(progress), Box::new(solution)),
+        }
+    }
+
+    // flag whether the minimal criterion is met
+    pub fn viable(self, meets_minimal_criterion: bool) -> Self {
+        match (self, meets_minimal_criterion) {
+            (Progress::Unviable(progress), true) => *progress,
+            (progress @ Progress::Unviable(_), false) => progress,
+            (progress, true) => progress,
+            (progress, false) => Progress::Unviable(Box::new(progress)),
+        }
+    }
+
+    pub fn is_viable(&self) -> bool {
+        !matches!(self, Progress::Unviable(_))
+    }
+
+    pub fn multi_objective(objectives: Vec<(&str, f64)>, behavior: Option<Vec<f64>>) -> Self {
+        Progress::MultiObjective(
+            objectives
+                .into_iter()
+                .map(|(name, value)| Objective {
+                    name: name.to_owned(),
+                    value,
+                })
+                .collect(),
+            behavior.map(Behavior::from),
+        )
+    }
+
+    pub fn objectives(&self) -> Option<&[Objective]> {
+        match self {
+            Progress::MultiObjective(objectives, _) => Some(objectives),
+            Progress::Solution(progress, _) => progress.objectives(),
+            Progress::Unviable(progress) => progress.objectives(),
+            _ => None,
+        }
+    }
+
+    // see Genome::split_heads for the outputs of each head
+    pub fn per_head(fitnesses: Vec<f64>, behavior: Option<Vec<f64>>) -> Self {
+        Progress::PerHead(fitnesses, behavior.map(Behavior::from))
+    }
+
+    pub fn head_fitnesses(&self) -> Option<&[f64]> {
+        match self {
+            Progress::PerHead(fitnesses, _) => Some(fitnesses),
+            Progress::Solution(progress, _) => progress.head_fitnesses(),
+            Progress::Unviable(progress) => progress.head_fitnesses(),
+            _ => None,
+        }
+    }
+
+    // weighted sum of objectives, objectives without weight count once
+    pub fn scalarized_fitness(&self, weights: &HashMap<String, f64>) -> Option<Raw<Fitness>> {
+        self.objectives()
+            .map(|objectives| scalarize(objectives, weights))
+    }
+
+    pub fn custom(progress: impl ProgressLike) -> Self {
+        Self::Custom(Box::new(progress))
+    }
+
+    // access the custom result if it is of given type
+    pub fn downcast_ref<T: ProgressLike>(&self) -> Option<&T> {
+        match self {
+            Progress::Custom(custom) => custom.as_any().downcast_ref(),
+            Progress::Unviable(progress) => progress.downcast_ref(),
+            _ => None,
         }
     }
 
     pub fn novelty(behavior: Vec<f64>) -> Self {
-        Self::Novelty(Behavior(behavior))
+        Self::Novelty(Behavior::from(behavior))
+    }
+
+    pub fn fitness(fitness: f64) -> Self {
+        Self::Fitness(Raw::fitness(fitness))
     }
 
     pub fn behavior(&self) -> Option<&Behavior> {
         match self {
             Progress::Status(_, behavior) => Some(behavior),
-            Progress::Solution(_, behavior, _) => behavior.as_ref(),
+            Progress::Solution(progress, _) => progress.behavior(),
             Progress::Novelty(behavior) => Some(behavior),
+            Progress::Fitness(_) => None,
             Progress::Empty => None,
+            Progress::MultiObjective(_, behavior) => behavior.as_ref(),
+            Progress::PerHead(_, behavior) => behavior.as_ref(),
+            Progress::Custom(custom) => custom.behavior(),
+            Progress::Unviable(progress) => progress.behavior(),
         }
     }
 
-    pub fn raw_fitness(&self) -> Option<Raw<Fitness>> {
-        match *self {
-            Progress::Status(fitness, _) => Some(fitness),
-            Progress::Solution(fitness, _, _) => fitness,
+    // heads aggregated by setup.head_aggregation, objectives scalarized by setup.objective_weights
+    pub fn raw_fitness(&self, setup: &Setup) -> Option<Raw<Fitness>> {
+        match self {
+            Progress::Status(fitness, _) => Some(*fitness),
+            Progress::Solution(progress, _) => progress.raw_fitness(setup),
+            Progress::Fitness(fitness) => Some(*fitness),
             Progress::Novelty(_) => None,
             Progress::Empty => None,
+            Progress::MultiObjective(objectives, _) => {
+                Some(scalarize(objectives, &setup.objective_weights))
+            }
+            Progress::PerHead(fitnesses, _) => {
+                Some(Raw::fitness(setup.head_aggregation.aggregate(fitnesses)))
+            }
+            Progress::Custom(custom) => custom.fitness().map(Raw::fitness),
+            Progress::Unviable(progress) => progress.raw_fitness(setup),
+        }
+    }
+
+    // combines repeated evaluations of one individual, see parameters.setup.trials_per_individual,
+    // objectives are combined by name and head fitnesses one by one, solved only if every trial is
+    pub fn from_trials(mut trials: Vec<Progress>, setup: &Setup) -> Self {
+        if trials.len() < 2 {
+            return trials.pop().unwrap_or(Progress::Empty);
+        }
+
+        let combine = |fitnesses: Vec<f64>| setup.trial_fitness.aggregate(&fitnesses);
+
+        let behaviors: Vec<Vec<f64>> = trials
+            .iter()
+            .filter_map(Progress::behavior)
+            .map(Behavior::values)
+            .collect();
+        let behavior = if behaviors.is_empty() {
+            None
+        } else {
+            Some(setup.trial_behavior.aggregate(&behaviors))
+        };
+
+        let combined = if let Some(objectives) = trials[0].objectives() {
+            Progress::MultiObjective(
+                objectives
+                    .iter()
+                    .map(|objective| Objective {
+                        name: objective.name.clone(),
+                        value: combine(
+                            trials
+                                .iter()
+                                .filter_map(|trial| {
+                                    trial
+                                        .objectives()?
+                                        .iter()
+                                        .find(|other| other.name == objective.name)
+                                })
+                                .map(|objective| objective.value)
+                                .collect(),
+                        ),
+                    })
+                    .collect(),
+                behavior.map(Behavior::from),
+            )
+        } else if let Some(fitnesses) = trials[0].head_fitnesses() {
+            Progress::PerHead(
+                (0..fitnesses.len())
+                    .map(|index| {
+                        combine(
+                            trials
+                                .iter()
+                                .filter_map(|trial| trial.head_fitnesses()?.get(index))
+                                .cloned()
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+                behavior.map(Behavior::from),
+            )
+        } else {
+            let fitnesses: Vec<f64> = trials
+                .iter()
+                .filter_map(|trial| trial.raw_fitness(setup))
+                .map(|fitness| fitness.value())
+                .collect();
+            match (fitnesses.is_empty(), behavior) {
+                (false, Some(behavior)) => Progress::new(combine(fitnesses), behavior),
+                (false, None) => Progress::fitness(combine(fitnesses)),
+                (true, Some(behavior)) => Progress::novelty(behavior),
+                (true, None) => Progress::Empty,
+            }
+        };
+
+        let viable = trials.iter().all(Progress::is_viable);
+        let combined = match trials
+            .iter()
+            .map(Progress::is_solution)
+            .collect::<Option<Vec<&Individual>>>()
+        {
+            Some(solutions) => combined.solved(solutions[0].clone()),
+            None => combined,
+        };
+        combined.viable(viable)
+    }
+
+    // copy kept by the evaluation cache, custom results keep their fitness and behavior only
+    pub fn cached(&self) -> Self {
+        match self {
+            Progress::Empty => Progress::Empty,
+            Progress::Novelty(behavior) => Progress::Novelty(behavior.clone()),
+            Progress::Fitness(fitness) => Progress::Fitness(*fitness),
+            Progress::Status(fitness, behavior) => Progress::Status(*fitness, behavior.clone()),
+            Progress::Solution(progress, solution) => {
+                Progress::Solution(Box::new(progress.cached()), solution.clone())
+            }
+            Progress::MultiObjective(objectives, behavior) => {
+                Progress::MultiObjective(objectives.clone(), behavior.clone())
+            }
+            Progress::PerHead(fitnesses, behavior) => {
+                Progress::PerHead(fitnesses.clone(), behavior.clone())
+            }
+            Progress::Unviable(progress) => Progress::Unviable(Box::new(progress.cached())),
+            Progress::Custom(custom) => {
+                match (
+                    custom.fitness().map(Raw::fitness),
+                    custom.behavior().cloned(),
+                ) {
+                    (Some(fitness), Some(behavior)) => Progress::Status(fitness, behavior),
+                    (Some(fitness), None) => Progress::Fitness(fitness),
+                    (None, Some(behavior)) => Progress::Novelty(behavior),
+                    (None, None) => Progress::Empty,
+                }
+            }
         }
     }
 
     pub fn is_solution(&self) -> Option<&Individual> {
         match self {
-            Progress::Solution(_, _, individual) => Some(individual),
+            Progress::Solution(_, individual) => Some(individual),
+            Progress::Custom(custom) => custom.solution(),
+            Progress::Unviable(progress) => progress.is_solution(),
             _ => None,
         }
     }
 }
+
+fn scalarize(objectives: &[Objective], weights: &HashMap<String, f64>) -> Raw<Fitness> {
+    Raw::fitness(
+        objectives
+            .iter()
+            .map(|objective| objective.value * weights.get(&objective.name).unwrap_or(&1.0))
+            .sum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Progress;
+    use crate::{
+        individual::{scores::ScoreValue, Individual},
+        parameters::{HeadAggregation, Setup, TrialBehavior, TrialFitness},
+    };
+
+    #[test]
+    fn scalarize_objectives() {
+        let progress = Progress::multi_objective(vec![("distance", 2.0), ("energy", -1.0)], None);
+
+        let mut weights = HashMap::new();
+        weights.insert("energy".to_owned(), 0.5);
+
+        assert_eq!(
+            progress.raw_fitness(&Setup::default()).unwrap().value(),
+            1.0
+        );
+        assert_eq!(progress.scalarized_fitness(&weights).unwrap().value(), 1.5);
+
+        let setup = Setup {
+            objective_weights: weights,
+            ..Default::default()
+        };
+        assert_eq!(progress.raw_fitness(&setup).unwrap().value(), 1.5);
+
+        // objectives of trials are combined by name
+        let combined = Progress::from_trials(
+            vec![
+                progress,
+                Progress::multi_objective(vec![("energy", -3.0), ("distance", 4.0)], None),
+            ],
+            &setup,
+        );
+        assert_eq!(combined.raw_fitness(&setup).unwrap().value(), 2.0);
+    }
+
+    #[test]
+    fn aggregate_heads() {
+        let progress = Progress::per_head(vec![3.0, 1.0], None);
+
+        let mut setup = Setup::default();
+        assert_eq!(progress.raw_fitness(&setup).unwrap().value(), 4.0);
+
+        setup.head_aggregation = HeadAggregation::Minimum;
+        assert_eq!(progress.raw_fitness(&setup).unwrap().value(), 1.0);
+
+        let solution = progress.solved(Individual::default());
+        assert_eq!(solution.head_fitnesses().unwrap(), &[3.0, 1.0]);
+        assert_eq!(solution.raw_fitness(&setup).unwrap().value(), 1.0);
+    }
+
+    #[test]
+    fn combine_trials() {
+        let trials = || {
+            vec![
+                Progress::new(1.0, vec![0.0, 1.0]),
+                Progress::new(5.0, vec![1.0, 1.0]),
+                Progress::new(3.0, vec![2.0, 1.0]).viable(false),
+            ]
+        };
+
+        let mut setup = Setup::default();
+        let combined = Progress::from_trials(trials(), &setup);
+        assert_eq!(combined.raw_fitness(&setup).unwrap().value(), 3.0);
+        assert_eq!(combined.behavior().unwrap().values(), vec![1.0, 1.0]);
+        assert!(!combined.is_viable());
+
+        setup.trial_fitness = TrialFitness::Minimum;
+        setup.trial_behavior = TrialBehavior::Concatenation;
+        let combined = Progress::from_trials(trials(), &setup);
+        assert_eq!(combined.raw_fitness(&setup).unwrap().value(), 1.0);
+        assert_eq!(combined.behavior().unwrap().len(), 6);
+
+        let combined = Progress::from_trials(
+            vec![
+                Progress::per_head(vec![1.0, 4.0], None),
+                Progress::per_head(vec![3.0, 2.0], None),
+                Progress::per_head(vec![2.0, 0.0], None),
+            ],
+            &Setup {
+                trial_fitness: TrialFitness::Median,
+                ..Default::default()
+            },
+        );
+        assert_eq!(combined.head_fitnesses().unwrap(), &[2.0, 2.0]);
+    }
+}
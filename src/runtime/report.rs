@@ -0,0 +1,30 @@
+use std::{fs::File, io::BufWriter, time::Duration};
+
+use serde::Serialize;
+
+use crate::{error::NeatError, individual::Individual, utility::statistics::Statistics};
+
+use super::checkpoint::Archive;
+
+// summary of a run whenever it is stopped, see Runtime::finalize
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    // the accepted solution if there is one, otherwise the individual with the highest raw fitness evaluated so far
+    pub best: Option<Individual>,
+    pub solved: bool,
+    pub generations: usize,
+    // calls of the progress function including trials, retries and solution verification, cache hits excluded
+    pub evaluations: usize,
+    // since the runtime was created, zero on wasm
+    pub wall_time: Duration,
+    pub archive: Archive,
+    // of the last finished generation
+    pub statistics: Statistics,
+}
+
+impl RunReport {
+    pub fn save(&self, path: &str) -> Result<(), NeatError> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+}
@@ -4,7 +4,6 @@ use favannat::network::{EdgeLike, NetLike, NodeLike, Recurrent};
 
 use crate::{
     genes::{
-        activations,
         connections::{Connection, FeedForward},
         nodes::{Input, Node, Output},
         Activation, Id, Weight,
@@ -12,24 +11,21 @@ use crate::{
     Individual,
 };
 
+// NodeLike has no notion of aggregation, favannat sums the inputs of every node,
+// use inference::Controller for genomes evolving other aggregations,
+// feed forward fabrication reads the genes as they are and needs an expressed individual, see Genome::expressed
 impl NodeLike for Node {
     fn id(&self) -> usize {
         self.id().0
     }
     fn activation(&self) -> fn(f64) -> f64 {
-        match self.1 {
-            Activation::Linear => activations::LINEAR,
-            Activation::Sigmoid => activations::SIGMOID,
-            Activation::Gaussian => activations::GAUSSIAN,
-            Activation::Tanh => activations::TANH,
-            Activation::Step => activations::STEP,
-            Activation::Sine => activations::SINE,
-            Activation::Cosine => activations::COSINE,
-            Activation::Inverse => activations::INVERSE,
-            Activation::Absolute => activations::ABSOLUTE,
-            Activation::Relu => activations::RELU,
-            Activation::Squared => activations::SQUARED,
-        }
+        self.1.function()
+    }
+    fn bias(&self) -> f64 {
+        self.effective_bias()
+    }
+    fn response(&self) -> f64 {
+        self.effective_response()
     }
 }
 
@@ -41,7 +37,7 @@ impl EdgeLike for Connection {
         self.output().0
     }
     fn weight(&self) -> f64 {
-        (self.1).0
+        self.1.value()
     }
 }
 
@@ -50,6 +46,10 @@ impl NetLike<Node, Connection> for Individual {
         self.genome.nodes().collect()
     }
     fn edges(&self) -> Vec<&Connection> {
+        debug_assert!(
+            self.parallel.is_empty() && self.disabled.is_empty(),
+            "favannat evaluates expressed individuals only"
+        );
         self.feed_forward.as_sorted_vec()
     }
     fn inputs(&self) -> Vec<&Node> {
@@ -63,22 +63,27 @@ impl NetLike<Node, Connection> for Individual {
 impl Recurrent<Node, Connection> for Individual {
     type Net = Self;
 
+    // unrolls the expressed genome, i.e. the network the runtime evaluates
     fn unroll(&self) -> Self::Net {
-        let mut unrolled_genome = self.clone();
+        let mut unrolled_genome = Individual {
+            genome: self.genome.expressed(),
+            ..self.clone()
+        };
 
         // maps recurrent connection input to wrapped actual input
         let mut unroll_map: HashMap<Id, Id> = HashMap::new();
         let mut tmp_ids = (0..usize::MAX).rev();
 
-        for recurrent_connection in self.recurrent.as_sorted_vec() {
+        let recurrent = unrolled_genome.recurrent.clone();
+        for recurrent_connection in recurrent.as_sorted_vec() {
             let recurrent_input = unroll_map
                 .entry(recurrent_connection.input())
                 .or_insert_with(|| {
                     let wrapper_input_id = Id(tmp_ids.next().unwrap());
 
-                    let wrapper_input_node = Input(Node(wrapper_input_id, Activation::Linear));
+                    let wrapper_input_node = Input(Node::new(wrapper_input_id, Activation::Linear));
                     let wrapper_output_node =
-                        Output(Node(Id(tmp_ids.next().unwrap()), Activation::Linear));
+                        Output(Node::new(Id(tmp_ids.next().unwrap()), Activation::Linear));
 
                     // used to carry value into next evaluation
                     let outward_wrapping_connection = FeedForward(Connection(
@@ -113,6 +118,10 @@ impl Recurrent<Node, Connection> for Individual {
     }
 
     fn recurrent_edges(&self) -> Vec<&Connection> {
+        debug_assert!(
+            self.disabled.is_empty(),
+            "favannat evaluates expressed individuals only"
+        );
         self.recurrent.as_sorted_vec()
     }
 }
@@ -121,7 +130,56 @@ impl Recurrent<Node, Connection> for Individual {
 mod tests {
     use favannat::network::Recurrent;
 
-    use crate::{Individual, Parameters};
+    use crate::{
+        genes::{
+            connections::{Connection, FeedForward, Recurrent as RecurrentConnection},
+            nodes::{Hidden, Input, Node, Output},
+            Activation, Id, Weight,
+        },
+        individual::genome::Genome,
+        Individual, Parameters,
+    };
+
+    #[test]
+    fn unroll_self_connection() {
+        let individual = Individual {
+            genome: Genome {
+                inputs: vec![Input(Node::new(Id(0), Activation::Linear))]
+                    .into_iter()
+                    .collect(),
+                outputs: vec![Output(Node::new(Id(1), Activation::Linear))]
+                    .into_iter()
+                    .collect(),
+                hidden: vec![Hidden(Node::new(Id(2), Activation::Tanh))]
+                    .into_iter()
+                    .collect(),
+                feed_forward: vec![
+                    FeedForward(Connection(Id(0), Weight(1.0), Id(2))),
+                    FeedForward(Connection(Id(2), Weight(1.0), Id(1))),
+                ]
+                .into_iter()
+                .collect(),
+                recurrent: vec![RecurrentConnection(Connection(Id(2), Weight(0.5), Id(2)))]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let unrolled = individual.unroll();
+
+        // one wrapper input and output carry the value of the self connection
+        assert_eq!(unrolled.inputs.len(), 2);
+        assert_eq!(unrolled.outputs.len(), 2);
+        // outward and inward wrapping connection
+        assert_eq!(unrolled.feed_forward.len(), 4);
+        // no node connects to itself after unrolling
+        assert!(unrolled
+            .feed_forward
+            .iter()
+            .all(|connection| connection.input() != connection.output()));
+    }
 
     #[test]
     fn unroll_genome() {
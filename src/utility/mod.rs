@@ -1,4 +1,6 @@
 mod favannat_impl;
 pub mod gym;
 pub mod rng;
+pub mod snapshot;
 pub mod statistics;
+pub mod time;
@@ -1,6 +1,16 @@
 use rand::{prelude::SmallRng, Rng, SeedableRng};
 use rand_distr::{Distribution, Normal};
 
+use crate::parameters::WeightInitialization;
+
+// seed shared by all evaluations of a generation, derived from the master seed by splitmix64
+pub fn generation_seed(seed: u64, generation: usize) -> u64 {
+    let mut z = seed.wrapping_add((generation as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
 #[derive(Debug)]
 pub struct NeatRng {
     pub small: SmallRng,
@@ -16,6 +26,11 @@ impl NeatRng {
         }
     }
 
+    pub fn set_weight_std_dev(&mut self, std_dev: f64) {
+        self.weight_distribution =
+            Normal::new(0.0, std_dev).expect("could not create weight distribution");
+    }
+
     pub fn gamble(&mut self, chance: f64) -> bool {
         self.small.gen::<f64>() < chance
     }
@@ -23,4 +38,35 @@ impl NeatRng {
     pub fn weight_perturbation(&mut self) -> f64 {
         self.weight_distribution.sample(&mut self.small)
     }
+
+    // weight of a new connection into a node with given number of incoming connections, itself included
+    pub fn initial_weight(&mut self, initialization: WeightInitialization, fan_in: usize) -> f64 {
+        match initialization {
+            WeightInitialization::Perturbation => self.weight_perturbation(),
+            WeightInitialization::Uniform(lower, upper) => {
+                lower + self.small.gen::<f64>() * (upper - lower)
+            }
+            WeightInitialization::Gaussian(std_dev) => self.perturbation(std_dev),
+            WeightInitialization::Xavier => self.perturbation((1.0 / fan_in.max(1) as f64).sqrt()),
+        }
+    }
+
+    // sample from zero centered gaussian with given standard deviation
+    pub fn perturbation(&mut self, std_dev: f64) -> f64 {
+        Normal::new(0.0, std_dev)
+            .expect("could not create perturbation distribution")
+            .sample(&mut self.small)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generation_seed;
+
+    #[test]
+    fn derive_generation_seeds() {
+        assert_eq!(generation_seed(42, 3), generation_seed(42, 3));
+        assert_ne!(generation_seed(42, 3), generation_seed(42, 4));
+        assert_ne!(generation_seed(42, 3), generation_seed(43, 3));
+    }
 }
@@ -0,0 +1,92 @@
+use std::{env, fs, path::Path};
+
+use serde::Serialize;
+
+use crate::error::NeatError;
+
+// set to rewrite stored snapshots instead of comparing against them
+pub const UPDATE_SNAPSHOTS: &str = "UPDATE_SNAPSHOTS";
+
+// pretty json with sorted object keys and genes ordered by id,
+// i.e. genomes or checkpoints of a seeded run serialize to identical text every time
+pub fn canonical_json<T: Serialize>(value: &T) -> Result<String, NeatError> {
+    // objects of serde_json::Value keep their keys sorted, which orders hash maps as well
+    let value = serde_json::to_value(value)?;
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+// golden file test, the snapshot at path is written if missing or UPDATE_SNAPSHOTS is set
+pub fn assert_snapshot<T: Serialize>(path: &str, value: &T) {
+    let actual = canonical_json(value).expect("snapshot value should serialize");
+
+    if env::var_os(UPDATE_SNAPSHOTS).is_some() || !Path::new(path).exists() {
+        fs::write(path, &actual).expect("snapshot should be writable");
+        return;
+    }
+
+    let expected = fs::read_to_string(path).expect("snapshot should be readable");
+
+    // editors may add a trailing newline
+    if expected.trim_end() != actual.trim_end() {
+        let expected_lines: Vec<&str> = expected.lines().collect();
+        let actual_lines: Vec<&str> = actual.lines().collect();
+        let line = (0..expected_lines.len().max(actual_lines.len()))
+            .find(|&index| expected_lines.get(index) != actual_lines.get(index))
+            .unwrap_or(0);
+
+        panic!(
+            "snapshot {} differs at line {}\n  expected: {}\n  actual:   {}\nrerun with {}=1 to accept the new output",
+            path,
+            line + 1,
+            expected_lines.get(line).unwrap_or(&""),
+            actual_lines.get(line).unwrap_or(&""),
+            UPDATE_SNAPSHOTS
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::{assert_snapshot, canonical_json};
+    use crate::{
+        genes::{
+            connections::{Connection, FeedForward},
+            Id, Weight,
+        },
+        individual::genome::Genome,
+    };
+
+    fn genome(ids: impl Iterator<Item = usize>) -> Genome {
+        Genome {
+            feed_forward: ids
+                .map(|id| FeedForward(Connection(Id(id), Weight(id as _), Id(id + 1))))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn canonical_gene_order() {
+        let ascending = canonical_json(&genome(0..32)).unwrap();
+        let descending = canonical_json(&genome((0..32).rev())).unwrap();
+
+        assert_eq!(ascending, descending);
+        // keys sorted, not in order of declaration
+        assert!(ascending.find("\"parallel\"").unwrap() < ascending.find("\"recurrent\"").unwrap());
+    }
+
+    #[test]
+    fn compare_against_snapshot() {
+        let path = env::temp_dir().join("novel_set_neat_snapshot_test.json");
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+
+        // first run records the snapshot, second one compares
+        assert_snapshot(path, &genome(0..4));
+        assert_snapshot(path, &genome((0..4).rev()));
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,13 +1,64 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    time::Duration,
+};
+
 use serde::Serialize;
 
-use crate::individual::Individual;
+use crate::{error::NeatError, individual::Individual};
 
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct Statistics {
     pub population: PopulationStatistics,
     pub num_generation: usize,
+    pub seed: u64,
+    // seed the generation was evaluated with, see EvaluationContext
+    pub generation_seed: u64,
     pub milliseconds_elapsed_evaluation: u128,
     pub time_stamp: u64,
+    // stays empty as long as the population is not speciated
+    pub species: Vec<SpeciesStatistics>,
+    // statistics of every island, population holds those of the island with the best individual
+    pub islands: Vec<PopulationStatistics>,
+    // lookups of the generation, see parameters.setup.evaluation_cache_size
+    pub evaluation_cache: CacheStatistics,
+    // a solution was reported but failed verification, see parameters.setup.solution_verification
+    pub rejected_solution: bool,
+    // calls of the progress function since the runtime was created, see parameters.setup.max_evaluations
+    pub total_evaluations: usize,
+    pub champion: ChampionStatistics,
+    // writing the run artifacts of the generation failed, see Runtime::set_artifact_directory
+    pub artifact_error: Option<String>,
+}
+
+// individual with the highest raw fitness of the run so far, unaffected by islands and extinction
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChampionStatistics {
+    pub id: usize,
+    pub fitness: f64,
+    pub generations_since_improvement: usize,
+    // gain of raw fitness when the champion was last replaced, zero for the first one
+    pub improvement: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheStatistics {
+    // includes duplicates of genomes evaluated in the same generation
+    pub hits: usize,
+    pub misses: usize,
+    // cached evaluations after the generation
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpeciesStatistics {
+    pub size: usize,
+    pub best_fitness: f64,
+    pub age: usize,
+    // generations without improvement of best fitness
+    pub stagnation: usize,
+    pub representative_id: usize,
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
@@ -22,6 +73,8 @@ pub struct FitnessStatisitcs {
     pub normalized_maximum: f64,
     pub normalized_minimum: f64,
     pub normalized_average: f64,
+    // all values were equal and got normalized uniformly
+    pub degenerate: bool,
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
@@ -35,13 +88,224 @@ pub struct NoveltyStatisitcs {
     pub normalized_maximum: f64,
     pub normalized_minimum: f64,
     pub normalized_average: f64,
+    // all values were equal and got normalized uniformly
+    pub degenerate: bool,
+    // effective k, see parameters.setup.novelty_neighbors_scaling
+    pub nearest_neighbors: usize,
+    // archive entries dropped this generation, see parameters.setup.archive_decay
+    pub archive_evictions: usize,
+    // archive entries added this generation and the current threshold, see parameters.setup.archive_addition
+    pub archive_additions: usize,
+    pub archive_threshold: f64,
 }
+// wall time spent in each phase of a generation
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhaseTimings {
+    pub evaluation: Duration,
+    pub fitness_assignment: Duration,
+    pub behavior_assignment: Duration,
+    pub novelty: Duration,
+    pub selection: Duration,
+    // offspring or refill as a whole, crossover and mutation are summed over all threads
+    pub reproduction: Duration,
+    pub crossover: Duration,
+    pub mutation: Duration,
+    pub statistics: Duration,
+    // archive hooks of every island
+    pub archive_hook: Duration,
+    // whole generation from the start of evaluation, set by the runtime
+    pub total: Duration,
+}
+
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct PopulationStatistics {
     pub milliseconds_elapsed_reproducing: u128,
+    pub timings: PhaseTimings,
     pub top_performer: Individual,
     pub age_maximum: usize,
     pub age_average: f64,
     pub fitness: FitnessStatisitcs,
     pub novelty: NoveltyStatisitcs,
+    pub complexity: ComplexityStatistics,
+    // structural mutations skipped for exceeding parameters.mutation.max_hidden_nodes or max_connections
+    pub capped_mutations: usize,
+    // individuals sharing their topology with another one counted before, see Genome::structural_hash
+    pub duplicate_topologies: usize,
+    // extra mutations of identical offspring, see parameters.mutation.duplicate_offspring_retries
+    pub duplicate_offspring_retries: usize,
+    pub objectives: Vec<ObjectiveStatistics>,
+    // generations since the best raw fitness improved
+    pub stagnation: usize,
+    // population was refilled with fresh genomes this generation, see parameters.setup.extinction
+    pub extinction: bool,
+    // evaluated individuals ranked by score, see parameters.setup.snapshot
+    pub snapshot: Vec<Individual>,
+}
+
+// raw values of a named objective, see Progress::MultiObjective
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ObjectiveStatistics {
+    pub name: String,
+    pub minimum: f64,
+    pub average: f64,
+    pub maximum: f64,
+}
+
+impl ObjectiveStatistics {
+    pub fn new(name: String, values: &[f64]) -> Self {
+        Self {
+            name,
+            minimum: values.iter().cloned().fold(f64::INFINITY, f64::min),
+            average: values.iter().sum::<f64>() / values.len() as f64,
+            maximum: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CountStatistics {
+    pub minimum: usize,
+    pub average: f64,
+    pub maximum: usize,
+}
+
+impl CountStatistics {
+    pub fn new(counts: impl Iterator<Item = usize>) -> Self {
+        let mut minimum = usize::MAX;
+        let mut maximum = 0;
+        let mut sum = 0;
+        let mut total = 0;
+
+        for count in counts {
+            minimum = minimum.min(count);
+            maximum = maximum.max(count);
+            sum += count;
+            total += 1;
+        }
+
+        if total == 0 {
+            return Self::default();
+        }
+
+        Self {
+            minimum,
+            average: sum as f64 / total as f64,
+            maximum,
+        }
+    }
+}
+
+// genome sizes across the population, to follow complexification
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ComplexityStatistics {
+    pub hidden_nodes: CountStatistics,
+    pub feed_forward_connections: CountStatistics,
+    pub recurrent_connections: CountStatistics,
+    // kept but not expressed, see parameters.mutation.toggle_connection_chance
+    pub disabled_connections: CountStatistics,
+}
+
+// statistics of every completed generation, see Runtime::record_history
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct History(pub Vec<Statistics>);
+
+const CSV_HEADER: &str = "generation,time_stamp,milliseconds_elapsed_evaluation,\
+fitness_raw_maximum,fitness_raw_minimum,fitness_raw_average,fitness_raw_std_dev,\
+novelty_raw_maximum,novelty_raw_minimum,novelty_raw_average,\
+age_maximum,age_average,hidden_nodes_average,hidden_nodes_maximum,\
+feed_forward_connections_average,recurrent_connections_average,\
+stagnation,extinction,top_performer_id,\
+milliseconds_novelty,milliseconds_selection,milliseconds_reproduction,\
+milliseconds_statistics,milliseconds_total,generation_seed";
+
+impl History {
+    pub fn save_json(&self, path: &str) -> Result<(), NeatError> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+
+    // scalar values only, one row per generation
+    pub fn save_csv(&self, path: &str) -> Result<(), NeatError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer)?;
+        Ok(writer.flush()?)
+    }
+
+    fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", CSV_HEADER)?;
+
+        for statistics in &self.0 {
+            let population = &statistics.population;
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                statistics.num_generation,
+                statistics.time_stamp,
+                statistics.milliseconds_elapsed_evaluation,
+                population.fitness.raw_maximum,
+                population.fitness.raw_minimum,
+                population.fitness.raw_average,
+                population.fitness.raw_std_dev,
+                population.novelty.raw_maximum,
+                population.novelty.raw_minimum,
+                population.novelty.raw_average,
+                population.age_maximum,
+                population.age_average,
+                population.complexity.hidden_nodes.average,
+                population.complexity.hidden_nodes.maximum,
+                population.complexity.feed_forward_connections.average,
+                population.complexity.recurrent_connections.average,
+                population.stagnation,
+                population.extinction,
+                population.top_performer.id,
+                population.timings.novelty.as_millis(),
+                population.timings.selection.as_millis(),
+                population.timings.reproduction.as_millis(),
+                population.timings.statistics.as_millis(),
+                population.timings.total.as_millis(),
+                statistics.generation_seed,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CountStatistics, History, Statistics, CSV_HEADER};
+
+    #[test]
+    fn count_statistics() {
+        let statistics = CountStatistics::new(vec![1, 2, 6].into_iter());
+
+        assert_eq!(statistics.minimum, 1);
+        assert_eq!(statistics.average, 3.0);
+        assert_eq!(statistics.maximum, 6);
+    }
+
+    #[test]
+    fn history_to_csv() {
+        let history = History(vec![
+            Statistics {
+                num_generation: 1,
+                ..Default::default()
+            },
+            Statistics {
+                num_generation: 2,
+                ..Default::default()
+            },
+        ]);
+
+        let mut csv = Vec::new();
+        history.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[2].starts_with("2,"));
+        assert_eq!(lines[1].split(',').count(), CSV_HEADER.split(',').count());
+    }
 }